@@ -1,7 +1,6 @@
 use chrono::{Duration, Utc};
 use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, TokenData, Validation};
 use serde::{Deserialize, Serialize};
-use std::env;
 use uuid::Uuid;
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -12,25 +11,15 @@ pub struct Claims {
     pub jti: String, // JWT ID (session ID)
 }
 
-#[derive(Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct AuthConfig {
+    /// Secret used to sign session tokens
     pub jwt_secret: String,
+    /// Hours before a session token expires
     pub token_expiry_hours: i64,
 }
 
-impl AuthConfig {
-    pub fn from_env() -> Self {
-        Self {
-            jwt_secret: env::var("JWT_SECRET")
-                .unwrap_or_else(|_| "your-secret-key".to_string()),
-            token_expiry_hours: env::var("TOKEN_EXPIRY_HOURS")
-                .unwrap_or_else(|_| "24".to_string())
-                .parse()
-                .unwrap_or(24),
-        }
-    }
-}
-
 #[derive(Clone)]
 pub struct AuthService {
     config: AuthConfig,
@@ -0,0 +1,168 @@
+//! Opaque cursors for keyset pagination of task listings.
+//!
+//! A cursor records the listing's ordering, which way to page and the position
+//! of the task at the edge of the current page, encoded as URL-safe base64 JSON.
+//! Clients should treat it as an opaque string.
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::Task;
+use crate::repository::{Keyset, SortKey, TaskSort};
+
+/// Which way a cursor pages from its position
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// Tasks after the position, in listing order
+    Next,
+    /// Tasks before the position
+    Previous,
+}
+
+/// A decoded pagination cursor
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cursor {
+    pub sort: TaskSort,
+    pub direction: Direction,
+    pub position: Keyset,
+}
+
+/// What is actually serialized; short names keep cursors compact
+#[derive(Serialize, Deserialize)]
+struct Wire {
+    s: String,
+    p: bool,
+    k: Vec<SortKey>,
+    i: Uuid,
+}
+
+impl Cursor {
+    /// Cursor for the page following `task` in a listing ordered by `sort`
+    pub fn next(sort: &TaskSort, task: &Task) -> Self {
+        Self {
+            sort: sort.clone(),
+            direction: Direction::Next,
+            position: Keyset::of(task, sort),
+        }
+    }
+
+    /// Cursor for the page preceding `task` in a listing ordered by `sort`
+    pub fn previous(sort: &TaskSort, task: &Task) -> Self {
+        Self {
+            sort: sort.clone(),
+            direction: Direction::Previous,
+            position: Keyset::of(task, sort),
+        }
+    }
+
+    pub fn encode(&self) -> String {
+        let wire = Wire {
+            s: self.sort.to_param(),
+            p: self.direction == Direction::Previous,
+            k: self.position.keys.clone(),
+            i: self.position.id,
+        };
+        // Serializing plain strings, bools and a UUID cannot fail
+        URL_SAFE_NO_PAD.encode(serde_json::to_vec(&wire).unwrap_or_default())
+    }
+
+    /// Parse a cursor from the `cursor` query parameter, or `None` if it is malformed
+    pub fn decode(encoded: &str) -> Option<Self> {
+        let bytes = URL_SAFE_NO_PAD.decode(encoded).ok()?;
+        let wire: Wire = serde_json::from_slice(&bytes).ok()?;
+
+        let sort = TaskSort::parse(&wire.s).ok()?;
+        let valid = sort.columns.len() == wire.k.len()
+            && sort
+                .columns
+                .iter()
+                .zip(&wire.k)
+                .all(|(column, key)| column.field.accepts(key));
+        if !valid {
+            return None;
+        }
+
+        Some(Self {
+            sort,
+            direction: if wire.p {
+                Direction::Previous
+            } else {
+                Direction::Next
+            },
+            position: Keyset {
+                keys: wire.k,
+                id: wire.i,
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::SortOrder;
+    use crate::repository::TaskSortField;
+    use chrono::Utc;
+
+    fn task(title: &str) -> Task {
+        Task {
+            id: Uuid::now_v7(),
+            title: title.to_string(),
+            description: None,
+            completed: false,
+            user_id: Uuid::nil(),
+            created_at: Some(Utc::now()),
+            updated_at: Some(Utc::now()),
+            due_at: None,
+            due_all_day: false,
+            start_at: None,
+            priority: Default::default(),
+            project_id: None,
+            parent_id: None,
+            auto_complete: false,
+            recurrence: None,
+            position: "i".to_string(),
+            tags: Vec::new(),
+            subtasks: None,
+            blocked: false,
+            relevance: None,
+            snippet: None,
+            description_html: None,
+        }
+    }
+
+    #[test]
+    fn test_cursor_round_trips() {
+        let sort = TaskSort::parse("-completed,title").unwrap();
+        for cursor in [
+            Cursor::next(&sort, &task("Buy milk")),
+            Cursor::previous(&TaskSort::default(), &task("Walk dog")),
+        ] {
+            assert_eq!(Cursor::decode(&cursor.encode()), Some(cursor));
+        }
+    }
+
+    #[test]
+    fn test_malformed_cursors_are_rejected() {
+        assert_eq!(Cursor::decode("not a cursor"), None);
+        assert_eq!(Cursor::decode(""), None);
+
+        // A key of the wrong type for the sort column
+        let wire = r#"{"s":"-completed","p":false,"k":[{"text":"x"}],"i":"00000000-0000-0000-0000-000000000000"}"#;
+        assert_eq!(Cursor::decode(&URL_SAFE_NO_PAD.encode(wire)), None);
+
+        // Fewer keys than sort columns
+        let wire = r#"{"s":"completed,title","p":false,"k":[{"bool":true}],"i":"00000000-0000-0000-0000-000000000000"}"#;
+        assert_eq!(Cursor::decode(&URL_SAFE_NO_PAD.encode(wire)), None);
+
+        // A column that cannot be sorted on
+        let wire = r#"{"s":"password_hash","p":false,"k":[{"text":"x"}],"i":"00000000-0000-0000-0000-000000000000"}"#;
+        assert_eq!(Cursor::decode(&URL_SAFE_NO_PAD.encode(wire)), None);
+
+        // Relevance depends on the query, so it cannot be resumed
+        let sort = TaskSort::by(TaskSortField::Relevance, SortOrder::Desc);
+        assert_eq!(Cursor::decode(&Cursor::next(&sort, &task("Buy milk")).encode()), None);
+    }
+}
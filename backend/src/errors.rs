@@ -9,16 +9,25 @@ use serde_json::json;
 #[derive(Debug)]
 pub enum AppError {
     SqlxError(sqlx::Error),
+    /// A stored row could not be decoded into its model
+    CorruptedRow(sqlx::Error),
     NotFound,
     ValidationError(String),
     NoFieldsToUpdate,
+    /// The request clashes with the current state of a resource
+    Conflict(String),
     Unauthorized(String),
     InternalServerError(String),
 }
 
 impl From<sqlx::Error> for AppError {
     fn from(error: sqlx::Error) -> Self {
-        AppError::SqlxError(error)
+        match error {
+            sqlx::Error::ColumnDecode { .. } | sqlx::Error::Decode(_) => {
+                AppError::CorruptedRow(error)
+            }
+            error => AppError::SqlxError(error),
+        }
     }
 }
 
@@ -37,6 +46,13 @@ impl IntoResponse for AppError {
                     ),
                 }
             }
+            AppError::CorruptedRow(error) => {
+                tracing::error!("Corrupted row: {}", error);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Internal server error".to_string(),
+                )
+            }
             AppError::NotFound => {
                 tracing::warn!("Resource not found");
                 (StatusCode::NOT_FOUND, "Resource not found".to_string())
@@ -52,6 +68,10 @@ impl IntoResponse for AppError {
                     "No fields to update".to_string(),
                 )
             }
+            AppError::Conflict(msg) => {
+                tracing::warn!("Conflict: {}", msg);
+                (StatusCode::CONFLICT, msg)
+            }
             AppError::Unauthorized(msg) => {
                 tracing::warn!("Unauthorized: {}", msg);
                 (StatusCode::UNAUTHORIZED, msg)
@@ -62,10 +82,40 @@ impl IntoResponse for AppError {
             }
         };
 
-        let body = Json(json!({
-            "error": error_message,
-        }));
+        let body = match crate::middleware::current_request_id() {
+            Some(request_id) => Json(json!({
+                "error": error_message,
+                "request_id": request_id,
+            })),
+            None => Json(json!({
+                "error": error_message,
+            })),
+        };
 
         (status, body).into_response()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_errors_are_reported_as_corrupted_rows() {
+        let error = sqlx::Error::ColumnDecode {
+            index: "\"id\"".to_string(),
+            source: "invalid length".into(),
+        };
+        let error = AppError::from(error);
+        assert!(matches!(error, AppError::CorruptedRow(_)));
+        assert_eq!(
+            error.into_response().status(),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+
+        assert!(matches!(
+            AppError::from(sqlx::Error::RowNotFound),
+            AppError::SqlxError(sqlx::Error::RowNotFound)
+        ));
+    }
+}
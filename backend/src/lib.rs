@@ -1,7 +1,20 @@
 // Expose modules for integration tests
 pub mod auth;
+pub mod config;
+pub mod cursor;
 pub mod db;
 pub mod errors;
+pub mod markdown;
+pub mod metrics;
 pub mod middleware;
 pub mod models;
+pub mod query;
+pub mod rank;
+pub mod recurrence;
+pub mod redact;
+pub mod reminders;
+pub mod repository;
 pub mod routes;
+pub mod schedule;
+pub mod search;
+pub mod telemetry;
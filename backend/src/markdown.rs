@@ -0,0 +1,53 @@
+//! Rendering of task descriptions.
+//!
+//! Descriptions are stored as the Markdown the client sent. Raw HTML is
+//! allowed in Markdown, so the rendered output is run through an allowlist
+//! sanitizer before it is returned; scripts, event handlers and `javascript:`
+//! links never reach the client.
+
+use pulldown_cmark::{html, Options, Parser};
+
+/// `markdown` as sanitized HTML
+pub fn render_html(markdown: &str) -> String {
+    let options = Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TABLES | Options::ENABLE_TASKLISTS;
+    let mut rendered = String::with_capacity(markdown.len() * 3 / 2);
+    html::push_html(&mut rendered, Parser::new_ext(markdown, options));
+
+    // Keep task list checkboxes, but no other kind of input
+    ammonia::Builder::default()
+        .add_tags(["input"])
+        .add_tag_attributes("input", ["type", "checked", "disabled"])
+        .attribute_filter(|element, attribute, value| match (element, attribute) {
+            ("input", "type") if value != "checkbox" => None,
+            _ => Some(value.into()),
+        })
+        .clean(&rendered)
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_renders_markdown() {
+        assert_eq!(
+            render_html("**Ship** it:\n\n- [x] tests\n- ~~docs~~"),
+            "<p><strong>Ship</strong> it:</p>\n<ul>\n<li><input disabled=\"\" type=\"checkbox\" checked=\"\">\ntests</li>\n<li><del>docs</del></li>\n</ul>\n"
+        );
+    }
+
+    #[test]
+    fn test_strips_unsafe_html() {
+        let html = render_html(
+            "[link](javascript:alert(1)) <img src=x onerror=alert(1)>\n\n<script>alert(1)</script>",
+        );
+        assert!(!html.contains("<script"), "{}", html);
+        assert!(!html.contains("javascript:"), "{}", html);
+        assert!(!html.contains("onerror"), "{}", html);
+        assert!(html.contains("<a rel=\"noopener noreferrer\">link</a>"), "{}", html);
+
+        let html = render_html("<input type=\"text\" value=\"x\">");
+        assert!(!html.contains("text"), "{}", html);
+    }
+}
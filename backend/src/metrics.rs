@@ -0,0 +1,205 @@
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    routing::get,
+    Router,
+};
+use metrics_exporter_prometheus::{Matcher, PrometheusBuilder, PrometheusHandle};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+
+use crate::db::AppState;
+use crate::errors::AppError;
+use crate::repository::{Repository, TaskFilter};
+
+/// Histogram buckets (in seconds) for HTTP request latency
+const LATENCY_BUCKETS: &[f64] = &[
+    0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+static PROMETHEUS_HANDLE: OnceLock<PrometheusHandle> = OnceLock::new();
+
+/// Install the Prometheus recorder (once per process) and return its handle
+pub fn install_recorder() -> PrometheusHandle {
+    PROMETHEUS_HANDLE
+        .get_or_init(|| {
+            PrometheusBuilder::new()
+                .set_buckets_for_metric(
+                    Matcher::Full("http_request_duration_seconds".to_string()),
+                    LATENCY_BUCKETS,
+                )
+                .expect("Latency buckets must not be empty")
+                .install_recorder()
+                .expect("Failed to install Prometheus recorder")
+        })
+        .clone()
+}
+
+/// Middleware recording request counts and latency by matched route and status
+pub async fn track_http_metrics(req: Request, next: Next) -> Response {
+    // Label by route template rather than raw path to keep cardinality bounded
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| "unmatched".to_string());
+    let method = req.method().to_string();
+
+    let started = Instant::now();
+    let response = next.run(req).await;
+    let elapsed = started.elapsed().as_secs_f64();
+
+    let labels = [
+        ("method", method),
+        ("route", route),
+        ("status", response.status().as_u16().to_string()),
+    ];
+    metrics::counter!("http_requests_total", &labels).increment(1);
+    metrics::histogram!("http_request_duration_seconds", &labels).record(elapsed);
+
+    response
+}
+
+/// Count an authentication attempt. `operation` is e.g. "login"; `outcome` is
+/// "success" or why it failed, e.g. "invalid_credentials".
+pub fn record_auth_attempt(operation: &'static str, outcome: &'static str) {
+    metrics::counter!("auth_attempts_total", "operation" => operation, "outcome" => outcome)
+        .increment(1);
+}
+
+/// The `auth_attempts_total` outcome of a login or registration
+pub fn auth_outcome<T>(result: &Result<T, AppError>) -> &'static str {
+    match result {
+        Ok(_) => "success",
+        Err(AppError::Unauthorized(_)) => "invalid_credentials",
+        Err(AppError::ValidationError(_)) => "rejected",
+        Err(_) => "error",
+    }
+}
+
+/// Set the connection pool gauges, which are read from memory
+fn record_pool_gauges(repository: &dyn Repository) {
+    let pool = repository.pool_stats();
+    metrics::gauge!("db_pool_connections").set(pool.size as f64);
+    metrics::gauge!("db_pool_idle_connections").set(pool.idle as f64);
+    metrics::gauge!("db_pool_max_connections").set(pool.max_connections as f64);
+}
+
+/// Refresh the gauges that need database queries. Runs on a timer rather than
+/// on every scrape, so scrapes never put load on the database.
+pub async fn refresh_gauges(repository: &dyn Repository) {
+    // Probe how long it currently takes to check a connection out of the pool
+    match repository.acquire_wait().await {
+        Ok(waited) => metrics::gauge!("db_pool_acquire_wait_seconds").set(waited.as_secs_f64()),
+        Err(error) => {
+            tracing::warn!("Failed to acquire connection for metrics: {}", error);
+            return;
+        }
+    }
+
+    match repository.count_tasks(&TaskFilter::default()).await {
+        Ok(total) => metrics::gauge!("tasks_total").set(total as f64),
+        Err(error) => tracing::warn!("Failed to count tasks for metrics: {}", error),
+    }
+
+    let completed = TaskFilter {
+        completed: Some(true),
+        ..Default::default()
+    };
+    match repository.count_tasks(&completed).await {
+        Ok(completed) => metrics::gauge!("tasks_completed_total").set(completed as f64),
+        Err(error) => tracing::warn!("Failed to count completed tasks for metrics: {}", error),
+    }
+
+    match repository.count_users().await {
+        Ok(total) => metrics::gauge!("users_total").set(total as f64),
+        Err(error) => tracing::warn!("Failed to count users for metrics: {}", error),
+    }
+}
+
+/// Refresh the database gauges every `period`, forever
+pub async fn refresh_gauges_every(repository: Arc<dyn Repository>, period: Duration) {
+    let mut interval = tokio::time::interval(period);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        interval.tick().await;
+        refresh_gauges(repository.as_ref()).await;
+    }
+}
+
+/// Render all metrics in the Prometheus text exposition format
+pub async fn metrics_handler(State(app_state): State<AppState>) -> impl IntoResponse {
+    record_pool_gauges(app_state.repository.as_ref());
+
+    (
+        StatusCode::OK,
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        install_recorder().render(),
+    )
+}
+
+/// Create metrics routes
+pub fn metrics_routes() -> Router<AppState> {
+    Router::new().route("/metrics", get(metrics_handler))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_http_metrics_are_labelled_by_route_template() {
+        let handle = install_recorder();
+        let app = Router::new()
+            .route("/widgets/{id}", get(|| async { "ok" }))
+            .layer(axum::middleware::from_fn(track_http_metrics));
+
+        let response = app
+            .oneshot(Request::get("/widgets/42").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let rendered = handle.render();
+        assert!(rendered
+            .contains(r#"http_requests_total{method="GET",route="/widgets/{id}",status="200"}"#));
+        assert!(rendered.contains("http_request_duration_seconds_bucket"));
+        assert!(!rendered.contains("/widgets/42"));
+    }
+
+    #[test]
+    fn test_auth_outcomes_name_the_failure() {
+        assert_eq!(auth_outcome(&Ok::<(), AppError>(())), "success");
+        assert_eq!(
+            auth_outcome::<()>(&Err(AppError::Unauthorized("Invalid credentials".to_string()))),
+            "invalid_credentials"
+        );
+        assert_eq!(
+            auth_outcome::<()>(&Err(AppError::ValidationError("Invalid email format".to_string()))),
+            "rejected"
+        );
+        assert_eq!(auth_outcome::<()>(&Err(AppError::NotFound)), "error");
+    }
+
+    #[tokio::test]
+    async fn test_unmatched_requests_share_a_single_label() {
+        let handle = install_recorder();
+        let app = Router::new()
+            .route("/widgets", get(|| async { "ok" }))
+            .layer(axum::middleware::from_fn(track_http_metrics));
+
+        let response = app
+            .oneshot(Request::get("/nope/123").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        assert!(handle
+            .render()
+            .contains(r#"http_requests_total{method="GET",route="unmatched",status="404"}"#));
+    }
+}
@@ -1,13 +1,12 @@
 use axum::{
-    extract::{FromRequestParts, Request, State},
-    http::{request::Parts, HeaderMap, StatusCode},
+    extract::{Request, State},
+    http::{HeaderMap, HeaderValue, StatusCode},
     middleware::Next,
     response::Response,
 };
-use async_trait::async_trait;
 use uuid::Uuid;
 
-use crate::{auth::AuthService, db::AppState};
+use crate::db::AppState;
 
 /// Middleware to extract and validate authentication
 pub async fn auth_middleware(
@@ -16,47 +15,89 @@ pub async fn auth_middleware(
     mut req: Request,
     next: Next,
 ) -> Result<Response, StatusCode> {
+    let result = authenticate(&state, &headers).await;
+    let outcome = match &result {
+        Ok(_) => "success",
+        Err(failure) => failure.outcome(),
+    };
+    crate::metrics::record_auth_attempt("token", outcome);
+
+    // Add user_id and session_id to request extensions for use in handlers
+    req.extensions_mut().insert(result.map_err(|failure| failure.status())?);
+    Ok(next.run(req).await)
+}
+
+/// Why a request could not be authenticated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AuthFailure {
+    /// No `Authorization: Bearer` header
+    MissingToken,
+    /// The token is malformed, expired or not signed by us
+    InvalidToken,
+    /// The token's session was logged out or has expired
+    RevokedSession,
+    /// The session could not be looked up
+    Error,
+}
+
+impl AuthFailure {
+    /// The `auth_attempts_total` outcome label
+    fn outcome(self) -> &'static str {
+        match self {
+            AuthFailure::MissingToken => "missing_token",
+            AuthFailure::InvalidToken => "invalid_token",
+            AuthFailure::RevokedSession => "revoked_session",
+            AuthFailure::Error => "error",
+        }
+    }
+
+    fn status(self) -> StatusCode {
+        match self {
+            AuthFailure::Error => StatusCode::INTERNAL_SERVER_ERROR,
+            _ => StatusCode::UNAUTHORIZED,
+        }
+    }
+}
+
+/// Validate the bearer token and look up the user it was issued to
+async fn authenticate(
+    state: &AppState,
+    headers: &HeaderMap,
+) -> Result<AuthUser, AuthFailure> {
     // Extract auth service from app state
     let auth_service = &state.auth_service;
 
-    // Extract Authorization header
-    let auth_header = headers
+    // Extract the bearer token from the Authorization header
+    let token = headers
         .get("Authorization")
         .and_then(|header| header.to_str().ok())
-        .ok_or(StatusCode::UNAUTHORIZED)?;
-
-    // Check if it's a Bearer token
-    if !auth_header.starts_with("Bearer ") {
-        return Err(StatusCode::UNAUTHORIZED);
-    }
-
-    let token = &auth_header[7..]; // Remove "Bearer " prefix
+        .and_then(|header| header.strip_prefix("Bearer "))
+        .ok_or(AuthFailure::MissingToken)?;
 
     // Validate the token
     let user_id = auth_service
         .get_user_id_from_token(token)
-        .map_err(|_| StatusCode::UNAUTHORIZED)?;
+        .map_err(|_| AuthFailure::InvalidToken)?;
 
     let session_id = auth_service
         .get_session_id_from_token(token)
-        .map_err(|_| StatusCode::UNAUTHORIZED)?;
+        .map_err(|_| AuthFailure::InvalidToken)?;
 
     // Verify session is still valid in the database
-    let session = crate::db::get_session_by_token(&state.pool, &crate::auth::hash_session_token(token))
+    let session = state
+        .repository
+        .get_session_by_token(&crate::auth::hash_session_token(token))
         .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        .map_err(|_| AuthFailure::Error)?;
 
     if session.is_none() {
-        return Err(StatusCode::UNAUTHORIZED);
+        return Err(AuthFailure::RevokedSession);
     }
 
-    // Add user_id and session_id to request extensions for use in handlers
-    req.extensions_mut().insert(AuthUser {
+    Ok(AuthUser {
         user_id,
         session_id,
-    });
-
-    Ok(next.run(req).await)
+    })
 }
 
 /// Struct to hold authenticated user information
@@ -76,43 +117,61 @@ pub async fn optional_auth_middleware(
     mut req: Request,
     next: Next,
 ) -> Response {
-    // Extract auth service from app state
-    let auth_service = &state.auth_service;
-    
-    // Try to extract Authorization header
-    if let Some(auth_header) = headers
-        .get("Authorization")
-        .and_then(|header| header.to_str().ok())
-    {
-        // Check if it's a Bearer token
-        if auth_header.starts_with("Bearer ") {
-            let token = &auth_header[7..]; // Remove "Bearer " prefix
-
-            // Try to validate the token
-            if let (Ok(user_id), Ok(session_id)) = (
-                auth_service.get_user_id_from_token(token),
-                auth_service.get_session_id_from_token(token),
-            ) {
-                // Verify session is still valid in the database
-                if let Ok(Some(_)) = crate::db::get_session_by_token(
-                    &state.pool,
-                    &crate::auth::hash_session_token(token),
-                )
-                .await
-                {
-                    // Add user info to request if authentication is successful
-                    req.extensions_mut().insert(AuthUser {
-                        user_id,
-                        session_id,
-                    });
-                }
-            }
-        }
+    // Add user info to request if authentication is successful
+    if let Ok(auth_user) = authenticate(&state, &headers).await {
+        req.extensions_mut().insert(auth_user);
     }
 
     next.run(req).await
 }
 
+/// Header carrying the request ID in both directions
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Longest caller-supplied request ID we accept before generating our own
+const MAX_REQUEST_ID_LENGTH: usize = 128;
+
+tokio::task_local! {
+    static REQUEST_ID: String;
+}
+
+/// The request ID of the request currently being handled, if any
+pub fn current_request_id() -> Option<String> {
+    REQUEST_ID.try_with(|id| id.clone()).ok()
+}
+
+/// Middleware that accepts the caller's `X-Request-Id` (or generates one), makes it
+/// available to spans and error bodies, and echoes it on the response
+pub async fn request_id_middleware(mut req: Request, next: Next) -> Response {
+    let request_id = req
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|value| is_valid_request_id(value))
+        .map(str::to_string)
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+
+    // Normalise the header so the request span picks up the ID we settled on
+    let header_value = HeaderValue::from_str(&request_id).expect("request IDs are visible ASCII");
+    req.headers_mut()
+        .insert(REQUEST_ID_HEADER, header_value.clone());
+
+    let mut response = REQUEST_ID.scope(request_id, next.run(req)).await;
+    response
+        .headers_mut()
+        .insert(REQUEST_ID_HEADER, header_value);
+    response
+}
+
+/// Accept short IDs made of characters that are safe to log and echo back
+fn is_valid_request_id(value: &str) -> bool {
+    !value.is_empty()
+        && value.len() <= MAX_REQUEST_ID_LENGTH
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | ':'))
+}
+
 /// Extract authenticated user from request extensions
 pub fn extract_auth_user(req: &Request) -> Option<&AuthUser> {
     req.extensions().get::<AuthUser>()
@@ -121,13 +180,80 @@ pub fn extract_auth_user(req: &Request) -> Option<&AuthUser> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use axum::{
-        body::Body,
-        http::{HeaderValue, Method},
-        Router,
-    };
+    use axum::{body::Body, routing::get, Router};
     use tower::ServiceExt;
 
+    fn request_id_app() -> Router {
+        Router::new()
+            .route(
+                "/",
+                get(|| async { current_request_id().unwrap_or_default() }),
+            )
+            .layer(axum::middleware::from_fn(request_id_middleware))
+    }
+
+    async fn body_text(response: Response) -> String {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        String::from_utf8(bytes.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_request_id_is_generated_when_missing() {
+        let response = request_id_app()
+            .oneshot(Request::get("/").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        let header = response.headers()[REQUEST_ID_HEADER]
+            .to_str()
+            .unwrap()
+            .to_string();
+        assert!(Uuid::parse_str(&header).is_ok());
+        assert_eq!(body_text(response).await, header);
+    }
+
+    #[tokio::test]
+    async fn test_request_id_from_caller_is_echoed() {
+        let response = request_id_app()
+            .oneshot(
+                Request::get("/")
+                    .header(REQUEST_ID_HEADER, "edge-1234.abc")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.headers()[REQUEST_ID_HEADER], "edge-1234.abc");
+        assert_eq!(body_text(response).await, "edge-1234.abc");
+    }
+
+    #[tokio::test]
+    async fn test_invalid_request_id_is_replaced() {
+        let response = request_id_app()
+            .oneshot(
+                Request::get("/")
+                    .header(REQUEST_ID_HEADER, "bad id with spaces")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let header = response.headers()[REQUEST_ID_HEADER].to_str().unwrap();
+        assert!(Uuid::parse_str(header).is_ok());
+    }
+
+    #[test]
+    fn test_request_id_validation() {
+        assert!(is_valid_request_id("abc-123_x.y:z"));
+        assert!(!is_valid_request_id(""));
+        assert!(!is_valid_request_id("has space"));
+        assert!(!is_valid_request_id(&"a".repeat(MAX_REQUEST_ID_LENGTH + 1)));
+    }
+
     // Note: These tests would need a more complete setup with actual database and auth service
     // They're included as examples of how the middleware would be tested
 
@@ -8,25 +8,217 @@ use uuid::Uuid;
 pub struct Task {
     pub id: Uuid,
     pub title: String,
+    /// Markdown notes
+    pub description: Option<String>,
     pub completed: bool,
     pub user_id: Uuid,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub created_at: Option<DateTime<Utc>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub updated_at: Option<DateTime<Utc>>,
+    /// When the task is due; for all-day tasks, the start of the due date in the owner's time zone
+    pub due_at: Option<DateTime<Utc>>,
+    /// Whether the task is due on a date rather than at a time
+    pub due_all_day: bool,
+    /// When work on the task should start
+    pub start_at: Option<DateTime<Utc>>,
+    pub priority: Priority,
+    /// The project the task belongs to, if any
+    pub project_id: Option<Uuid>,
+    /// The task this is a subtask of, if any
+    pub parent_id: Option<Uuid>,
+    /// Whether the task completes itself once all of its subtasks are completed
+    pub auto_complete: bool,
+    /// RFC 5545 recurrence rule; completing the task creates its next occurrence
+    pub recurrence: Option<String>,
+    /// Rank in the owner's manual order, see [`crate::rank`]; compare as plain strings
+    pub position: String,
+    /// Names of the task's tags, alphabetically
+    #[sqlx(skip)]
+    pub tags: Vec<String>,
+    /// How far along the task's direct subtasks are; only set when it has some
+    #[sqlx(skip)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subtasks: Option<SubtaskProgress>,
+    /// Whether a task blocking this one is not completed yet
+    #[sqlx(skip)]
+    pub blocked: bool,
+    /// Full-text search score; only set in full-text search results
+    #[sqlx(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub relevance: Option<f64>,
+    /// HTML title with the search matches in `<mark>`; only set in full-text search results
+    #[sqlx(skip)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snippet: Option<String>,
+    /// Sanitized HTML rendering of `description`; only set when requested with `?render=html`
+    #[sqlx(skip)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description_html: Option<String>,
+}
+
+/// Completion of a task's direct subtasks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SubtaskProgress {
+    pub total: u64,
+    pub completed: u64,
+    /// Share of the subtasks completed, rounded down
+    pub percent: u8,
+}
+
+impl SubtaskProgress {
+    pub fn new(total: u64, completed: u64) -> Self {
+        Self {
+            total,
+            completed,
+            percent: (completed * 100).checked_div(total).unwrap_or_default() as u8,
+        }
+    }
+
+    /// Whether there are subtasks and every one is completed
+    pub fn is_done(&self) -> bool {
+        self.total > 0 && self.completed == self.total
+    }
+}
+
+/// How urgent a task is. Stored as its number, so higher priorities sort later.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, sqlx::Type,
+)]
+#[serde(rename_all = "lowercase")]
+#[repr(i16)]
+pub enum Priority {
+    #[default]
+    None = 0,
+    Low = 1,
+    Medium = 2,
+    High = 3,
+    Urgent = 4,
+}
+
+impl Priority {
+    pub const ALL: [Priority; 5] = [
+        Priority::None,
+        Priority::Low,
+        Priority::Medium,
+        Priority::High,
+        Priority::Urgent,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Priority::None => "none",
+            Priority::Low => "low",
+            Priority::Medium => "medium",
+            Priority::High => "high",
+            Priority::Urgent => "urgent",
+        }
+    }
+
+    /// Parse a name as returned by [`Priority::name`]
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|priority| priority.name() == name)
+    }
 }
 
 // Payload for creating a task (omits id, created_at, updated_at as they are auto-generated/managed)
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct CreateTaskPayload {
     pub title: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// A date (`YYYY-MM-DD`, due all day in the user's time zone) or RFC 3339 timestamp
+    #[serde(default)]
+    pub due_at: Option<String>,
+    /// A date or RFC 3339 timestamp
+    #[serde(default)]
+    pub start_at: Option<String>,
+    #[serde(default)]
+    pub priority: Priority,
+    /// An existing, unarchived project to put the task in
+    #[serde(default)]
+    pub project_id: Option<Uuid>,
+    /// An existing task to make this a subtask of
+    #[serde(default)]
+    pub parent_id: Option<Uuid>,
+    /// Complete the task once all of its subtasks are completed
+    #[serde(default)]
+    pub auto_complete: bool,
+    /// An RFC 5545 recurrence rule such as `FREQ=WEEKLY;BYDAY=MO`; needs a due date
+    #[serde(default)]
+    pub recurrence: Option<String>,
+    /// Names of existing tags to attach
+    #[serde(default)]
+    pub tags: Vec<String>,
 }
 
-// Payload for updating a task (all fields optional)
+// Payload for updating a task (all fields optional; an empty description removes it,
+// as does `null` for the dates)
 #[derive(Debug, Deserialize)]
 pub struct UpdateTaskPayload {
     pub title: Option<String>,
+    pub description: Option<String>,
     pub completed: Option<bool>,
+    #[serde(default, with = "::serde_with::rust::double_option")]
+    pub due_at: Option<Option<String>>,
+    #[serde(default, with = "::serde_with::rust::double_option")]
+    pub start_at: Option<Option<String>>,
+    pub priority: Option<Priority>,
+    /// Moves the task to another project; `null` takes it out of its project
+    #[serde(default, with = "::serde_with::rust::double_option")]
+    pub project_id: Option<Option<Uuid>>,
+    /// Moves the task under another parent; `null` makes it a top-level task
+    #[serde(default, with = "::serde_with::rust::double_option")]
+    pub parent_id: Option<Option<Uuid>>,
+    pub auto_complete: Option<bool>,
+    /// Replaces the recurrence rule; `null` stops the task from recurring
+    #[serde(default, with = "::serde_with::rust::double_option")]
+    pub recurrence: Option<Option<String>>,
+    /// Names of existing tags to attach
+    #[serde(default)]
+    pub add_tags: Vec<String>,
+    /// Names of tags to detach
+    #[serde(default)]
+    pub remove_tags: Vec<String>,
+}
+
+// Query parameters for reading tasks
+#[derive(Debug, Default, Deserialize)]
+pub struct RenderParams {
+    /// `html` adds a sanitized `description_html` rendering to each task
+    pub render: Option<String>,
+}
+
+/// Where to move a task in the manual order; give either or both
+#[derive(Debug, Deserialize)]
+pub struct MoveTaskPayload {
+    /// The task to place it right after
+    #[serde(default)]
+    pub after: Option<Uuid>,
+    /// The task to place it right before
+    #[serde(default)]
+    pub before: Option<Uuid>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AddBlockerPayload {
+    /// The task that has to be completed first
+    pub blocker_id: Uuid,
+}
+
+// Query parameters for deleting a task
+#[derive(Debug, Default, Deserialize)]
+pub struct DeleteTaskParams {
+    /// `delete` (the default) deletes the subtasks too; `promote` moves them
+    /// up to the deleted task's parent
+    pub subtasks: Option<String>,
+}
+
+// Query parameters for previewing a recurring task's occurrences
+#[derive(Debug, Default, Deserialize)]
+pub struct OccurrencesParams {
+    /// How many occurrences to list after the current one
+    pub count: Option<u32>,
 }
 
 // Pagination parameters for listing tasks
@@ -39,12 +231,24 @@ pub struct PaginationParams {
     #[serde(default = "default_page_size")]
     #[serde_as(as = "DisplayFromStr")]
     pub page_size: u32,
+    /// Comma-separated sort columns, descending when prefixed with `-`
+    pub sort: Option<String>,
     pub sort_by: Option<String>,
     pub sort_order: Option<SortOrder>,
+    /// Opaque keyset cursor from a previous page's `next_cursor`/`prev_cursor`
+    pub cursor: Option<String>,
+    /// Page size in cursor mode; selects cursor mode even without a cursor
+    #[serde(default)]
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub limit: Option<u32>,
+    /// Set to `false` to skip counting the matching tasks
+    #[serde(default = "default_include_total")]
+    #[serde_as(as = "DisplayFromStr")]
+    pub include_total: bool,
 }
 
 // Sort order for pagination
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum SortOrder {
     Asc,
@@ -58,32 +262,74 @@ pub struct PaginatedResponse<T> {
     pub pagination: PaginationMeta,
 }
 
-// Pagination metadata
+// Number of tasks matching a listing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TotalCount {
+    pub items: u64,
+    // False when counting stopped at the configured cap and `items` is a lower bound
+    pub exact: bool,
+}
+
+// Pagination metadata. Page numbers are only reported in page mode and
+// cursors only in cursor mode. Totals are omitted when not requested, and
+// `total_pages` is only reported for exact totals.
 #[derive(Debug, Serialize)]
 pub struct PaginationMeta {
-    pub page: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page: Option<u32>,
     pub page_size: u32,
-    pub total_items: u64,
-    pub total_pages: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_items: Option<u64>,
+    pub total_exact: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_pages: Option<u32>,
     pub has_next: bool,
     pub has_previous: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prev_cursor: Option<String>,
 }
 
 impl PaginationMeta {
-    pub fn new(page: u32, page_size: u32, total_items: u64) -> Self {
-        let total_pages = if total_items == 0 {
-            1
-        } else {
-            ((total_items - 1) / page_size as u64 + 1) as u32
-        };
-        
+    pub fn new(page: u32, page_size: u32, total: Option<TotalCount>, has_next: bool) -> Self {
+        let total_pages = total.filter(|total| total.exact).map(|total| {
+            if total.items == 0 {
+                1
+            } else {
+                ((total.items - 1) / page_size as u64 + 1) as u32
+            }
+        });
+
         Self {
-            page,
+            page: Some(page),
             page_size,
-            total_items,
+            total_items: total.map(|total| total.items),
+            total_exact: total.is_some_and(|total| total.exact),
             total_pages,
-            has_next: page < total_pages,
+            has_next,
             has_previous: page > 1,
+            next_cursor: None,
+            prev_cursor: None,
+        }
+    }
+
+    pub fn with_cursors(
+        page_size: u32,
+        total: Option<TotalCount>,
+        next_cursor: Option<String>,
+        prev_cursor: Option<String>,
+    ) -> Self {
+        Self {
+            page: None,
+            page_size,
+            total_items: total.map(|total| total.items),
+            total_exact: total.is_some_and(|total| total.exact),
+            total_pages: None,
+            has_next: next_cursor.is_some(),
+            has_previous: prev_cursor.is_some(),
+            next_cursor,
+            prev_cursor,
         }
     }
 }
@@ -108,6 +354,19 @@ impl PaginationParams {
     pub fn limit(&self) -> u64 {
         self.page_size as u64
     }
+
+    /// Whether the request asked for cursor pagination
+    pub fn is_cursor_mode(&self) -> bool {
+        self.cursor.is_some() || self.limit.is_some()
+    }
+
+    /// Page size in cursor mode, bounded like `page_size`
+    pub fn cursor_limit(&self) -> u32 {
+        match self.limit {
+            Some(limit) if (1..=100).contains(&limit) => limit,
+            _ => default_page_size(),
+        }
+    }
 }
 
 fn default_page() -> u32 {
@@ -118,6 +377,188 @@ fn default_page_size() -> u32 {
     20
 }
 
+fn default_include_total() -> bool {
+    true
+}
+
+// Tag Models
+
+/// A label a user can put on any number of their tasks
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Tag {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    /// Lowercase, unique per user
+    pub name: String,
+    /// `#rrggbb`
+    pub color: Option<String>,
+    /// Number of tasks with this tag
+    pub task_count: i64,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateTagPayload {
+    pub name: String,
+    #[serde(default)]
+    pub color: Option<String>,
+}
+
+// Payload for updating a tag (all fields optional; `null` removes the color)
+#[derive(Debug, Deserialize)]
+pub struct UpdateTagPayload {
+    pub name: Option<String>,
+    #[serde(default, with = "::serde_with::rust::double_option")]
+    pub color: Option<Option<String>>,
+}
+
+// Project Models
+
+/// A list grouping some of a user's tasks
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Project {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    /// Unique per user
+    pub name: String,
+    /// `#rrggbb`
+    pub color: Option<String>,
+    /// Archived projects are hidden from the project list by default
+    pub archived: bool,
+    /// Where the project appears in the user's list, lowest first
+    pub position: i32,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateProjectPayload {
+    pub name: String,
+    #[serde(default)]
+    pub color: Option<String>,
+    /// Defaults to after the user's last project
+    #[serde(default)]
+    pub position: Option<i32>,
+}
+
+// Payload for updating a project (all fields optional; `null` removes the color)
+#[derive(Debug, Deserialize)]
+pub struct UpdateProjectPayload {
+    pub name: Option<String>,
+    #[serde(default, with = "::serde_with::rust::double_option")]
+    pub color: Option<Option<String>>,
+    pub archived: Option<bool>,
+    pub position: Option<i32>,
+}
+
+// Query parameters for listing projects
+#[serde_as]
+#[derive(Debug, Default, Deserialize)]
+pub struct ProjectListParams {
+    /// `true` lists archived projects as well
+    #[serde(default)]
+    #[serde_as(as = "DisplayFromStr")]
+    pub include_archived: bool,
+}
+
+/// Where a reminder is delivered. Stored as its number.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize, sqlx::Type,
+)]
+#[serde(rename_all = "snake_case")]
+#[repr(i16)]
+pub enum ReminderChannel {
+    /// Listed under `/notifications`
+    #[default]
+    InApp = 0,
+    Email = 1,
+    /// POSTed as JSON to a URL
+    Webhook = 2,
+}
+
+impl ReminderChannel {
+    pub fn name(self) -> &'static str {
+        match self {
+            ReminderChannel::InApp => "in_app",
+            ReminderChannel::Email => "email",
+            ReminderChannel::Webhook => "webhook",
+        }
+    }
+}
+
+/// A reminder about a task, at a fixed time or some minutes before it is due
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Reminder {
+    pub id: Uuid,
+    pub task_id: Uuid,
+    pub user_id: Uuid,
+    pub channel: ReminderChannel,
+    /// Email address or webhook URL; unset for in-app reminders
+    pub target: Option<String>,
+    /// The fixed time to remind at
+    pub remind_at: Option<DateTime<Utc>>,
+    /// How long before the task is due to remind, instead of a fixed time
+    pub minutes_before: Option<i32>,
+    /// When the reminder is sent next; unset while waiting for the task to get a due date
+    pub fire_at: Option<DateTime<Utc>>,
+    /// When the reminder was last sent; cleared when it is snoozed or rescheduled
+    pub delivered_at: Option<DateTime<Utc>>,
+    /// Failed deliveries since the reminder was last scheduled
+    pub attempts: i32,
+    pub last_error: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateReminderPayload {
+    /// A date or RFC 3339 timestamp to remind at
+    #[serde(default)]
+    pub remind_at: Option<String>,
+    /// Remind this many minutes before the task is due instead
+    #[serde(default)]
+    pub minutes_before: Option<i32>,
+    #[serde(default)]
+    pub channel: ReminderChannel,
+    /// Email address (the user's by default) or webhook URL
+    #[serde(default)]
+    pub target: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SnoozeReminderPayload {
+    /// Send the reminder again this many minutes from now
+    #[serde(default)]
+    pub minutes: Option<i64>,
+    /// Or at this date or RFC 3339 timestamp
+    #[serde(default)]
+    pub until: Option<String>,
+}
+
+/// An in-app message, such as a reminder delivered through [`ReminderChannel::InApp`]
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Notification {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    /// The task the notification is about, if any
+    pub task_id: Option<Uuid>,
+    pub message: String,
+    pub created_at: Option<DateTime<Utc>>,
+    /// When the user marked the notification as read
+    pub read_at: Option<DateTime<Utc>>,
+}
+
+// Query parameters for listing notifications
+#[serde_as]
+#[derive(Debug, Default, Deserialize)]
+pub struct NotificationListParams {
+    /// `true` lists only notifications that have not been read
+    #[serde(default)]
+    #[serde_as(as = "DisplayFromStr")]
+    pub unread: bool,
+}
+
 // Authentication Models
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -125,6 +566,8 @@ pub struct User {
     pub id: Uuid,
     pub email: String,
     pub password_hash: String,
+    /// IANA time zone that dates such as "today" are read in
+    pub time_zone: String,
     pub created_at: Option<DateTime<Utc>>,
     pub updated_at: Option<DateTime<Utc>>,
 }
@@ -144,6 +587,16 @@ pub struct Session {
 pub struct RegisterPayload {
     pub email: String,
     pub password: String,
+    /// IANA time zone name such as `Europe/Paris`; UTC when omitted
+    #[serde(default)]
+    pub time_zone: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateProfilePayload {
+    /// IANA time zone name such as `Europe/Paris`
+    #[serde(default)]
+    pub time_zone: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -162,6 +615,7 @@ pub struct AuthResponse {
 pub struct UserResponse {
     pub id: Uuid,
     pub email: String,
+    pub time_zone: String,
     pub created_at: Option<DateTime<Utc>>,
 }
 
@@ -170,16 +624,29 @@ impl From<User> for UserResponse {
         Self {
             id: user.id,
             email: user.email,
+            time_zone: user.time_zone,
             created_at: user.created_at,
         }
     }
 }
 
 // Search parameters for filtering tasks
+#[serde_as]
 #[derive(Debug, Deserialize)]
 pub struct SearchParams {
     pub q: Option<String>,      // Search query for title
     pub status: Option<String>, // Filter by status: "completed", "pending", "all"
+    pub search_mode: Option<String>, // How `q` matches: "substring" (default) or "fulltext"
+    pub filter: Option<String>, // Conditions in the query language described in `crate::query`
+    pub due_before: Option<String>, // Due before a date or timestamp, in the user's time zone
+    pub due_after: Option<String>,  // Due after a date or timestamp, in the user's time zone
+    #[serde(default)]
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub overdue: Option<bool>, // Only tasks that are (or are not) overdue
+    pub priority: Option<String>, // Comma-separated priorities, any of which matches
+    pub tags_any: Option<String>,  // Comma-separated tag names; tasks with at least one
+    pub tags_all: Option<String>,  // Comma-separated tag names; tasks with every one
+    pub tags_none: Option<String>, // Comma-separated tag names; tasks with none of them
 }
 
 // Combined query parameters for tasks endpoint
@@ -189,4 +656,6 @@ pub struct TaskQueryParams {
     pub pagination: PaginationParams,
     #[serde(flatten)]
     pub search: SearchParams,
+    #[serde(flatten)]
+    pub render: RenderParams,
 }
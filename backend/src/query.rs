@@ -0,0 +1,598 @@
+//! The task filter language used by `GET /tasks?filter=...`.
+//!
+//! A filter is a space-separated list of conditions that must all hold:
+//!
+//! - `completed:true` / `completed:false`
+//! - `created:2026-01-01`, `created:>2026-01-01`, `updated:<=2026-03-31T12:00:00Z`
+//!   (dates cover the whole day in the user's time zone; `>`, `>=`, `<`, `<=` compare)
+//! - `due:` and `start:` take the same values, plus `today`, `tomorrow` and
+//!   `none`; `due:overdue` matches pending tasks whose due date has passed
+//! - `priority:high`, `priority:>=medium`: from `none` up to `urgent`
+//! - `tag:work`: tasks carrying the tag
+//! - `title:release` or `title:"release notes"`: case-insensitive substring
+//! - a bare word or `"quoted phrase"`: same as `title:`
+//!
+//! Prefixing a condition with `-` negates it. Filters are parsed into a
+//! [`TaskQuery`] of typed comparisons, which the repositories compile to bound
+//! SQL parameters (or evaluate in memory), so values never reach the SQL text.
+
+use chrono::{DateTime, Days, Utc};
+use std::fmt;
+
+use crate::models::{Priority, Task};
+use crate::schedule::{Calendar, DateOrTime};
+
+/// Task column a comparison reads
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Column {
+    Title,
+    Completed,
+    CreatedAt,
+    UpdatedAt,
+    DueAt,
+    DueAllDay,
+    StartAt,
+    Priority,
+}
+
+impl Column {
+    /// Column name; only ever one of a fixed set, so it is safe to splice into SQL
+    pub fn name(self) -> &'static str {
+        match self {
+            Column::Title => "title",
+            Column::Completed => "completed",
+            Column::CreatedAt => "created_at",
+            Column::UpdatedAt => "updated_at",
+            Column::DueAt => "due_at",
+            Column::DueAllDay => "due_all_day",
+            Column::StartAt => "start_at",
+            Column::Priority => "priority",
+        }
+    }
+
+    /// Value of a timestamp column for `task`
+    fn time(self, task: &Task) -> Option<DateTime<Utc>> {
+        match self {
+            Column::CreatedAt => task.created_at,
+            Column::UpdatedAt => task.updated_at,
+            Column::DueAt => task.due_at,
+            Column::StartAt => task.start_at,
+            Column::Title | Column::Completed | Column::DueAllDay | Column::Priority => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
+    Eq,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    /// Case-insensitive substring match
+    Contains,
+}
+
+impl Operator {
+    /// SQL operator; backends spell case-insensitive `Contains` their own way
+    pub fn sql(self) -> &'static str {
+        match self {
+            Operator::Eq => "=",
+            Operator::Lt => "<",
+            Operator::Le => "<=",
+            Operator::Gt => ">",
+            Operator::Ge => ">=",
+            Operator::Contains => "LIKE",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operand {
+    Bool(bool),
+    Time(DateTime<Utc>),
+    Text(String),
+    Priority(Priority),
+    /// The column has no value; only compared with `Eq`
+    Null,
+}
+
+/// `column operator operand`. A comparison on a column without a value is
+/// false, other than with [`Operand::Null`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comparison {
+    pub column: Column,
+    pub operator: Operator,
+    pub operand: Operand,
+}
+
+/// A condition on a task
+#[derive(Debug, Clone, PartialEq)]
+pub enum Condition {
+    Compare(Comparison),
+    /// Every condition holds
+    All(Vec<Condition>),
+    /// At least one condition holds
+    Any(Vec<Condition>),
+    /// The task carries the tag with this (lowercase) name
+    Tagged(String),
+    /// The task is blocked by another task that is not completed yet
+    Blocked,
+    Not(Box<Condition>),
+}
+
+/// A parsed filter; a task matches when every condition holds
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TaskQuery {
+    pub conditions: Vec<Condition>,
+}
+
+/// Why a filter could not be parsed, pointing at the offending token
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// 1-based character position of the token in the filter
+    pub position: usize,
+    pub token: String,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Invalid filter at position {}: {}",
+            self.position, self.message
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// A whitespace-separated token, with quotes allowed around spaces
+struct Token<'a> {
+    text: &'a str,
+    position: usize,
+}
+
+fn tokenize(filter: &str) -> Result<Vec<Token<'_>>, ParseError> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    let mut quoted = false;
+
+    for (index, c) in filter.char_indices() {
+        match (start, c) {
+            (None, c) if c.is_whitespace() => {}
+            (None, c) => {
+                start = Some(index);
+                quoted = c == '"';
+            }
+            (Some(_), '"') => quoted = !quoted,
+            (Some(begin), c) if c.is_whitespace() && !quoted => {
+                tokens.push(token(filter, begin, index));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(begin) = start {
+        let last = token(filter, begin, filter.len());
+        if quoted {
+            return Err(last.error("unterminated quote"));
+        }
+        tokens.push(last);
+    }
+
+    Ok(tokens)
+}
+
+fn token(filter: &str, begin: usize, end: usize) -> Token<'_> {
+    Token {
+        text: &filter[begin..end],
+        position: filter[..begin].chars().count() + 1,
+    }
+}
+
+impl Token<'_> {
+    fn error(&self, message: impl Into<String>) -> ParseError {
+        ParseError {
+            position: self.position,
+            token: self.text.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+/// Remove one pair of surrounding quotes
+fn unquote(text: &str) -> &str {
+    text.strip_prefix('"')
+        .and_then(|text| text.strip_suffix('"'))
+        .unwrap_or(text)
+}
+
+/// Split a leading comparison operator off a value
+fn split_operator(value: &str) -> (Operator, &str) {
+    for (prefix, operator) in [
+        (">=", Operator::Ge),
+        ("<=", Operator::Le),
+        (">", Operator::Gt),
+        ("<", Operator::Lt),
+        ("=", Operator::Eq),
+    ] {
+        if let Some(rest) = value.strip_prefix(prefix) {
+            return (operator, rest);
+        }
+    }
+    (Operator::Eq, value)
+}
+
+fn compare(column: Column, operator: Operator, operand: Operand) -> Condition {
+    Condition::Compare(Comparison {
+        column,
+        operator,
+        operand,
+    })
+}
+
+impl Condition {
+    /// `column operator value`, where a date stands for the whole day in
+    /// `calendar`'s time zone
+    pub fn time(
+        column: Column,
+        operator: Operator,
+        value: DateOrTime,
+        calendar: &Calendar,
+    ) -> Self {
+        let date = match value {
+            DateOrTime::Time(time) => return compare(column, operator, Operand::Time(time)),
+            DateOrTime::Date(date) => date,
+        };
+        let at = |operator, time| compare(column, operator, Operand::Time(time));
+        let (start, end) = (calendar.start_of(date), calendar.end_of(date));
+
+        match operator {
+            Operator::Eq | Operator::Contains => {
+                Condition::All(vec![at(Operator::Ge, start), at(Operator::Lt, end)])
+            }
+            Operator::Lt => at(Operator::Lt, start),
+            Operator::Le => at(Operator::Lt, end),
+            Operator::Gt => at(Operator::Ge, end),
+            Operator::Ge => at(Operator::Ge, start),
+        }
+    }
+
+    /// Pending tasks due before now; all-day tasks only once their day is over
+    pub fn overdue(calendar: &Calendar) -> Self {
+        let today = calendar.start_of(calendar.today());
+        Condition::All(vec![
+            compare(Column::Completed, Operator::Eq, Operand::Bool(false)),
+            compare(Column::DueAt, Operator::Lt, Operand::Time(calendar.now)),
+            Condition::Not(Box::new(Condition::All(vec![
+                compare(Column::DueAllDay, Operator::Eq, Operand::Bool(true)),
+                compare(Column::DueAt, Operator::Ge, Operand::Time(today)),
+            ]))),
+        ])
+    }
+
+    /// The task has one of `priorities`
+    pub fn priority_in(priorities: &[Priority]) -> Self {
+        Condition::Any(
+            priorities
+                .iter()
+                .map(|priority| compare(Column::Priority, Operator::Eq, Operand::Priority(*priority)))
+                .collect(),
+        )
+    }
+
+    /// Whether `task` meets this condition, for backends that filter in process
+    pub fn matches(&self, task: &Task) -> bool {
+        match self {
+            Condition::Compare(comparison) => comparison.matches(task),
+            Condition::All(conditions) => conditions.iter().all(|condition| condition.matches(task)),
+            Condition::Any(conditions) => conditions.iter().any(|condition| condition.matches(task)),
+            Condition::Tagged(name) => task.tags.contains(name),
+            Condition::Blocked => task.blocked,
+            Condition::Not(condition) => !condition.matches(task),
+        }
+    }
+}
+
+/// Comparison on a timestamp column
+fn time_condition(
+    column: Column,
+    value: &str,
+    token: &Token<'_>,
+    calendar: &Calendar,
+) -> Result<Condition, ParseError> {
+    let (operator, value) = split_operator(value);
+    let today = calendar.today();
+    let value = match value {
+        "today" => Some(DateOrTime::Date(today)),
+        "tomorrow" => today.checked_add_days(Days::new(1)).map(DateOrTime::Date),
+        _ => DateOrTime::parse(value),
+    }
+    .ok_or_else(|| {
+        token.error("expected a date (YYYY-MM-DD, today or tomorrow) or RFC 3339 timestamp")
+    })?;
+
+    Ok(Condition::time(column, operator, value, calendar))
+}
+
+/// Comparison on `due:` or `start:`, which also accept `none`
+fn schedule_condition(
+    column: Column,
+    value: &str,
+    token: &Token<'_>,
+    calendar: &Calendar,
+) -> Result<Condition, ParseError> {
+    match value {
+        "none" => Ok(compare(column, Operator::Eq, Operand::Null)),
+        "overdue" if column == Column::DueAt => Ok(Condition::overdue(calendar)),
+        _ => time_condition(column, value, token, calendar),
+    }
+}
+
+/// Comparison on `priority:`, ordered from `none` to `urgent`
+fn priority_condition(value: &str, token: &Token<'_>) -> Result<Condition, ParseError> {
+    let (operator, value) = split_operator(value);
+    let priority = Priority::from_name(value)
+        .ok_or_else(|| token.error("expected none, low, medium, high or urgent"))?;
+    Ok(compare(Column::Priority, operator, Operand::Priority(priority)))
+}
+
+fn title_contains(text: &str, token: &Token<'_>) -> Result<Condition, ParseError> {
+    let text = unquote(text);
+    if text.is_empty() {
+        return Err(token.error("expected text to search for"));
+    }
+    Ok(compare(
+        Column::Title,
+        Operator::Contains,
+        Operand::Text(text.to_string()),
+    ))
+}
+
+fn parse_bool(value: &str, token: &Token<'_>) -> Result<bool, ParseError> {
+    match value {
+        "true" | "yes" => Ok(true),
+        "false" | "no" => Ok(false),
+        _ => Err(token.error("expected true or false")),
+    }
+}
+
+fn parse_condition(token: &Token<'_>, calendar: &Calendar) -> Result<Condition, ParseError> {
+    let (negated, text) = match token.text.strip_prefix('-') {
+        Some(rest) if !rest.is_empty() => (true, rest),
+        _ => (false, token.text),
+    };
+
+    let condition = match text.split_once(':') {
+        Some((field, value)) if !field.starts_with('"') => match field {
+            "completed" => compare(
+                Column::Completed,
+                Operator::Eq,
+                Operand::Bool(parse_bool(value, token)?),
+            ),
+            "blocked" => match parse_bool(value, token)? {
+                true => Condition::Blocked,
+                false => Condition::Not(Box::new(Condition::Blocked)),
+            },
+            "created" => time_condition(Column::CreatedAt, value, token, calendar)?,
+            "updated" => time_condition(Column::UpdatedAt, value, token, calendar)?,
+            "due" => schedule_condition(Column::DueAt, value, token, calendar)?,
+            "start" => schedule_condition(Column::StartAt, value, token, calendar)?,
+            "priority" => priority_condition(value, token)?,
+            "tag" => match unquote(value) {
+                "" => return Err(token.error("expected a tag name")),
+                name => Condition::Tagged(name.to_lowercase()),
+            },
+            "title" => title_contains(value, token)?,
+            _ => {
+                return Err(token.error(
+                    "unknown field; expected completed, blocked, created, updated, due, start, priority, tag or title",
+                ))
+            }
+        },
+        _ => title_contains(text, token)?,
+    };
+
+    Ok(if negated {
+        Condition::Not(Box::new(condition))
+    } else {
+        condition
+    })
+}
+
+impl TaskQuery {
+    /// Parse a filter, reading dates in `calendar`'s time zone
+    pub fn parse(filter: &str, calendar: &Calendar) -> Result<Self, ParseError> {
+        let conditions = tokenize(filter)?
+            .iter()
+            .map(|token| parse_condition(token, calendar))
+            .collect::<Result<_, _>>()?;
+        Ok(Self { conditions })
+    }
+
+    /// The same query, also requiring `condition`
+    pub fn and(mut self, condition: Condition) -> Self {
+        self.conditions.push(condition);
+        self
+    }
+
+    /// Whether `task` matches, for backends that filter in process
+    pub fn matches(&self, task: &Task) -> bool {
+        self.conditions.iter().all(|condition| condition.matches(task))
+    }
+}
+
+impl Comparison {
+    fn matches(&self, task: &Task) -> bool {
+        match (&self.operand, self.column) {
+            (Operand::Text(text), Column::Title) => task
+                .title
+                .to_lowercase()
+                .contains(&text.to_lowercase()),
+            (Operand::Bool(value), Column::Completed) => task.completed == *value,
+            (Operand::Bool(value), Column::DueAllDay) => task.due_all_day == *value,
+            (Operand::Priority(value), Column::Priority) => match self.operator {
+                Operator::Eq | Operator::Contains => task.priority == *value,
+                Operator::Lt => task.priority < *value,
+                Operator::Le => task.priority <= *value,
+                Operator::Gt => task.priority > *value,
+                Operator::Ge => task.priority >= *value,
+            },
+            (Operand::Null, column) => column.time(task).is_none(),
+            (Operand::Time(time), column) => column.time(task).is_some_and(|value| {
+                match self.operator {
+                    Operator::Eq | Operator::Contains => value == *time,
+                    Operator::Lt => value < *time,
+                    Operator::Le => value <= *time,
+                    Operator::Gt => value > *time,
+                    Operator::Ge => value >= *time,
+                }
+            }),
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveDate, TimeZone};
+
+    fn utc() -> Calendar {
+        Calendar {
+            time_zone: chrono_tz::Tz::UTC,
+            now: Utc.with_ymd_and_hms(2026, 1, 5, 12, 0, 0).unwrap(),
+        }
+    }
+
+    fn task() -> Task {
+        Task {
+            id: uuid::Uuid::nil(),
+            title: "Write release notes".to_string(),
+            description: None,
+            completed: false,
+            user_id: uuid::Uuid::nil(),
+            created_at: Some(Utc.with_ymd_and_hms(2026, 1, 5, 10, 0, 0).unwrap()),
+            updated_at: Some(Utc.with_ymd_and_hms(2026, 1, 5, 10, 0, 0).unwrap()),
+            due_at: None,
+            due_all_day: false,
+            start_at: None,
+            priority: Priority::None,
+            project_id: None,
+            parent_id: None,
+            auto_complete: false,
+            recurrence: None,
+            position: "i".to_string(),
+            tags: vec!["work".to_string()],
+            subtasks: None,
+            blocked: false,
+            relevance: None,
+            snippet: None,
+            description_html: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_conditions() {
+        let query = TaskQuery::parse(
+            r#"completed:false created:>2026-01-01 title:"release notes" -draft"#,
+            &utc(),
+        )
+        .unwrap();
+        let day = |d| Utc.with_ymd_and_hms(2026, 1, d, 0, 0, 0).unwrap();
+
+        assert_eq!(
+            query.conditions,
+            vec![
+                compare(Column::Completed, Operator::Eq, Operand::Bool(false)),
+                compare(Column::CreatedAt, Operator::Ge, Operand::Time(day(2))),
+                compare(
+                    Column::Title,
+                    Operator::Contains,
+                    Operand::Text("release notes".to_string())
+                ),
+                Condition::Not(Box::new(compare(
+                    Column::Title,
+                    Operator::Contains,
+                    Operand::Text("draft".to_string())
+                ))),
+            ]
+        );
+
+        let query = TaskQuery::parse("updated:2026-01-05", &utc()).unwrap();
+        assert!(matches!(&query.conditions[0], Condition::All(both) if both.len() == 2));
+        assert_eq!(TaskQuery::parse("   ", &utc()).unwrap(), TaskQuery::default());
+    }
+
+    #[test]
+    fn test_errors_point_at_the_bad_token() {
+        let error = TaskQuery::parse("completed:false created:>2026-13-01", &utc()).unwrap_err();
+        assert_eq!(error.position, 17);
+        assert_eq!(error.token, "created:>2026-13-01");
+        assert!(error.message.starts_with("expected a date"));
+        assert_eq!(
+            error.to_string(),
+            "Invalid filter at position 17: expected a date (YYYY-MM-DD, today or tomorrow) or RFC 3339 timestamp"
+        );
+
+        let error = TaskQuery::parse("owner:jane", &utc()).unwrap_err();
+        assert_eq!(error.position, 1);
+        assert!(error.message.starts_with("unknown field"));
+        assert!(!error.to_string().contains("owner"));
+
+        assert!(TaskQuery::parse("completed:maybe", &utc()).is_err());
+        assert!(TaskQuery::parse("priority:>critical", &utc()).is_err());
+        assert!(TaskQuery::parse("start:overdue", &utc()).is_err());
+        assert!(TaskQuery::parse(r#"title:"unterminated"#, &utc()).is_err());
+        assert!(TaskQuery::parse(r#"title:"""#, &utc()).is_err());
+    }
+
+    #[test]
+    fn test_matches_in_memory() {
+        let task = task();
+        let matches = |filter: &str| TaskQuery::parse(filter, &utc()).unwrap().matches(&task);
+
+        assert!(matches("RELEASE completed:false created:2026-01-05"));
+        assert!(matches("created:>=2026-01-05 created:<2026-01-06 created:today"));
+        assert!(!matches("created:>2026-01-05"));
+        assert!(!matches("-release"));
+        assert!(matches("-completed:true"));
+        assert!(matches("priority:none priority:<low -priority:>=medium"));
+        assert!(matches("tag:Work -tag:home"));
+        assert!(matches("blocked:false -blocked:yes"));
+        // Tasks without a due date never match a due date comparison
+        assert!(matches("due:none -due:today -due:overdue"));
+        assert!(!matches("due:<2030-01-01"));
+    }
+
+    #[test]
+    fn test_due_dates_follow_the_time_zone() {
+        let tokyo = Calendar {
+            time_zone: "Asia/Tokyo".parse().unwrap(),
+            // Already January 6th in Tokyo
+            now: Utc.with_ymd_and_hms(2026, 1, 5, 20, 0, 0).unwrap(),
+        };
+        let mut task = task();
+        let matches =
+            |filter: &str, task: &Task| TaskQuery::parse(filter, &tokyo).unwrap().matches(task);
+
+        // Due all day on the 5th in Tokyo: overdue now that the 6th has begun there
+        task.due_at = Some(tokyo.start_of(NaiveDate::from_ymd_opt(2026, 1, 5).unwrap()));
+        task.due_all_day = true;
+        assert!(matches("due:2026-01-05 due:overdue -due:today", &task));
+
+        // Due all day today: not overdue until the day is over
+        task.due_at = Some(tokyo.start_of(tokyo.today()));
+        assert!(matches("due:today -due:overdue", &task));
+
+        // Due at a time earlier today: overdue
+        task.due_at = Some(tokyo.now - chrono::Duration::hours(1));
+        task.due_all_day = false;
+        assert!(matches("due:today due:overdue", &task));
+        task.completed = true;
+        assert!(!matches("due:overdue", &task));
+    }
+}
@@ -0,0 +1,196 @@
+//! Lexicographic ranks for ordering tasks by hand.
+//!
+//! A rank is a string of digits and lowercase letters read as a base-36
+//! fraction: `i` is 18/36 and `i8` is 18/36 + 8/36². Comparing ranks as plain
+//! strings orders them by value, and there is always another rank between two
+//! others, so moving a task only rewrites its own rank. Ranks never end in
+//! `0`, which would leave no room right before them.
+//!
+//! Moves between the same two tasks make ranks longer; once one grows past
+//! [`MAX_LENGTH`], all of the owner's ranks are respaced with [`spread`].
+
+/// Ranks longer than this are shortened by respacing them all
+pub const MAX_LENGTH: usize = 32;
+
+const BASE: u8 = 36;
+
+/// Digit values of a valid rank
+fn digits(rank: &str) -> Option<Vec<u8>> {
+    if rank.is_empty() || rank.ends_with('0') {
+        return None;
+    }
+    rank.chars()
+        .map(|c| match c {
+            '0'..='9' | 'a'..='z' => c.to_digit(u32::from(BASE)).map(|digit| digit as u8),
+            _ => None,
+        })
+        .collect()
+}
+
+fn text(digits: &[u8]) -> String {
+    digits
+        .iter()
+        .map(|digit| char::from_digit(u32::from(*digit), u32::from(BASE)).unwrap())
+        .collect()
+}
+
+/// Digits strictly between `lower` and `upper` (the end of the order when
+/// `None`), which must be ordered and not end in 0
+fn midpoint(lower: &[u8], upper: Option<&[u8]>) -> Vec<u8> {
+    if let Some(upper) = upper {
+        // Keep the common prefix, reading missing lower digits as 0
+        let common = upper
+            .iter()
+            .enumerate()
+            .take_while(|(index, digit)| lower.get(*index).copied().unwrap_or(0) == **digit)
+            .count();
+        if common > 0 {
+            let mut rank = upper[..common].to_vec();
+            rank.extend(midpoint(lower.get(common..).unwrap_or_default(), Some(&upper[common..])));
+            return rank;
+        }
+    }
+
+    let low = lower.first().copied().unwrap_or(0);
+    let high = upper.map_or(BASE, |upper| upper[0]);
+    if high - low > 1 {
+        vec![(low + high).div_ceil(2)]
+    } else if let Some(upper) = upper.filter(|upper| upper.len() > 1) {
+        // A prefix of the upper rank comes before it
+        vec![upper[0]]
+    } else {
+        let mut rank = vec![low];
+        rank.extend(midpoint(lower.get(1..).unwrap_or_default(), None));
+        rank
+    }
+}
+
+/// A rank strictly between `lower` and `upper`, where `None` stands for the
+/// start or the end of the order. `None` when there is no room: the bounds are
+/// equal, out of order or not valid ranks.
+pub fn between(lower: Option<&str>, upper: Option<&str>) -> Option<String> {
+    let lower_digits = match lower {
+        Some(lower) => digits(lower)?,
+        None => Vec::new(),
+    };
+    let upper_digits = match upper {
+        Some(upper) => Some(digits(upper)?),
+        None => None,
+    };
+    if let (Some(lower), Some(upper)) = (lower, upper) {
+        if lower >= upper {
+            return None;
+        }
+    }
+    Some(text(&midpoint(&lower_digits, upper_digits.as_deref())))
+}
+
+/// A rank after `last`, the last in the order, for appending. Counts up in
+/// the last digit that can be raised, so appends keep ranks short.
+pub fn after(last: Option<&str>) -> String {
+    let Some(last) = last else {
+        return "i".to_string();
+    };
+    let Some(mut digits) = digits(last) else {
+        // Still sorts after it; respacing replaces it with a valid rank
+        return format!("{}i", last);
+    };
+    match digits.iter().rposition(|digit| *digit < BASE - 1) {
+        Some(index) => {
+            digits.truncate(index + 1);
+            digits[index] += 1;
+        }
+        None => digits.push(1),
+    }
+    text(&digits)
+}
+
+/// `count` ranks spaced evenly through the order, as short as leaves room
+/// for many moves between neighbours
+pub fn spread(count: usize) -> Vec<String> {
+    let base = u128::from(BASE);
+    let slots = count as u128 + 1;
+    let mut width = 1;
+    let mut space = base;
+    while space / slots < base * base {
+        width += 1;
+        space *= base;
+    }
+    let step = space / slots;
+
+    (1..slots)
+        .map(|index| {
+            let mut value = index * step;
+            let mut digits = vec![0; width];
+            for digit in digits.iter_mut().rev() {
+                *digit = (value % base) as u8;
+                value /= base;
+            }
+            while digits.last() == Some(&0) {
+                digits.pop();
+            }
+            text(&digits)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_between_finds_a_rank_inside_the_bounds() {
+        assert_eq!(between(None, None).as_deref(), Some("i"));
+        for (lower, upper) in [
+            (None, Some("1")),
+            (Some("i"), None),
+            (Some("z"), None),
+            (Some("a"), Some("b")),
+            (Some("a"), Some("a1")),
+            (Some("az"), Some("b")),
+            (Some("0001"), Some("0002")),
+            (Some("abc"), Some("abd")),
+        ] {
+            let rank = between(lower, upper).unwrap();
+            assert!(digits(&rank).is_some(), "{}", rank);
+            assert!(lower.is_none_or(|lower| lower < rank.as_str()), "{:?} < {}", lower, rank);
+            assert!(upper.is_none_or(|upper| rank.as_str() < upper), "{} < {:?}", rank, upper);
+        }
+
+        assert_eq!(between(Some("b"), Some("b")), None);
+        assert_eq!(between(Some("c"), Some("b")), None);
+        assert_eq!(between(Some("B"), None), None);
+        assert_eq!(between(Some("a0"), None), None);
+    }
+
+    #[test]
+    fn test_repeated_moves_grow_ranks_slowly() {
+        // Always moving a task to just after the first one
+        let first = between(None, None).unwrap();
+        let mut upper = after(Some(&first));
+        for _ in 0..100 {
+            upper = between(Some(&first), Some(&upper)).unwrap();
+        }
+        assert!(upper.len() <= 22, "{}", upper);
+
+        // Appending counts up without growing
+        let mut last = "a3k".to_string();
+        for _ in 0..100 {
+            let next = after(Some(&last));
+            assert!(next > last && next.len() <= 3, "{} after {}", next, last);
+            last = next;
+        }
+        assert_eq!(after(Some("zz")), "zz1");
+    }
+
+    #[test]
+    fn test_spread_ranks_are_ordered_and_short() {
+        assert!(spread(0).is_empty());
+        for count in [1, 35, 1000] {
+            let ranks = spread(count);
+            assert_eq!(ranks.len(), count);
+            assert!(ranks.windows(2).all(|pair| pair[0] < pair[1]));
+            assert!(ranks.iter().all(|rank| digits(rank).is_some() && rank.len() <= 4));
+        }
+    }
+}
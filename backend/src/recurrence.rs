@@ -0,0 +1,401 @@
+//! Recurrence rules for repeating tasks.
+//!
+//! A subset of RFC 5545 `RRULE`s: `FREQ` (`DAILY`, `WEEKLY`, `MONTHLY` or
+//! `YEARLY`), `INTERVAL`, `BYDAY` with plain weekdays (daily and weekly rules
+//! only) and one of `COUNT` or `UNTIL`. A recurring task's due date is its
+//! current occurrence; the following ones are computed from it in the user's
+//! time zone, so a task due at 9:00 stays due at 9:00 across daylight saving
+//! changes. Monthly and yearly dates that do not exist, such as the 31st of a
+//! shorter month, are skipped as RFC 5545 requires.
+
+use std::fmt;
+
+use chrono::{DateTime, Datelike, Days, Months, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc, Weekday};
+
+use crate::schedule::{Calendar, Due};
+
+/// How often a rule repeats, before its interval
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+/// The last moment a rule may fall on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Until {
+    /// Up to and including this date, in the user's time zone
+    Date(NaiveDate),
+    Time(DateTime<Utc>),
+}
+
+/// A parsed recurrence rule
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recurrence {
+    pub frequency: Frequency,
+    /// Repeat every `interval` days, weeks, months or years
+    pub interval: u32,
+    /// Days of the week occurrences fall on, Monday first; empty for the
+    /// weekday of the current occurrence
+    pub weekdays: Vec<Weekday>,
+    /// How many occurrences are left, counting the current one
+    pub count: Option<u32>,
+    pub until: Option<Until>,
+}
+
+/// Why a recurrence rule could not be parsed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleError {
+    pub message: String,
+}
+
+impl fmt::Display for RuleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid recurrence rule: {}", self.message)
+    }
+}
+
+impl std::error::Error for RuleError {}
+
+fn error(message: impl Into<String>) -> RuleError {
+    RuleError {
+        message: message.into(),
+    }
+}
+
+const WEEKDAYS: [(&str, Weekday); 7] = [
+    ("MO", Weekday::Mon),
+    ("TU", Weekday::Tue),
+    ("WE", Weekday::Wed),
+    ("TH", Weekday::Thu),
+    ("FR", Weekday::Fri),
+    ("SA", Weekday::Sat),
+    ("SU", Weekday::Sun),
+];
+
+fn parse_frequency(value: &str) -> Result<Frequency, RuleError> {
+    match value {
+        "DAILY" => Ok(Frequency::Daily),
+        "WEEKLY" => Ok(Frequency::Weekly),
+        "MONTHLY" => Ok(Frequency::Monthly),
+        "YEARLY" => Ok(Frequency::Yearly),
+        _ => Err(error(format!(
+            "unsupported FREQ {:?}; expected DAILY, WEEKLY, MONTHLY or YEARLY",
+            value
+        ))),
+    }
+}
+
+fn parse_positive(key: &str, value: &str) -> Result<u32, RuleError> {
+    value
+        .parse()
+        .ok()
+        .filter(|number| *number > 0)
+        .ok_or_else(|| error(format!("{} must be a positive number, got {:?}", key, value)))
+}
+
+fn parse_weekdays(value: &str) -> Result<Vec<Weekday>, RuleError> {
+    let mut weekdays = value
+        .split(',')
+        .map(|day| {
+            WEEKDAYS
+                .iter()
+                .find(|(name, _)| *name == day.trim())
+                .map(|(_, weekday)| *weekday)
+                .ok_or_else(|| error(format!("unsupported BYDAY value {:?}; expected MO to SU", day)))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    weekdays.sort_by_key(Weekday::num_days_from_monday);
+    weekdays.dedup();
+    Ok(weekdays)
+}
+
+fn parse_until(value: &str) -> Result<Until, RuleError> {
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y%m%d") {
+        return Ok(Until::Date(date));
+    }
+    value
+        .strip_suffix('Z')
+        .and_then(|time| NaiveDateTime::parse_from_str(time, "%Y%m%dT%H%M%S").ok())
+        .map(|time| Until::Time(time.and_utc()))
+        .ok_or_else(|| {
+            error(format!(
+                "UNTIL must be a date (YYYYMMDD) or UTC time (YYYYMMDDTHHMMSSZ), got {:?}",
+                value
+            ))
+        })
+}
+
+impl Recurrence {
+    /// Parse a rule such as `FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,WE;COUNT=10`,
+    /// with or without a leading `RRULE:`
+    pub fn parse(rule: &str) -> Result<Self, RuleError> {
+        let rule = rule.trim();
+        let rule = match rule.get(..6) {
+            Some(prefix) if prefix.eq_ignore_ascii_case("RRULE:") => &rule[6..],
+            _ => rule,
+        };
+
+        let mut frequency = None;
+        let mut interval = None;
+        let mut weekdays = None;
+        let mut count = None;
+        let mut until = None;
+        for part in rule.split(';').filter(|part| !part.trim().is_empty()) {
+            let (key, value) = part
+                .split_once('=')
+                .ok_or_else(|| error(format!("expected KEY=VALUE, got {:?}", part)))?;
+            let key = key.trim().to_ascii_uppercase();
+            let value = value.trim().to_ascii_uppercase();
+            let repeated = match key.as_str() {
+                "FREQ" => frequency.replace(parse_frequency(&value)?).is_some(),
+                "INTERVAL" => interval.replace(parse_positive(&key, &value)?).is_some(),
+                "BYDAY" => weekdays.replace(parse_weekdays(&value)?).is_some(),
+                "COUNT" => count.replace(parse_positive(&key, &value)?).is_some(),
+                "UNTIL" => until.replace(parse_until(&value)?).is_some(),
+                _ => {
+                    return Err(error(format!(
+                        "unsupported part {:?}; expected FREQ, INTERVAL, BYDAY, COUNT or UNTIL",
+                        key
+                    )))
+                }
+            };
+            if repeated {
+                return Err(error(format!("{} is given more than once", key)));
+            }
+        }
+
+        let frequency = frequency.ok_or_else(|| error("FREQ is required"))?;
+        if count.is_some() && until.is_some() {
+            return Err(error("COUNT and UNTIL cannot be combined"));
+        }
+        if weekdays.is_some() && matches!(frequency, Frequency::Monthly | Frequency::Yearly) {
+            return Err(error("BYDAY is only supported with FREQ=DAILY or FREQ=WEEKLY"));
+        }
+        Ok(Self {
+            frequency,
+            interval: interval.unwrap_or(1),
+            weekdays: weekdays.unwrap_or_default(),
+            count,
+            until,
+        })
+    }
+
+    /// The rule for the occurrence after the current one, with one occurrence
+    /// fewer left
+    pub fn following(&self) -> Self {
+        Self {
+            count: self.count.map(|count| count.saturating_sub(1)),
+            ..self.clone()
+        }
+    }
+
+    /// The occurrences after the one `due`, as seen in `calendar`'s time zone
+    pub fn occurrences<'a>(&'a self, due: Due, calendar: &'a Calendar) -> impl Iterator<Item = Due> + 'a {
+        let local = due.at.with_timezone(&calendar.time_zone).naive_local();
+        let remaining = self.count.map_or(usize::MAX, |count| count.saturating_sub(1) as usize);
+        std::iter::successors(Some(local.date()), |date| self.next_date(*date))
+            .skip(1)
+            .take_while(move |date| match self.until {
+                Some(Until::Date(until)) => *date <= until,
+                _ => true,
+            })
+            .map(move |date| Due {
+                at: if due.all_day {
+                    calendar.start_of(date)
+                } else {
+                    local_instant(calendar, date, local.time())
+                },
+                all_day: due.all_day,
+            })
+            .take_while(move |next| match self.until {
+                Some(Until::Time(until)) => next.at <= until,
+                _ => true,
+            })
+            .take(remaining)
+    }
+
+    /// The first date after `date` the rule falls on, treating `date` as an
+    /// occurrence; `None` when there is none, or none chrono can represent
+    fn next_date(&self, date: NaiveDate) -> Option<NaiveDate> {
+        let interval = self.interval;
+        match self.frequency {
+            // Weekdays repeat every 7 steps, so a match comes within 7 if at all
+            Frequency::Daily => (1..=7)
+                .map_while(|step| date.checked_add_days(Days::new(u64::from(interval) * step)))
+                .find(|next| self.weekdays.is_empty() || self.weekdays.contains(&next.weekday())),
+            Frequency::Weekly => {
+                let weekdays = match self.weekdays.as_slice() {
+                    [] => &[date.weekday()][..],
+                    weekdays => weekdays,
+                };
+                let weekday = date.weekday().num_days_from_monday();
+                // Later the same week, else on the first weekday `interval` weeks on
+                match weekdays.iter().find(|day| day.num_days_from_monday() > weekday) {
+                    Some(day) => date.checked_add_days(Days::new(u64::from(day.num_days_from_monday() - weekday))),
+                    None => date
+                        .checked_sub_days(Days::new(u64::from(weekday)))?
+                        .checked_add_days(Days::new(7 * u64::from(interval)))?
+                        .checked_add_days(Days::new(u64::from(weekdays[0].num_days_from_monday()))),
+                }
+            }
+            // February 29th may not come back for 8 years, or longer with an interval
+            Frequency::Monthly | Frequency::Yearly => {
+                let months = match self.frequency {
+                    Frequency::Monthly => interval,
+                    _ => interval.checked_mul(12)?,
+                };
+                (1..=400)
+                    .map_while(|step| {
+                        let first = date.with_day(1)?.checked_add_months(Months::new(months.checked_mul(step)?))?;
+                        Some(first.with_day(date.day()))
+                    })
+                    .flatten()
+                    .next()
+            }
+        }
+    }
+}
+
+/// The instant `time` on `date` in the calendar's time zone. Times skipped by
+/// a daylight saving change move forward an hour.
+fn local_instant(calendar: &Calendar, date: NaiveDate, time: NaiveTime) -> DateTime<Utc> {
+    let local = date.and_time(time);
+    [local, local + chrono::Duration::hours(1)]
+        .iter()
+        .find_map(|local| calendar.time_zone.from_local_datetime(local).earliest())
+        .map(|instant| instant.with_timezone(&Utc))
+        .unwrap_or_else(|| local.and_utc())
+}
+
+/// The canonical form rules are stored in, e.g. `FREQ=WEEKLY;BYDAY=MO,WE;COUNT=3`
+impl fmt::Display for Recurrence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let frequency = match self.frequency {
+            Frequency::Daily => "DAILY",
+            Frequency::Weekly => "WEEKLY",
+            Frequency::Monthly => "MONTHLY",
+            Frequency::Yearly => "YEARLY",
+        };
+        write!(f, "FREQ={}", frequency)?;
+        if self.interval != 1 {
+            write!(f, ";INTERVAL={}", self.interval)?;
+        }
+        if !self.weekdays.is_empty() {
+            let names: Vec<&str> = self
+                .weekdays
+                .iter()
+                .filter_map(|weekday| WEEKDAYS.iter().find(|(_, day)| day == weekday))
+                .map(|(name, _)| *name)
+                .collect();
+            write!(f, ";BYDAY={}", names.join(","))?;
+        }
+        if let Some(count) = self.count {
+            write!(f, ";COUNT={}", count)?;
+        }
+        match self.until {
+            Some(Until::Date(date)) => write!(f, ";UNTIL={}", date.format("%Y%m%d")),
+            Some(Until::Time(time)) => write!(f, ";UNTIL={}", time.format("%Y%m%dT%H%M%SZ")),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn calendar(time_zone: &str) -> Calendar {
+        Calendar::for_time_zone(time_zone)
+    }
+
+    fn dates(rule: &str, first: &str, calendar: &Calendar) -> Vec<String> {
+        let first = NaiveDate::parse_from_str(first, "%Y-%m-%d").unwrap();
+        let due = Due {
+            at: calendar.start_of(first),
+            all_day: true,
+        };
+        Recurrence::parse(rule)
+            .unwrap()
+            .occurrences(due, calendar)
+            .take(6)
+            .map(|due| {
+                due.at
+                    .with_timezone(&calendar.time_zone)
+                    .date_naive()
+                    .to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_rules_parse_to_their_canonical_form() {
+        let rule = Recurrence::parse("RRULE:freq=weekly;byday=we,mo,WE;interval=2;count=3").unwrap();
+        assert_eq!(rule.weekdays, [Weekday::Mon, Weekday::Wed]);
+        assert_eq!(rule.to_string(), "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,WE;COUNT=3");
+        assert_eq!(rule.following().to_string(), "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,WE;COUNT=2");
+        let rule = Recurrence::parse("FREQ=DAILY;UNTIL=20261231T235959Z").unwrap();
+        assert_eq!(Recurrence::parse(&rule.to_string()), Ok(rule));
+
+        for invalid in [
+            "",
+            "INTERVAL=2",
+            "FREQ=HOURLY",
+            "FREQ=DAILY;INTERVAL=0",
+            "FREQ=DAILY;FREQ=WEEKLY",
+            "FREQ=WEEKLY;BYDAY=1MO",
+            "FREQ=MONTHLY;BYDAY=MO",
+            "FREQ=DAILY;COUNT=2;UNTIL=20261231",
+            "FREQ=DAILY;UNTIL=2026-12-31",
+            "FREQ=DAILY;BYMONTH=1",
+            "FREQ",
+        ] {
+            assert!(Recurrence::parse(invalid).is_err(), "{:?}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_occurrences_follow_the_rule() {
+        let utc = calendar("UTC");
+        // 2026-10-19 is a Monday
+        assert_eq!(
+            dates("FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,WE", "2026-10-21", &utc),
+            ["2026-11-02", "2026-11-04", "2026-11-16", "2026-11-18", "2026-11-30", "2026-12-02"]
+        );
+        assert_eq!(
+            dates("FREQ=DAILY;INTERVAL=2;BYDAY=SA,SU;COUNT=3", "2026-10-19", &utc),
+            ["2026-10-25", "2026-10-31"]
+        );
+        // Every 7 days from a Monday never lands on a Tuesday
+        assert_eq!(dates("FREQ=DAILY;INTERVAL=7;BYDAY=TU", "2026-10-19", &utc), Vec::<String>::new());
+        assert_eq!(
+            dates("FREQ=MONTHLY;UNTIL=20270531", "2027-01-31", &utc),
+            ["2027-03-31", "2027-05-31"]
+        );
+        assert_eq!(
+            dates("FREQ=YEARLY;COUNT=3", "2028-02-29", &utc),
+            ["2032-02-29", "2036-02-29"]
+        );
+    }
+
+    #[test]
+    fn test_occurrences_keep_the_local_time_of_day() {
+        // New York leaves daylight saving time on 2026-11-01
+        let new_york = calendar("America/New_York");
+        let due = Due {
+            at: Utc.with_ymd_and_hms(2026, 10, 31, 13, 0, 0).unwrap(),
+            all_day: false,
+        };
+        let rule = Recurrence::parse("FREQ=DAILY;UNTIL=20261101T140000Z").unwrap();
+        let next: Vec<Due> = rule.occurrences(due, &new_york).collect();
+        assert_eq!(
+            next,
+            [Due {
+                at: Utc.with_ymd_and_hms(2026, 11, 1, 14, 0, 0).unwrap(),
+                all_day: false
+            }]
+        );
+    }
+}
@@ -0,0 +1,86 @@
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Whether personal data and task content are masked in logs (on by default)
+static REDACTION_ENABLED: AtomicBool = AtomicBool::new(true);
+
+/// Turn log redaction on or off for the whole process
+pub fn set_enabled(enabled: bool) {
+    REDACTION_ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Whether log redaction is currently on
+pub fn is_enabled() -> bool {
+    REDACTION_ENABLED.load(Ordering::Relaxed)
+}
+
+/// Log-safe view of an email address: `jane.doe@example.com` becomes `j***@example.com`
+pub fn email(value: &str) -> Redacted<'_> {
+    Redacted {
+        value,
+        kind: Kind::Email,
+    }
+}
+
+/// Log-safe view of user-written content such as task titles or search terms
+pub fn text(value: &str) -> Redacted<'_> {
+    Redacted {
+        value,
+        kind: Kind::Text,
+    }
+}
+
+enum Kind {
+    Email,
+    Text,
+}
+
+/// A value that is masked when displayed, unless redaction is disabled
+pub struct Redacted<'a> {
+    value: &'a str,
+    kind: Kind,
+}
+
+impl fmt::Display for Redacted<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !is_enabled() {
+            return f.write_str(self.value);
+        }
+
+        match self.kind {
+            Kind::Email => match self.value.split_once('@') {
+                Some((local, domain)) => match local.chars().next() {
+                    Some(first) => write!(f, "{}***@{}", first, domain),
+                    None => write!(f, "***@{}", domain),
+                },
+                None => f.write_str("***"),
+            },
+            Kind::Text => write!(f, "[redacted {} chars]", self.value.chars().count()),
+        }
+    }
+}
+
+impl fmt::Debug for Redacted<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_email_keeps_first_character_and_domain() {
+        assert_eq!(email("jane.doe@example.com").to_string(), "j***@example.com");
+        assert_eq!(email("@example.com").to_string(), "***@example.com");
+        assert_eq!(email("not-an-email").to_string(), "***");
+    }
+
+    #[test]
+    fn test_text_only_reveals_length() {
+        assert_eq!(text("Buy milk").to_string(), "[redacted 8 chars]");
+        assert_eq!(text("日本語").to_string(), "[redacted 3 chars]");
+        assert_eq!(format!("{:?}", text("secret")), "[redacted 6 chars]");
+    }
+}
@@ -0,0 +1,418 @@
+//! Reminder delivery.
+//!
+//! The [`Scheduler`] polls for reminders that are due and hands each to the
+//! [`Channel`] registered for its [`ReminderChannel`]. Before sending, it claims
+//! the delivery for the reminder's fire time in the database, so a reminder
+//! goes out at most once per fire time even with several servers polling the
+//! same database. Failed deliveries release their claim and are retried on
+//! later polls until `reminders.max_attempts` is reached.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use std::collections::HashMap;
+use std::fmt;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::config::RemindersConfig;
+use crate::models::{Notification, Reminder, ReminderChannel, Task};
+use crate::redact;
+use crate::repository::Repository;
+
+/// Longest delivery error kept on a reminder, in characters
+const MAX_ERROR_LENGTH: usize = 1000;
+
+/// Why a reminder could not be delivered
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeliveryError {
+    pub message: String,
+}
+
+impl DeliveryError {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for DeliveryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for DeliveryError {}
+
+/// A way of delivering reminders
+#[async_trait]
+pub trait Channel: Send + Sync {
+    async fn deliver(&self, reminder: &Reminder, task: &Task) -> Result<(), DeliveryError>;
+}
+
+/// An email to send
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Email {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/// Sends emails, over SMTP or through a provider's API
+#[async_trait]
+pub trait Mailer: Send + Sync {
+    async fn send(&self, email: &Email) -> Result<(), DeliveryError>;
+}
+
+/// Logs emails instead of sending them, until a real [`Mailer`] is plugged in
+pub struct LogMailer;
+
+#[async_trait]
+impl Mailer for LogMailer {
+    async fn send(&self, email: &Email) -> Result<(), DeliveryError> {
+        tracing::info!(
+            to = %redact::email(&email.to),
+            "Not sending email (no mailer configured): {}",
+            redact::text(&email.subject)
+        );
+        Ok(())
+    }
+}
+
+/// What a reminder says about its task
+fn reminder_text(task: &Task) -> String {
+    match task.due_at {
+        Some(due_at) => format!("Reminder: {} (due {})", task.title, due_at.to_rfc3339()),
+        None => format!("Reminder: {}", task.title),
+    }
+}
+
+/// Reminders by email, to the address stored on the reminder
+pub struct EmailChannel {
+    mailer: Arc<dyn Mailer>,
+}
+
+impl EmailChannel {
+    pub fn new(mailer: Arc<dyn Mailer>) -> Self {
+        Self { mailer }
+    }
+}
+
+#[async_trait]
+impl Channel for EmailChannel {
+    async fn deliver(&self, reminder: &Reminder, task: &Task) -> Result<(), DeliveryError> {
+        let to = reminder
+            .target
+            .clone()
+            .ok_or_else(|| DeliveryError::new("Email reminder has no address"))?;
+        let email = Email {
+            to,
+            subject: format!("Reminder: {}", task.title),
+            body: reminder_text(task),
+        };
+        self.mailer.send(&email).await
+    }
+}
+
+/// Whether webhooks may be sent to `ip`: not this host, a private network, a
+/// link-local address such as a cloud metadata service, or a multicast group
+pub fn is_public_address(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [first, second, ..] = ip.octets();
+            !(ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_multicast()
+                // "This network", 0.0.0.0/8
+                || first == 0
+                // Shared address space for carrier-grade NAT, 100.64.0.0/10
+                || (first == 100 && second & 0xc0 == 64))
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public_address(IpAddr::V4(ip)),
+            None => {
+                let first = ip.segments()[0];
+                !(ip.is_loopback()
+                    || ip.is_unspecified()
+                    || ip.is_multicast()
+                    // Unique local, fc00::/7
+                    || first & 0xfe00 == 0xfc00
+                    // Link-local, fe80::/10
+                    || first & 0xffc0 == 0xfe80)
+            }
+        },
+    }
+}
+
+/// Resolve `host`, failing unless it has addresses and all of them are public
+async fn resolve_public(host: &str, port: u16) -> Result<Vec<SocketAddr>, DeliveryError> {
+    let addresses: Vec<SocketAddr> = tokio::net::lookup_host((host, port))
+        .await
+        .map_err(|error| DeliveryError::new(format!("Cannot resolve {}: {}", host, error)))?
+        .collect();
+    if addresses.is_empty() {
+        return Err(DeliveryError::new(format!("Cannot resolve {}", host)));
+    }
+    if let Some(address) = addresses.iter().find(|address| !is_public_address(address.ip())) {
+        return Err(DeliveryError::new(format!(
+            "{} resolves to the non-public address {}",
+            host,
+            address.ip()
+        )));
+    }
+    Ok(addresses)
+}
+
+/// Fail unless a webhook may be sent to `url`: its host is checked as is when
+/// it is an IP address, and after resolving it otherwise
+pub async fn check_webhook_url(url: &reqwest::Url) -> Result<(), DeliveryError> {
+    let host = url
+        .host_str()
+        .ok_or_else(|| DeliveryError::new("Webhook URL has no host"))?;
+    // IPv6 hosts keep their brackets
+    match host.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>() {
+        Ok(ip) if !is_public_address(ip) => Err(DeliveryError::new(format!(
+            "{} is not a public address",
+            ip
+        ))),
+        Ok(_) => Ok(()),
+        Err(_) => {
+            let port = url.port_or_known_default().unwrap_or(80);
+            resolve_public(host, port).await.map(|_| ())
+        }
+    }
+}
+
+/// Resolves webhook hosts for the HTTP client, so that a name cannot be
+/// pointed at a non-public address after it was checked
+struct PublicResolver;
+
+impl reqwest::dns::Resolve for PublicResolver {
+    fn resolve(&self, name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+        Box::pin(async move {
+            let addresses = resolve_public(name.as_str(), 0).await?;
+            Ok(Box::new(addresses.into_iter()) as reqwest::dns::Addrs)
+        })
+    }
+}
+
+/// Reminders POSTed as JSON to the URL stored on the reminder
+pub struct WebhookChannel {
+    client: reqwest::Client,
+    allow_private: bool,
+}
+
+impl WebhookChannel {
+    /// A channel giving up on webhooks that take longer than `timeout`, and
+    /// refusing non-public addresses unless `allow_private`
+    pub fn new(timeout: Duration, allow_private: bool) -> Self {
+        let mut builder = reqwest::Client::builder()
+            .timeout(timeout)
+            // A redirect could lead anywhere, including back inside
+            .redirect(reqwest::redirect::Policy::none());
+        if !allow_private {
+            // A proxy would resolve the host itself
+            builder = builder.dns_resolver(Arc::new(PublicResolver)).no_proxy();
+        }
+        let client = builder
+            .build()
+            .expect("Failed to build the webhook HTTP client");
+        Self {
+            client,
+            allow_private,
+        }
+    }
+}
+
+#[async_trait]
+impl Channel for WebhookChannel {
+    async fn deliver(&self, reminder: &Reminder, task: &Task) -> Result<(), DeliveryError> {
+        let url = reminder
+            .target
+            .as_deref()
+            .ok_or_else(|| DeliveryError::new("Webhook reminder has no URL"))?;
+        let url = reqwest::Url::parse(url)
+            .map_err(|error| DeliveryError::new(format!("Invalid webhook URL: {}", error)))?;
+        if !self.allow_private {
+            // The resolver only sees names, not IP addresses
+            check_webhook_url(&url)
+                .await
+                .map_err(|error| DeliveryError::new(format!("Webhook refused: {}", error)))?;
+        }
+        let body = json!({
+            "event": "reminder",
+            "reminder_id": reminder.id,
+            "fire_at": reminder.fire_at,
+            "message": reminder_text(task),
+            "task": task,
+        });
+
+        let response = self
+            .client
+            .post(url)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|error| DeliveryError::new(format!("Webhook request failed: {}", error)))?;
+        if !response.status().is_success() {
+            return Err(DeliveryError::new(format!(
+                "Webhook answered {}",
+                response.status()
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Reminders added to the owner's notification list (`/notifications`)
+pub struct InAppChannel {
+    repository: Arc<dyn Repository>,
+}
+
+impl InAppChannel {
+    pub fn new(repository: Arc<dyn Repository>) -> Self {
+        Self { repository }
+    }
+}
+
+#[async_trait]
+impl Channel for InAppChannel {
+    async fn deliver(&self, reminder: &Reminder, task: &Task) -> Result<(), DeliveryError> {
+        let notification = Notification {
+            id: Uuid::now_v7(),
+            user_id: reminder.user_id,
+            task_id: Some(task.id),
+            message: reminder_text(task),
+            created_at: Some(Utc::now()),
+            read_at: None,
+        };
+        self.repository
+            .create_notification(&notification)
+            .await
+            .map_err(|error| DeliveryError::new(format!("Failed to store notification: {}", error)))
+    }
+}
+
+/// What one pass over the due reminders did
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DispatchReport {
+    pub delivered: usize,
+    pub failed: usize,
+    /// Claimed by another scheduler, or whose task was deleted meanwhile
+    pub skipped: usize,
+}
+
+/// Finds due reminders and delivers them through their channels
+pub struct Scheduler {
+    repository: Arc<dyn Repository>,
+    channels: HashMap<ReminderChannel, Arc<dyn Channel>>,
+    config: RemindersConfig,
+}
+
+impl Scheduler {
+    /// A scheduler delivering in-app, by webhook, and by email through `mailer`
+    pub fn new(repository: Arc<dyn Repository>, mailer: Arc<dyn Mailer>, config: RemindersConfig) -> Self {
+        let webhook = WebhookChannel::new(
+            Duration::from_secs(config.webhook_timeout_secs),
+            config.allow_private_webhooks,
+        );
+        let channels: HashMap<ReminderChannel, Arc<dyn Channel>> = HashMap::from([
+            (
+                ReminderChannel::InApp,
+                Arc::new(InAppChannel::new(repository.clone())) as Arc<dyn Channel>,
+            ),
+            (ReminderChannel::Email, Arc::new(EmailChannel::new(mailer))),
+            (ReminderChannel::Webhook, Arc::new(webhook)),
+        ]);
+        Self {
+            repository,
+            channels,
+            config,
+        }
+    }
+
+    /// Deliver reminders for `kind` through `channel` instead
+    pub fn with_channel(mut self, kind: ReminderChannel, channel: Arc<dyn Channel>) -> Self {
+        self.channels.insert(kind, channel);
+        self
+    }
+
+    /// Deliver up to `reminders.batch_size` reminders due by `now`
+    pub async fn dispatch_due(&self, now: DateTime<Utc>) -> Result<DispatchReport, sqlx::Error> {
+        let reminders = self
+            .repository
+            .due_reminders(
+                now,
+                i32::try_from(self.config.max_attempts).unwrap_or(i32::MAX),
+                i64::from(self.config.batch_size),
+            )
+            .await?;
+
+        let mut report = DispatchReport::default();
+        for reminder in reminders {
+            let Some(fire_at) = reminder.fire_at else {
+                continue;
+            };
+            if !self.repository.claim_delivery(reminder.id, fire_at).await? {
+                report.skipped += 1;
+                continue;
+            }
+            let task = match self.repository.get_task(reminder.task_id).await {
+                Ok(task) => task,
+                Err(sqlx::Error::RowNotFound) => {
+                    report.skipped += 1;
+                    continue;
+                }
+                Err(error) => return Err(error),
+            };
+
+            let channel = reminder.channel.name();
+            let result = match self.channels.get(&reminder.channel) {
+                Some(sender) => sender.deliver(&reminder, &task).await,
+                None => Err(DeliveryError::new(format!("No {} channel is configured", channel))),
+            };
+            match result {
+                Ok(()) => {
+                    self.repository.mark_delivered(reminder.id, Utc::now()).await?;
+                    metrics::counter!("reminder_deliveries_total", "channel" => channel, "outcome" => "delivered")
+                        .increment(1);
+                    report.delivered += 1;
+                }
+                Err(error) => {
+                    tracing::warn!(reminder_id = %reminder.id, channel, "Failed to deliver reminder: {}", error);
+                    let message: String = error.message.chars().take(MAX_ERROR_LENGTH).collect();
+                    self.repository.record_failure(reminder.id, fire_at, &message).await?;
+                    metrics::counter!("reminder_deliveries_total", "channel" => channel, "outcome" => "failed")
+                        .increment(1);
+                    report.failed += 1;
+                }
+            }
+        }
+        Ok(report)
+    }
+
+    /// Dispatch due reminders every `reminders.poll_interval_secs`, forever
+    pub async fn run(self) {
+        let mut interval = tokio::time::interval(Duration::from_secs(self.config.poll_interval_secs));
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            match self.dispatch_due(Utc::now()).await {
+                Ok(report) if report != DispatchReport::default() => tracing::info!(
+                    delivered = report.delivered,
+                    failed = report.failed,
+                    skipped = report.skipped,
+                    "Dispatched reminders"
+                ),
+                Ok(_) => {}
+                Err(error) => tracing::error!("Failed to dispatch reminders: {}", error),
+            }
+        }
+    }
+}
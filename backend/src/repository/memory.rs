@@ -0,0 +1,1053 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::cmp::Ordering;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+use uuid::Uuid;
+
+use super::{
+    searchable_text, DependencyRepository, Keyset, Migration, NewProject, NewReminder, NewTag, NewTask,
+    NotificationRepository, PoolStats, ProjectChanges, ProjectRepository, ReminderRepository, Repository,
+    SessionRepository, SortKey, TagChanges, TagRepository, TaskChanges, TaskFilter, TaskRepository,
+    TaskSort, UserRepository, ANONYMOUS_USER_ID,
+};
+use crate::models::{Notification, Project, Reminder, Session, SortOrder, SubtaskProgress, Tag, Task, User};
+use crate::schedule::DEFAULT_TIME_ZONE;
+
+/// Repository that keeps everything in process memory.
+///
+/// Behaves like the SQL backends (including `RowNotFound` for missing rows and
+/// the seeded anonymous task owner) so handlers can be tested without a database.
+#[derive(Default)]
+pub struct InMemoryRepository {
+    state: Mutex<State>,
+}
+
+/// The unique violation a database reports for a duplicate name, so that
+/// callers can tell it apart from other failures the same way
+#[derive(Debug)]
+struct UniqueViolation(&'static str);
+
+impl fmt::Display for UniqueViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+impl Error for UniqueViolation {}
+
+impl sqlx::error::DatabaseError for UniqueViolation {
+    fn message(&self) -> &str {
+        self.0
+    }
+
+    fn as_error(&self) -> &(dyn Error + Send + Sync + 'static) {
+        self
+    }
+
+    fn as_error_mut(&mut self) -> &mut (dyn Error + Send + Sync + 'static) {
+        self
+    }
+
+    fn into_error(self: Box<Self>) -> Box<dyn Error + Send + Sync + 'static> {
+        self
+    }
+
+    fn kind(&self) -> sqlx::error::ErrorKind {
+        sqlx::error::ErrorKind::UniqueViolation
+    }
+}
+
+fn unique_violation(message: &'static str) -> sqlx::Error {
+    sqlx::Error::Database(Box::new(UniqueViolation(message)))
+}
+
+#[derive(Default)]
+struct State {
+    tasks: Vec<Task>,
+    tags: HashMap<Uuid, Tag>,
+    /// `(task_id, tag_id)` pairs
+    task_tags: BTreeSet<(Uuid, Uuid)>,
+    projects: HashMap<Uuid, Project>,
+    /// `(task_id, blocker_id)` pairs
+    dependencies: BTreeSet<(Uuid, Uuid)>,
+    reminders: HashMap<Uuid, Reminder>,
+    /// Claimed `(reminder_id, fire_at)` deliveries
+    deliveries: BTreeSet<(Uuid, DateTime<Utc>)>,
+    notifications: Vec<Notification>,
+    users: HashMap<Uuid, User>,
+    sessions: HashMap<Uuid, Session>,
+}
+
+impl State {
+    /// Fail like a foreign key would when a task refers to a missing project
+    fn check_project(&self, project_id: Option<Uuid>) -> Result<(), sqlx::Error> {
+        match project_id {
+            Some(project_id) if !self.projects.contains_key(&project_id) => Err(
+                sqlx::Error::Protocol(format!("project {} does not exist", project_id)),
+            ),
+            _ => Ok(()),
+        }
+    }
+
+    /// Fail like a foreign key would when a task refers to a missing parent
+    fn check_parent(&self, parent_id: Option<Uuid>) -> Result<(), sqlx::Error> {
+        match parent_id {
+            Some(parent_id) if !self.tasks.iter().any(|task| task.id == parent_id) => Err(
+                sqlx::Error::Protocol(format!("task {} does not exist", parent_id)),
+            ),
+            _ => Ok(()),
+        }
+    }
+
+    /// Names of the tags on a task, alphabetically
+    fn tag_names(&self, task_id: Uuid) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .task_tags
+            .range((task_id, Uuid::nil())..=(task_id, Uuid::max()))
+            .filter_map(|(_, tag_id)| self.tags.get(tag_id))
+            .map(|tag| tag.name.clone())
+            .collect();
+        names.sort();
+        names
+    }
+
+    /// Fail like the foreign keys would when `task` refers to missing rows, or
+    /// the unique index when its position is taken
+    fn check_new_task(&self, task: &NewTask<'_>) -> Result<(), sqlx::Error> {
+        if !self.users.contains_key(&task.user_id) {
+            return Err(sqlx::Error::Protocol(format!("user {} does not exist", task.user_id)));
+        }
+        self.check_project(task.project_id)?;
+        self.check_parent(task.parent_id)?;
+        match task.position {
+            Some(position) => self.check_position(task.user_id, task.id, position),
+            None => Ok(()),
+        }
+    }
+
+    /// Fail like the unique index would when another of the user's tasks has
+    /// the position
+    fn check_position(&self, user_id: Uuid, id: Uuid, position: &str) -> Result<(), sqlx::Error> {
+        if self
+            .tasks
+            .iter()
+            .any(|task| task.user_id == user_id && task.id != id && task.position == position)
+        {
+            return Err(unique_violation("duplicate task position"));
+        }
+        Ok(())
+    }
+
+    fn insert_task(&mut self, task: &NewTask<'_>) -> Result<Task, sqlx::Error> {
+        self.check_new_task(task)?;
+
+        let last = self
+            .tasks
+            .iter()
+            .filter(|other| other.user_id == task.user_id)
+            .map(|other| other.position.as_str())
+            .max();
+        let task = task.into_task(Utc::now(), last);
+        self.tasks.push(task.clone());
+
+        Ok(task)
+    }
+
+    fn update_task(&mut self, id: Uuid, changes: &TaskChanges<'_>) -> Result<Task, sqlx::Error> {
+        if let Some(project_id) = changes.project_id {
+            self.check_project(project_id)?;
+        }
+        if let Some(parent_id) = changes.parent_id {
+            self.check_parent(parent_id)?;
+        }
+        if let Some(position) = changes.position {
+            let user_id = self
+                .tasks
+                .iter()
+                .find(|task| task.id == id)
+                .ok_or(sqlx::Error::RowNotFound)?
+                .user_id;
+            self.check_position(user_id, id, position)?;
+        }
+        let task = self
+            .tasks
+            .iter_mut()
+            .find(|task| task.id == id)
+            .ok_or(sqlx::Error::RowNotFound)?;
+
+        changes.apply(task);
+        task.updated_at = Some(Utc::now());
+
+        Ok(task.clone())
+    }
+
+    /// Ids of the tasks blocking a task
+    fn blocker_ids(&self, task_id: Uuid) -> impl Iterator<Item = Uuid> + '_ {
+        self.dependencies
+            .range((task_id, Uuid::nil())..=(task_id, Uuid::max()))
+            .map(|(_, blocker_id)| *blocker_id)
+    }
+
+    /// Whether `task_id` is `blocked_id` or blocks it, directly or through other tasks
+    fn blocks(&self, task_id: Uuid, blocked_id: Uuid) -> bool {
+        let mut seen = HashSet::from([blocked_id]);
+        let mut pending = vec![blocked_id];
+        while let Some(id) = pending.pop() {
+            if id == task_id {
+                return true;
+            }
+            pending.extend(self.blocker_ids(id).filter(|blocker_id| seen.insert(*blocker_id)));
+        }
+        false
+    }
+
+    /// Whether a task is blocked by one that is not completed
+    fn is_blocked(&self, task_id: Uuid) -> bool {
+        self.blocker_ids(task_id).any(|blocker_id| {
+            self.tasks
+                .iter()
+                .any(|task| task.id == blocker_id && !task.completed)
+        })
+    }
+
+    /// A stored tag with its current usage count
+    fn counted_tag(&self, tag: &Tag) -> Tag {
+        Tag {
+            task_count: self
+                .task_tags
+                .iter()
+                .filter(|(_, tag_id)| *tag_id == tag.id)
+                .count() as i64,
+            ..tag.clone()
+        }
+    }
+}
+
+impl InMemoryRepository {
+    /// An empty repository containing only the anonymous task owner
+    pub fn new() -> Self {
+        let repository = Self::default();
+        repository.state().users.insert(
+            ANONYMOUS_USER_ID,
+            User {
+                id: ANONYMOUS_USER_ID,
+                email: "anonymous@localhost".to_string(),
+                password_hash: "!".to_string(),
+                time_zone: DEFAULT_TIME_ZONE.to_string(),
+                created_at: Some(Utc::now()),
+                updated_at: Some(Utc::now()),
+            },
+        );
+        repository
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        // A panic while holding the lock cannot leave the maps half-updated
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Copies of the tasks passing `filter`, with their tags and whether they
+    /// are blocked, scored when it is a full-text search
+    fn matching_tasks(&self, filter: &TaskFilter) -> Vec<Task> {
+        let fulltext = filter.fulltext();
+        let state = self.state();
+        state
+            .tasks
+            .iter()
+            .map(|task| Task {
+                tags: state.tag_names(task.id),
+                blocked: state.is_blocked(task.id),
+                relevance: fulltext
+                    .as_ref()
+                    .and_then(|query| query.score(&searchable_text(task))),
+                ..task.clone()
+            })
+            .filter(|task| filter.matches(task))
+            .collect()
+    }
+}
+
+/// Order two listing positions the way the SQL backends do for `sort`
+fn compare_positions(a: &Keyset, b: &Keyset, sort: &TaskSort) -> Ordering {
+    let directed = |ordering: Ordering, order| match order {
+        SortOrder::Asc => ordering,
+        SortOrder::Desc => ordering.reverse(),
+    };
+
+    sort.columns
+        .iter()
+        .zip(a.keys.iter().zip(&b.keys))
+        .map(|(column, keys)| {
+            let ordering = match keys {
+                (SortKey::Timestamp(a), SortKey::Timestamp(b)) => a.cmp(b),
+                // Titles sort case-insensitively, like MySQL's default collation
+                (SortKey::Text(a), SortKey::Text(b)) => a.to_lowercase().cmp(&b.to_lowercase()),
+                (SortKey::Bool(a), SortKey::Bool(b)) => a.cmp(b),
+                (SortKey::Score(a), SortKey::Score(b)) => a.total_cmp(b),
+                (SortKey::Integer(a), SortKey::Integer(b)) => a.cmp(b),
+                // Missing values come last whatever the direction, unless reversed
+                (SortKey::Null, SortKey::Null) => Ordering::Equal,
+                (SortKey::Null, _) if column.nulls_first => return Ordering::Less,
+                (SortKey::Null, _) => return Ordering::Greater,
+                (_, SortKey::Null) if column.nulls_first => return Ordering::Greater,
+                (_, SortKey::Null) => return Ordering::Less,
+                _ => Ordering::Equal,
+            };
+            directed(ordering, column.order)
+        })
+        .fold(Ordering::Equal, Ordering::then)
+        .then(directed(a.id.cmp(&b.id), sort.id_order()))
+}
+
+/// Order two tasks the way the SQL backends do for `sort`
+fn compare_tasks(a: &Task, b: &Task, sort: &TaskSort) -> Ordering {
+    compare_positions(&Keyset::of(a, sort), &Keyset::of(b, sort), sort)
+}
+
+#[async_trait]
+impl TaskRepository for InMemoryRepository {
+    async fn create_task(&self, task: &NewTask<'_>) -> Result<Task, sqlx::Error> {
+        self.state().insert_task(task)
+    }
+
+    async fn get_task(&self, id: Uuid) -> Result<Task, sqlx::Error> {
+        self.state()
+            .tasks
+            .iter()
+            .find(|task| task.id == id)
+            .cloned()
+            .ok_or(sqlx::Error::RowNotFound)
+    }
+
+    async fn list_all_tasks(&self) -> Result<Vec<Task>, sqlx::Error> {
+        let mut tasks = self.state().tasks.clone();
+        tasks.sort_by(|a, b| compare_tasks(a, b, &TaskSort::default()));
+        Ok(tasks)
+    }
+
+    async fn count_tasks(&self, filter: &TaskFilter) -> Result<u64, sqlx::Error> {
+        Ok(self.matching_tasks(filter).len() as u64)
+    }
+
+    async fn count_tasks_up_to(&self, filter: &TaskFilter, limit: u64) -> Result<u64, sqlx::Error> {
+        let count = self.count_tasks(filter).await?;
+        Ok(count.min(limit))
+    }
+
+    async fn list_tasks(
+        &self,
+        filter: &TaskFilter,
+        sort: &TaskSort,
+        offset: u64,
+        limit: u64,
+    ) -> Result<Vec<Task>, sqlx::Error> {
+        let sort = sort.for_filter(filter);
+        let mut tasks = self.matching_tasks(filter);
+        tasks.sort_by(|a, b| compare_tasks(a, b, &sort));
+
+        Ok(tasks
+            .into_iter()
+            .skip(offset as usize)
+            .take(limit as usize)
+            .collect())
+    }
+
+    async fn list_tasks_after(
+        &self,
+        filter: &TaskFilter,
+        sort: &TaskSort,
+        after: Option<&Keyset>,
+        limit: u64,
+    ) -> Result<Vec<Task>, sqlx::Error> {
+        let sort = sort.for_filter(filter);
+        let mut tasks = self.matching_tasks(filter);
+        tasks.retain(|task| {
+            after.is_none_or(|after| {
+                compare_positions(&Keyset::of(task, &sort), after, &sort) == Ordering::Greater
+            })
+        });
+        tasks.sort_by(|a, b| compare_tasks(a, b, &sort));
+        tasks.truncate(limit as usize);
+
+        Ok(tasks)
+    }
+
+    async fn update_task(&self, id: Uuid, changes: &TaskChanges<'_>) -> Result<Task, sqlx::Error> {
+        self.state().update_task(id, changes)
+    }
+
+    async fn complete_recurring_task(
+        &self,
+        id: Uuid,
+        changes: &TaskChanges<'_>,
+        next: &NewTask<'_>,
+    ) -> Result<Option<Task>, sqlx::Error> {
+        let mut state = self.state();
+        if !state.tasks.iter().any(|task| task.id == id && !task.completed) {
+            return Ok(None);
+        }
+        // Check the next occurrence first, so a failure changes nothing
+        state.check_new_task(next)?;
+
+        let task = state.update_task(id, changes)?;
+        state.insert_task(next)?;
+        Ok(Some(task))
+    }
+
+    async fn delete_task(&self, id: Uuid) -> Result<bool, sqlx::Error> {
+        let mut state = self.state();
+        if !state.tasks.iter().any(|task| task.id == id) {
+            return Ok(false);
+        }
+
+        // Cascade to the subtasks, level by level
+        let mut deleted = BTreeSet::from([id]);
+        let mut level = vec![id];
+        while !level.is_empty() {
+            level = state
+                .tasks
+                .iter()
+                .filter(|task| task.parent_id.is_some_and(|parent_id| level.contains(&parent_id)))
+                .map(|task| task.id)
+                .collect();
+            deleted.extend(&level);
+        }
+        state.tasks.retain(|task| !deleted.contains(&task.id));
+        state.task_tags.retain(|(task_id, _)| !deleted.contains(task_id));
+        state.dependencies.retain(|(task_id, blocker_id)| {
+            !deleted.contains(task_id) && !deleted.contains(blocker_id)
+        });
+        state.reminders.retain(|_, reminder| !deleted.contains(&reminder.task_id));
+        let State { reminders, deliveries, .. } = &mut *state;
+        deliveries.retain(|(reminder_id, _)| reminders.contains_key(reminder_id));
+        state
+            .notifications
+            .retain(|notification| !notification.task_id.is_some_and(|task_id| deleted.contains(&task_id)));
+        Ok(true)
+    }
+
+    async fn subtask_ids(&self, parent_ids: &[Uuid]) -> Result<Vec<Uuid>, sqlx::Error> {
+        Ok(self
+            .state()
+            .tasks
+            .iter()
+            .filter(|task| task.parent_id.is_some_and(|parent_id| parent_ids.contains(&parent_id)))
+            .map(|task| task.id)
+            .collect())
+    }
+
+    async fn subtask_progress(
+        &self,
+        parent_ids: &[Uuid],
+    ) -> Result<HashMap<Uuid, SubtaskProgress>, sqlx::Error> {
+        let mut counts: HashMap<Uuid, (u64, u64)> = HashMap::new();
+        for task in &self.state().tasks {
+            if let Some(parent_id) = task.parent_id.filter(|id| parent_ids.contains(id)) {
+                let (total, completed) = counts.entry(parent_id).or_default();
+                *total += 1;
+                *completed += u64::from(task.completed);
+            }
+        }
+        Ok(counts
+            .into_iter()
+            .map(|(parent_id, (total, completed))| {
+                (parent_id, SubtaskProgress::new(total, completed))
+            })
+            .collect())
+    }
+
+    async fn reparent_subtasks(
+        &self,
+        parent_id: Uuid,
+        new_parent_id: Option<Uuid>,
+    ) -> Result<u64, sqlx::Error> {
+        let mut state = self.state();
+        state.check_parent(new_parent_id)?;
+
+        let now = Utc::now();
+        let mut moved = 0;
+        for task in &mut state.tasks {
+            if task.parent_id == Some(parent_id) {
+                task.parent_id = new_parent_id;
+                task.updated_at = Some(now);
+                moved += 1;
+            }
+        }
+        Ok(moved)
+    }
+
+    async fn adjacent_tasks(
+        &self,
+        task: &Task,
+        direction: SortOrder,
+        limit: i64,
+    ) -> Result<Vec<Task>, sqlx::Error> {
+        let key = |other: &Task| (other.position.clone(), other.id);
+        let mut tasks: Vec<Task> = self
+            .state()
+            .tasks
+            .iter()
+            .filter(|other| other.user_id == task.user_id)
+            .filter(|other| match direction {
+                SortOrder::Asc => key(other) > key(task),
+                SortOrder::Desc => key(other) < key(task),
+            })
+            .cloned()
+            .collect();
+        tasks.sort_by_key(key);
+        if direction == SortOrder::Desc {
+            tasks.reverse();
+        }
+        tasks.truncate(usize::try_from(limit).unwrap_or(0));
+        Ok(tasks)
+    }
+
+    async fn rebalance_positions(&self, user_id: Uuid) -> Result<u64, sqlx::Error> {
+        let mut state = self.state();
+        let mut tasks: Vec<&mut Task> = state.tasks.iter_mut().filter(|task| task.user_id == user_id).collect();
+        tasks.sort_by(|a, b| (&a.position, a.id).cmp(&(&b.position, b.id)));
+
+        let mut renumbered = 0;
+        let ranks = crate::rank::spread(tasks.len());
+        for (task, rank) in tasks.into_iter().zip(ranks) {
+            if task.position != rank {
+                task.position = rank;
+                renumbered += 1;
+            }
+        }
+        Ok(renumbered)
+    }
+}
+
+#[async_trait]
+impl TagRepository for InMemoryRepository {
+    async fn create_tag(&self, tag: &NewTag<'_>) -> Result<Tag, sqlx::Error> {
+        let mut state = self.state();
+        if state
+            .tags
+            .values()
+            .any(|existing| existing.user_id == tag.user_id && existing.name == tag.name)
+        {
+            return Err(unique_violation("duplicate tag name"));
+        }
+
+        let tag = tag.into_tag(Utc::now());
+        state.tags.insert(tag.id, tag.clone());
+
+        Ok(tag)
+    }
+
+    async fn get_tag(&self, id: Uuid) -> Result<Tag, sqlx::Error> {
+        let state = self.state();
+        let tag = state.tags.get(&id).ok_or(sqlx::Error::RowNotFound)?;
+        Ok(state.counted_tag(tag))
+    }
+
+    async fn list_tags(&self, user_id: Uuid) -> Result<Vec<Tag>, sqlx::Error> {
+        let state = self.state();
+        let mut tags: Vec<Tag> = state
+            .tags
+            .values()
+            .filter(|tag| tag.user_id == user_id)
+            .map(|tag| state.counted_tag(tag))
+            .collect();
+        tags.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(tags)
+    }
+
+    async fn update_tag(&self, id: Uuid, changes: &TagChanges<'_>) -> Result<Tag, sqlx::Error> {
+        let mut state = self.state();
+        let user_id = state.tags.get(&id).ok_or(sqlx::Error::RowNotFound)?.user_id;
+        if let Some(name) = changes.name {
+            if state
+                .tags
+                .values()
+                .any(|existing| existing.id != id && existing.user_id == user_id && existing.name == name)
+            {
+                return Err(unique_violation("duplicate tag name"));
+            }
+        }
+
+        let tag = state.tags.get_mut(&id).ok_or(sqlx::Error::RowNotFound)?;
+
+        if let Some(name) = changes.name {
+            tag.name = name.to_string();
+        }
+        if let Some(color) = changes.color {
+            tag.color = color.map(str::to_string);
+        }
+        tag.updated_at = Some(Utc::now());
+
+        let tag = tag.clone();
+        Ok(state.counted_tag(&tag))
+    }
+
+    async fn delete_tag(&self, id: Uuid) -> Result<bool, sqlx::Error> {
+        let mut state = self.state();
+        state.task_tags.retain(|(_, tag_id)| *tag_id != id);
+        Ok(state.tags.remove(&id).is_some())
+    }
+
+    async fn add_task_tags(&self, task_id: Uuid, tag_ids: &[Uuid]) -> Result<(), sqlx::Error> {
+        let mut state = self.state();
+        if !state.tasks.iter().any(|task| task.id == task_id) {
+            return Err(sqlx::Error::Protocol(format!("task {} does not exist", task_id)));
+        }
+        if let Some(missing) = tag_ids.iter().find(|tag_id| !state.tags.contains_key(tag_id)) {
+            return Err(sqlx::Error::Protocol(format!("tag {} does not exist", missing)));
+        }
+
+        state
+            .task_tags
+            .extend(tag_ids.iter().map(|tag_id| (task_id, *tag_id)));
+        Ok(())
+    }
+
+    async fn remove_task_tags(&self, task_id: Uuid, tag_ids: &[Uuid]) -> Result<(), sqlx::Error> {
+        let mut state = self.state();
+        for tag_id in tag_ids {
+            state.task_tags.remove(&(task_id, *tag_id));
+        }
+        Ok(())
+    }
+
+    async fn task_tag_names(
+        &self,
+        task_ids: &[Uuid],
+    ) -> Result<HashMap<Uuid, Vec<String>>, sqlx::Error> {
+        let state = self.state();
+        Ok(task_ids
+            .iter()
+            .map(|task_id| (*task_id, state.tag_names(*task_id)))
+            .filter(|(_, names)| !names.is_empty())
+            .collect())
+    }
+}
+
+#[async_trait]
+impl ProjectRepository for InMemoryRepository {
+    async fn create_project(&self, project: &NewProject<'_>) -> Result<Project, sqlx::Error> {
+        let mut state = self.state();
+        if state
+            .projects
+            .values()
+            .any(|existing| existing.user_id == project.user_id && existing.name == project.name)
+        {
+            return Err(unique_violation("duplicate project name"));
+        }
+
+        let project = project.into_project(Utc::now());
+        state.projects.insert(project.id, project.clone());
+
+        Ok(project)
+    }
+
+    async fn get_project(&self, id: Uuid) -> Result<Project, sqlx::Error> {
+        self.state()
+            .projects
+            .get(&id)
+            .cloned()
+            .ok_or(sqlx::Error::RowNotFound)
+    }
+
+    async fn list_projects(&self, user_id: Uuid) -> Result<Vec<Project>, sqlx::Error> {
+        let mut projects: Vec<Project> = self
+            .state()
+            .projects
+            .values()
+            .filter(|project| project.user_id == user_id)
+            .cloned()
+            .collect();
+        projects.sort_by_key(|project| (project.position, project.id));
+        Ok(projects)
+    }
+
+    async fn update_project(
+        &self,
+        id: Uuid,
+        changes: &ProjectChanges<'_>,
+    ) -> Result<Project, sqlx::Error> {
+        let mut state = self.state();
+        let user_id = state.projects.get(&id).ok_or(sqlx::Error::RowNotFound)?.user_id;
+        if let Some(name) = changes.name {
+            if state
+                .projects
+                .values()
+                .any(|existing| existing.id != id && existing.user_id == user_id && existing.name == name)
+            {
+                return Err(unique_violation("duplicate project name"));
+            }
+        }
+
+        let project = state.projects.get_mut(&id).ok_or(sqlx::Error::RowNotFound)?;
+        changes.apply(project);
+        project.updated_at = Some(Utc::now());
+
+        Ok(project.clone())
+    }
+
+    async fn delete_project(&self, id: Uuid) -> Result<bool, sqlx::Error> {
+        let mut state = self.state();
+        for task in &mut state.tasks {
+            if task.project_id == Some(id) {
+                task.project_id = None;
+            }
+        }
+        Ok(state.projects.remove(&id).is_some())
+    }
+}
+
+#[async_trait]
+impl DependencyRepository for InMemoryRepository {
+    async fn add_blocker(&self, task_id: Uuid, blocker_id: Uuid) -> Result<bool, sqlx::Error> {
+        let mut state = self.state();
+        if let Some(missing) = [task_id, blocker_id]
+            .into_iter()
+            .find(|id| !state.tasks.iter().any(|task| task.id == *id))
+        {
+            return Err(sqlx::Error::Protocol(format!("task {} does not exist", missing)));
+        }
+        if state.blocks(task_id, blocker_id) {
+            return Ok(false);
+        }
+
+        state.dependencies.insert((task_id, blocker_id));
+        Ok(true)
+    }
+
+    async fn remove_blocker(&self, task_id: Uuid, blocker_id: Uuid) -> Result<bool, sqlx::Error> {
+        Ok(self.state().dependencies.remove(&(task_id, blocker_id)))
+    }
+
+    async fn list_blockers(&self, task_id: Uuid) -> Result<Vec<Task>, sqlx::Error> {
+        let state = self.state();
+        let blocker_ids: HashSet<Uuid> = state.blocker_ids(task_id).collect();
+        let mut blockers: Vec<Task> = state
+            .tasks
+            .iter()
+            .filter(|task| blocker_ids.contains(&task.id))
+            .cloned()
+            .collect();
+        blockers.sort_by_key(|task| (task.created_at, task.id));
+        Ok(blockers)
+    }
+
+    async fn blocker_ids(&self, task_ids: &[Uuid]) -> Result<Vec<Uuid>, sqlx::Error> {
+        let state = self.state();
+        let blocker_ids: BTreeSet<Uuid> = task_ids
+            .iter()
+            .flat_map(|task_id| state.blocker_ids(*task_id))
+            .collect();
+        Ok(blocker_ids.into_iter().collect())
+    }
+
+    async fn blocked_task_ids(&self, task_ids: &[Uuid]) -> Result<HashSet<Uuid>, sqlx::Error> {
+        let state = self.state();
+        Ok(task_ids
+            .iter()
+            .copied()
+            .filter(|task_id| state.is_blocked(*task_id))
+            .collect())
+    }
+}
+
+#[async_trait]
+impl ReminderRepository for InMemoryRepository {
+    async fn create_reminder(&self, reminder: &NewReminder<'_>) -> Result<Reminder, sqlx::Error> {
+        let mut state = self.state();
+        if !state.tasks.iter().any(|task| task.id == reminder.task_id) {
+            return Err(sqlx::Error::Protocol(format!("task {} does not exist", reminder.task_id)));
+        }
+
+        let reminder = reminder.into_reminder(Utc::now());
+        state.reminders.insert(reminder.id, reminder.clone());
+
+        Ok(reminder)
+    }
+
+    async fn get_reminder(&self, id: Uuid) -> Result<Reminder, sqlx::Error> {
+        self.state()
+            .reminders
+            .get(&id)
+            .cloned()
+            .ok_or(sqlx::Error::RowNotFound)
+    }
+
+    async fn list_reminders(&self, task_id: Uuid) -> Result<Vec<Reminder>, sqlx::Error> {
+        let mut reminders: Vec<Reminder> = self
+            .state()
+            .reminders
+            .values()
+            .filter(|reminder| reminder.task_id == task_id)
+            .cloned()
+            .collect();
+        reminders.sort_by_key(|reminder| reminder.id);
+        Ok(reminders)
+    }
+
+    async fn delete_reminder(&self, id: Uuid) -> Result<bool, sqlx::Error> {
+        let mut state = self.state();
+        state.deliveries.retain(|(reminder_id, _)| *reminder_id != id);
+        Ok(state.reminders.remove(&id).is_some())
+    }
+
+    async fn schedule_reminder(
+        &self,
+        id: Uuid,
+        fire_at: Option<DateTime<Utc>>,
+    ) -> Result<Reminder, sqlx::Error> {
+        let mut state = self.state();
+        state.deliveries.retain(|(reminder_id, _)| *reminder_id != id);
+        let reminder = state.reminders.get_mut(&id).ok_or(sqlx::Error::RowNotFound)?;
+
+        reminder.fire_at = fire_at;
+        reminder.delivered_at = None;
+        reminder.attempts = 0;
+        reminder.last_error = None;
+        reminder.updated_at = Some(Utc::now());
+
+        Ok(reminder.clone())
+    }
+
+    async fn due_reminders(
+        &self,
+        now: DateTime<Utc>,
+        max_attempts: i32,
+        limit: i64,
+    ) -> Result<Vec<Reminder>, sqlx::Error> {
+        let state = self.state();
+        let mut reminders: Vec<Reminder> = state
+            .reminders
+            .values()
+            .filter(|reminder| {
+                reminder.delivered_at.is_none()
+                    && reminder.attempts < max_attempts
+                    && reminder.fire_at.is_some_and(|fire_at| fire_at <= now)
+                    && state
+                        .tasks
+                        .iter()
+                        .any(|task| task.id == reminder.task_id && !task.completed)
+            })
+            .cloned()
+            .collect();
+        reminders.sort_by_key(|reminder| (reminder.fire_at, reminder.id));
+        reminders.truncate(limit.max(0) as usize);
+        Ok(reminders)
+    }
+
+    async fn claim_delivery(&self, id: Uuid, fire_at: DateTime<Utc>) -> Result<bool, sqlx::Error> {
+        Ok(self.state().deliveries.insert((id, fire_at)))
+    }
+
+    async fn mark_delivered(&self, id: Uuid, delivered_at: DateTime<Utc>) -> Result<(), sqlx::Error> {
+        if let Some(reminder) = self.state().reminders.get_mut(&id) {
+            reminder.delivered_at = Some(delivered_at);
+            reminder.last_error = None;
+            reminder.updated_at = Some(Utc::now());
+        }
+        Ok(())
+    }
+
+    async fn record_failure(
+        &self,
+        id: Uuid,
+        fire_at: DateTime<Utc>,
+        error: &str,
+    ) -> Result<(), sqlx::Error> {
+        let mut state = self.state();
+        if let Some(reminder) = state.reminders.get_mut(&id) {
+            reminder.attempts += 1;
+            reminder.last_error = Some(error.to_string());
+            reminder.updated_at = Some(Utc::now());
+        }
+        state.deliveries.remove(&(id, fire_at));
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl NotificationRepository for InMemoryRepository {
+    async fn create_notification(&self, notification: &Notification) -> Result<(), sqlx::Error> {
+        self.state().notifications.push(notification.clone());
+        Ok(())
+    }
+
+    async fn list_notifications(
+        &self,
+        user_id: Uuid,
+        unread_only: bool,
+    ) -> Result<Vec<Notification>, sqlx::Error> {
+        let mut notifications: Vec<Notification> = self
+            .state()
+            .notifications
+            .iter()
+            .filter(|notification| notification.user_id == user_id)
+            .filter(|notification| !unread_only || notification.read_at.is_none())
+            .cloned()
+            .collect();
+        notifications.sort_by_key(|notification| std::cmp::Reverse(notification.id));
+        Ok(notifications)
+    }
+
+    async fn mark_notification_read(&self, id: Uuid, read_at: DateTime<Utc>) -> Result<bool, sqlx::Error> {
+        let mut state = self.state();
+        match state.notifications.iter_mut().find(|notification| notification.id == id) {
+            Some(notification) => {
+                notification.read_at.get_or_insert(read_at);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+}
+
+#[async_trait]
+impl UserRepository for InMemoryRepository {
+    async fn create_user(
+        &self,
+        id: Uuid,
+        email: &str,
+        password_hash: &str,
+        time_zone: &str,
+    ) -> Result<User, sqlx::Error> {
+        let mut state = self.state();
+        if state.users.values().any(|user| user.email == email) {
+            return Err(sqlx::Error::Protocol(format!("duplicate email {}", email)));
+        }
+
+        let now = Utc::now();
+        let user = User {
+            id,
+            email: email.to_string(),
+            password_hash: password_hash.to_string(),
+            time_zone: time_zone.to_string(),
+            created_at: Some(now),
+            updated_at: Some(now),
+        };
+        state.users.insert(id, user.clone());
+
+        Ok(user)
+    }
+
+    async fn get_user_by_id(&self, user_id: Uuid) -> Result<User, sqlx::Error> {
+        self.state()
+            .users
+            .get(&user_id)
+            .cloned()
+            .ok_or(sqlx::Error::RowNotFound)
+    }
+
+    async fn get_user_by_email(&self, email: &str) -> Result<Option<User>, sqlx::Error> {
+        Ok(self
+            .state()
+            .users
+            .values()
+            .find(|user| user.email == email)
+            .cloned())
+    }
+
+    async fn count_users(&self) -> Result<u64, sqlx::Error> {
+        Ok(self.state().users.len() as u64)
+    }
+
+    async fn update_user_time_zone(&self, user_id: Uuid, time_zone: &str) -> Result<User, sqlx::Error> {
+        let mut state = self.state();
+        let user = state.users.get_mut(&user_id).ok_or(sqlx::Error::RowNotFound)?;
+        user.time_zone = time_zone.to_string();
+        user.updated_at = Some(Utc::now());
+        Ok(user.clone())
+    }
+}
+
+#[async_trait]
+impl SessionRepository for InMemoryRepository {
+    async fn create_session(
+        &self,
+        id: Uuid,
+        user_id: Uuid,
+        token_hash: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<Session, sqlx::Error> {
+        let mut state = self.state();
+        if !state.users.contains_key(&user_id) {
+            return Err(sqlx::Error::Protocol(format!("user {} does not exist", user_id)));
+        }
+
+        let session = Session {
+            id,
+            user_id,
+            token_hash: token_hash.to_string(),
+            expires_at,
+            created_at: Some(Utc::now()),
+        };
+        state.sessions.insert(id, session.clone());
+
+        Ok(session)
+    }
+
+    async fn get_session_by_id(&self, session_id: Uuid) -> Result<Session, sqlx::Error> {
+        self.state()
+            .sessions
+            .get(&session_id)
+            .cloned()
+            .ok_or(sqlx::Error::RowNotFound)
+    }
+
+    async fn get_session_by_token(&self, token_hash: &str) -> Result<Option<Session>, sqlx::Error> {
+        let now = Utc::now();
+        Ok(self
+            .state()
+            .sessions
+            .values()
+            .find(|session| session.token_hash == token_hash && session.expires_at > now)
+            .cloned())
+    }
+
+    async fn delete_session(&self, session_id: Uuid) -> Result<(), sqlx::Error> {
+        self.state().sessions.remove(&session_id);
+        Ok(())
+    }
+
+    async fn cleanup_expired_sessions(&self) -> Result<u64, sqlx::Error> {
+        let now = Utc::now();
+        let mut state = self.state();
+        let before = state.sessions.len();
+        state.sessions.retain(|_, session| session.expires_at > now);
+        Ok((before - state.sessions.len()) as u64)
+    }
+}
+
+#[async_trait]
+impl Repository for InMemoryRepository {
+    fn backend(&self) -> &'static str {
+        "memory"
+    }
+
+    fn pool_stats(&self) -> PoolStats {
+        PoolStats::default()
+    }
+
+    async fn ping(&self) -> Result<(), sqlx::Error> {
+        Ok(())
+    }
+
+    async fn acquire_wait(&self) -> Result<Duration, sqlx::Error> {
+        Ok(Duration::ZERO)
+    }
+
+    fn migrations(&self) -> &'static [Migration] {
+        &[]
+    }
+
+    async fn migrate_to(&self, _target: Option<i64>) -> Result<(), sqlx::Error> {
+        Ok(())
+    }
+
+    async fn pending_migrations(&self) -> Result<Vec<&'static Migration>, sqlx::Error> {
+        Ok(Vec::new())
+    }
+}
@@ -0,0 +1,463 @@
+use sqlx::{mysql::MySqlPoolOptions, MySql, MySqlPool};
+use std::time::Duration;
+
+use super::sql::{sql_repository, Dialect};
+use super::Migration;
+use crate::config::DatabaseConfig;
+use crate::search::SearchQuery;
+
+/// Repository backed by a MySQL (or MariaDB) connection pool
+#[derive(Clone)]
+pub struct MySqlRepository {
+    pool: MySqlPool,
+}
+
+impl MySqlRepository {
+    /// Wrap an existing pool
+    pub fn new(pool: MySqlPool) -> Self {
+        Self { pool }
+    }
+
+    /// Create a MySQL connection pool from the database settings
+    #[tracing::instrument(skip_all, fields(db.system = "mysql", db.operation = "CONNECT"))]
+    pub async fn connect(config: &DatabaseConfig) -> Result<Self, sqlx::Error> {
+        let pool = MySqlPoolOptions::new()
+            .max_connections(config.max_connections)
+            .acquire_timeout(Duration::from_secs(config.acquire_timeout_secs))
+            .connect(&config.url)
+            .await?;
+
+        Ok(Self::new(pool))
+    }
+
+    /// The underlying connection pool
+    pub fn pool(&self) -> &MySqlPool {
+        &self.pool
+    }
+}
+
+sql_repository!(MySqlRepository, MySql, DIALECT);
+
+const DIALECT: Dialect = Dialect {
+    system: "mysql",
+    like: "LIKE",
+    returning: false,
+    insert_ignore: ["INSERT IGNORE INTO", ""],
+    fulltext_query: SearchQuery::to_mysql,
+    // The column list must match a FULLTEXT index exactly
+    fulltext_match: ["MATCH (title, description) AGAINST (", " IN BOOLEAN MODE)"],
+    fulltext_rank: ["MATCH (title, description) AGAINST (", " IN BOOLEAN MODE)"],
+    create_migrations_table: r#"
+        CREATE TABLE IF NOT EXISTS schema_migrations (
+            version BIGINT PRIMARY KEY,
+            description VARCHAR(255) NOT NULL,
+            applied_at TIMESTAMP(6) DEFAULT CURRENT_TIMESTAMP(6)
+        )
+        "#,
+    migrations_table_exists: r#"
+        SELECT COUNT(*) AS count
+        FROM information_schema.tables
+        WHERE table_schema = DATABASE() AND table_name = 'schema_migrations'
+        "#,
+    migrations: MIGRATIONS,
+};
+
+/// Schema migrations in the order they must be applied
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "create users, sessions and tasks tables",
+        statements: &[
+            r#"
+            CREATE TABLE IF NOT EXISTS users (
+                id CHAR(36) PRIMARY KEY,
+                email VARCHAR(255) NOT NULL UNIQUE,
+                password_hash VARCHAR(255) NOT NULL,
+                created_at TIMESTAMP(6) DEFAULT CURRENT_TIMESTAMP(6),
+                updated_at TIMESTAMP(6) DEFAULT CURRENT_TIMESTAMP(6) ON UPDATE CURRENT_TIMESTAMP(6),
+                INDEX idx_users_email (email)
+            )
+            "#,
+            r#"
+            CREATE TABLE IF NOT EXISTS sessions (
+                id CHAR(36) PRIMARY KEY,
+                user_id CHAR(36) NOT NULL,
+                token_hash VARCHAR(255) NOT NULL,
+                expires_at TIMESTAMP(6) NOT NULL,
+                created_at TIMESTAMP(6) DEFAULT CURRENT_TIMESTAMP(6),
+                FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+                INDEX idx_sessions_token_hash (token_hash),
+                INDEX idx_sessions_user_id (user_id),
+                INDEX idx_sessions_expires_at (expires_at)
+            )
+            "#,
+            r#"
+            CREATE TABLE IF NOT EXISTS tasks (
+                id CHAR(36) PRIMARY KEY,
+                title VARCHAR(255) NOT NULL,
+                completed BOOLEAN NOT NULL DEFAULT FALSE,
+                user_id CHAR(36) NOT NULL,
+                created_at TIMESTAMP(6) DEFAULT CURRENT_TIMESTAMP(6),
+                updated_at TIMESTAMP(6) DEFAULT CURRENT_TIMESTAMP(6) ON UPDATE CURRENT_TIMESTAMP(6),
+                FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+                INDEX idx_tasks_user_id (user_id),
+                INDEX idx_tasks_created_at (created_at),
+                INDEX idx_tasks_completed (completed)
+            )
+            "#,
+        ],
+        backfill: &[],
+    },
+    Migration {
+        version: 2,
+        description: "add owner for tasks created without a user",
+        statements: &[r#"
+            INSERT IGNORE INTO users (id, email, password_hash)
+            VALUES ('00000000-0000-0000-0000-000000000000', 'anonymous@localhost', '!')
+            "#],
+        backfill: &[],
+    },
+    // Keys move from CHAR(36) to BINARY(16) in two steps so large tables can be
+    // converted while the previous release keeps serving: v3 only adds nullable
+    // columns (an instant change) and fills them in batches, v4 copies any rows
+    // written since and swaps the columns over. Keys that are not UUIDs are left
+    // unconverted, and v4 then fails before changing anything.
+    Migration {
+        version: 3,
+        description: "add binary key columns",
+        statements: &[
+            "ALTER TABLE users ADD COLUMN id_bin BINARY(16) NULL",
+            "ALTER TABLE sessions ADD COLUMN id_bin BINARY(16) NULL, ADD COLUMN user_id_bin BINARY(16) NULL",
+            "ALTER TABLE tasks ADD COLUMN id_bin BINARY(16) NULL, ADD COLUMN user_id_bin BINARY(16) NULL",
+        ],
+        backfill: &[
+            r#"
+            UPDATE users SET id_bin = UNHEX(REPLACE(id, '-', ''))
+            WHERE id_bin IS NULL AND LENGTH(UNHEX(REPLACE(id, '-', ''))) = 16
+            LIMIT 1000
+            "#,
+            r#"
+            UPDATE sessions
+            SET id_bin = UNHEX(REPLACE(id, '-', '')), user_id_bin = UNHEX(REPLACE(user_id, '-', ''))
+            WHERE id_bin IS NULL
+                AND LENGTH(UNHEX(REPLACE(id, '-', ''))) = 16
+                AND LENGTH(UNHEX(REPLACE(user_id, '-', ''))) = 16
+            LIMIT 1000
+            "#,
+            r#"
+            UPDATE tasks
+            SET id_bin = UNHEX(REPLACE(id, '-', '')), user_id_bin = UNHEX(REPLACE(user_id, '-', ''))
+            WHERE id_bin IS NULL
+                AND LENGTH(UNHEX(REPLACE(id, '-', ''))) = 16
+                AND LENGTH(UNHEX(REPLACE(user_id, '-', ''))) = 16
+            LIMIT 1000
+            "#,
+        ],
+    },
+    Migration {
+        version: 4,
+        description: "switch keys to BINARY(16)",
+        statements: &[
+            // Rows written by the previous release since version 3 was applied
+            r#"
+            UPDATE users SET id_bin = UNHEX(REPLACE(id, '-', ''))
+            WHERE id_bin IS NULL AND LENGTH(UNHEX(REPLACE(id, '-', ''))) = 16
+            "#,
+            r#"
+            UPDATE sessions
+            SET id_bin = UNHEX(REPLACE(id, '-', '')), user_id_bin = UNHEX(REPLACE(user_id, '-', ''))
+            WHERE id_bin IS NULL
+                AND LENGTH(UNHEX(REPLACE(id, '-', ''))) = 16
+                AND LENGTH(UNHEX(REPLACE(user_id, '-', ''))) = 16
+            "#,
+            r#"
+            UPDATE tasks
+            SET id_bin = UNHEX(REPLACE(id, '-', '')), user_id_bin = UNHEX(REPLACE(user_id, '-', ''))
+            WHERE id_bin IS NULL
+                AND LENGTH(UNHEX(REPLACE(id, '-', ''))) = 16
+                AND LENGTH(UNHEX(REPLACE(user_id, '-', ''))) = 16
+            "#,
+            // Fails on any key left unconverted, while the migration can still be
+            // rerun once the offending rows are fixed
+            "ALTER TABLE users MODIFY id_bin BINARY(16) NOT NULL",
+            "ALTER TABLE sessions MODIFY id_bin BINARY(16) NOT NULL, MODIFY user_id_bin BINARY(16) NOT NULL",
+            "ALTER TABLE tasks MODIFY id_bin BINARY(16) NOT NULL, MODIFY user_id_bin BINARY(16) NOT NULL",
+            "ALTER TABLE sessions DROP FOREIGN KEY sessions_ibfk_1",
+            "ALTER TABLE tasks DROP FOREIGN KEY tasks_ibfk_1",
+            r#"
+            ALTER TABLE users
+                DROP PRIMARY KEY,
+                DROP COLUMN id,
+                CHANGE COLUMN id_bin id BINARY(16) NOT NULL,
+                ADD PRIMARY KEY (id)
+            "#,
+            r#"
+            ALTER TABLE sessions
+                DROP PRIMARY KEY,
+                DROP INDEX idx_sessions_user_id,
+                DROP COLUMN id,
+                DROP COLUMN user_id,
+                CHANGE COLUMN id_bin id BINARY(16) NOT NULL,
+                CHANGE COLUMN user_id_bin user_id BINARY(16) NOT NULL,
+                ADD PRIMARY KEY (id),
+                ADD INDEX idx_sessions_user_id (user_id)
+            "#,
+            r#"
+            ALTER TABLE tasks
+                DROP PRIMARY KEY,
+                DROP INDEX idx_tasks_user_id,
+                DROP COLUMN id,
+                DROP COLUMN user_id,
+                CHANGE COLUMN id_bin id BINARY(16) NOT NULL,
+                CHANGE COLUMN user_id_bin user_id BINARY(16) NOT NULL,
+                ADD PRIMARY KEY (id),
+                ADD INDEX idx_tasks_user_id (user_id)
+            "#,
+            r#"
+            ALTER TABLE sessions ADD CONSTRAINT fk_sessions_user
+                FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+            "#,
+            r#"
+            ALTER TABLE tasks ADD CONSTRAINT fk_tasks_user
+                FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+            "#,
+        ],
+        backfill: &[],
+    },
+    Migration {
+        version: 5,
+        description: "add full-text index on task titles",
+        statements: &["ALTER TABLE tasks ADD FULLTEXT INDEX ft_tasks_title (title)"],
+        backfill: &[],
+    },
+    Migration {
+        version: 6,
+        description: "add task descriptions to tasks and the full-text index",
+        statements: &[
+            "ALTER TABLE tasks ADD COLUMN description TEXT NULL AFTER title",
+            r#"
+            ALTER TABLE tasks
+                DROP INDEX ft_tasks_title,
+                ADD FULLTEXT INDEX ft_tasks_text (title, description)
+            "#,
+        ],
+        backfill: &[],
+    },
+    Migration {
+        version: 7,
+        description: "add due and start dates to tasks and time zones to users",
+        statements: &[
+            r#"
+            ALTER TABLE tasks
+                ADD COLUMN due_at DATETIME(6) NULL,
+                ADD COLUMN due_all_day BOOLEAN NOT NULL DEFAULT FALSE,
+                ADD COLUMN start_at DATETIME(6) NULL,
+                ADD INDEX idx_tasks_due_at (due_at)
+            "#,
+            "ALTER TABLE users ADD COLUMN time_zone VARCHAR(64) NOT NULL DEFAULT 'UTC' AFTER password_hash",
+        ],
+        backfill: &[],
+    },
+    Migration {
+        version: 8,
+        description: "add priorities to tasks",
+        statements: &[
+            r#"
+            ALTER TABLE tasks
+                ADD COLUMN priority SMALLINT NOT NULL DEFAULT 0,
+                ADD INDEX idx_tasks_priority (priority)
+            "#,
+        ],
+        backfill: &[],
+    },
+    Migration {
+        version: 9,
+        description: "create tags and task_tags tables",
+        statements: &[
+            r#"
+            CREATE TABLE IF NOT EXISTS tags (
+                id BINARY(16) PRIMARY KEY,
+                user_id BINARY(16) NOT NULL,
+                name VARCHAR(50) NOT NULL,
+                color CHAR(7) NULL,
+                created_at TIMESTAMP(6) NULL,
+                updated_at TIMESTAMP(6) NULL,
+                UNIQUE INDEX uq_tags_user_name (user_id, name),
+                CONSTRAINT fk_tags_user FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+            )
+            "#,
+            r#"
+            CREATE TABLE IF NOT EXISTS task_tags (
+                task_id BINARY(16) NOT NULL,
+                tag_id BINARY(16) NOT NULL,
+                PRIMARY KEY (task_id, tag_id),
+                INDEX idx_task_tags_tag_id (tag_id),
+                CONSTRAINT fk_task_tags_task FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE,
+                CONSTRAINT fk_task_tags_tag FOREIGN KEY (tag_id) REFERENCES tags(id) ON DELETE CASCADE
+            )
+            "#,
+        ],
+        backfill: &[],
+    },
+    Migration {
+        version: 10,
+        description: "create projects table and add projects to tasks",
+        statements: &[
+            r#"
+            CREATE TABLE IF NOT EXISTS projects (
+                id BINARY(16) PRIMARY KEY,
+                user_id BINARY(16) NOT NULL,
+                name VARCHAR(100) NOT NULL,
+                color CHAR(7) NULL,
+                archived BOOLEAN NOT NULL DEFAULT FALSE,
+                position INT NOT NULL DEFAULT 0,
+                created_at TIMESTAMP(6) NULL,
+                updated_at TIMESTAMP(6) NULL,
+                UNIQUE INDEX uq_projects_user_name (user_id, name),
+                INDEX idx_projects_user_position (user_id, position),
+                CONSTRAINT fk_projects_user FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+            )
+            "#,
+            r#"
+            ALTER TABLE tasks
+                ADD COLUMN project_id BINARY(16) NULL,
+                ADD INDEX idx_tasks_project_id (project_id),
+                ADD CONSTRAINT fk_tasks_project FOREIGN KEY (project_id) REFERENCES projects(id) ON DELETE SET NULL
+            "#,
+        ],
+        backfill: &[],
+    },
+    Migration {
+        version: 11,
+        description: "add subtasks to tasks",
+        statements: &[
+            r#"
+            ALTER TABLE tasks
+                ADD COLUMN parent_id BINARY(16) NULL,
+                ADD COLUMN auto_complete BOOLEAN NOT NULL DEFAULT FALSE,
+                ADD INDEX idx_tasks_parent_id (parent_id),
+                ADD CONSTRAINT fk_tasks_parent FOREIGN KEY (parent_id) REFERENCES tasks(id) ON DELETE CASCADE
+            "#,
+        ],
+        backfill: &[],
+    },
+    Migration {
+        version: 12,
+        description: "create task_dependencies table",
+        statements: &[
+            r#"
+            CREATE TABLE IF NOT EXISTS task_dependencies (
+                task_id BINARY(16) NOT NULL,
+                blocker_id BINARY(16) NOT NULL,
+                PRIMARY KEY (task_id, blocker_id),
+                INDEX idx_task_dependencies_blocker_id (blocker_id),
+                CONSTRAINT fk_task_dependencies_task FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE,
+                CONSTRAINT fk_task_dependencies_blocker FOREIGN KEY (blocker_id) REFERENCES tasks(id) ON DELETE CASCADE
+            )
+            "#,
+        ],
+        backfill: &[],
+    },
+    Migration {
+        version: 13,
+        description: "add recurrence rules to tasks",
+        statements: &["ALTER TABLE tasks ADD COLUMN recurrence VARCHAR(255) NULL"],
+        backfill: &[],
+    },
+    Migration {
+        version: 14,
+        description: "create reminders, reminder_deliveries and notifications tables",
+        statements: &[
+            r#"
+            CREATE TABLE IF NOT EXISTS reminders (
+                id BINARY(16) PRIMARY KEY,
+                task_id BINARY(16) NOT NULL,
+                user_id BINARY(16) NOT NULL,
+                channel SMALLINT NOT NULL DEFAULT 0,
+                target VARCHAR(2048) NULL,
+                remind_at DATETIME(6) NULL,
+                minutes_before INT NULL,
+                fire_at DATETIME(6) NULL,
+                delivered_at DATETIME(6) NULL,
+                attempts INT NOT NULL DEFAULT 0,
+                last_error TEXT NULL,
+                created_at TIMESTAMP(6) NULL,
+                updated_at TIMESTAMP(6) NULL,
+                INDEX idx_reminders_task_id (task_id),
+                INDEX idx_reminders_fire_at (fire_at),
+                CONSTRAINT fk_reminders_task FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE,
+                CONSTRAINT fk_reminders_user FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+            )
+            "#,
+            r#"
+            CREATE TABLE IF NOT EXISTS reminder_deliveries (
+                reminder_id BINARY(16) NOT NULL,
+                fire_at DATETIME(6) NOT NULL,
+                claimed_at DATETIME(6) NOT NULL,
+                PRIMARY KEY (reminder_id, fire_at),
+                CONSTRAINT fk_reminder_deliveries_reminder FOREIGN KEY (reminder_id) REFERENCES reminders(id) ON DELETE CASCADE
+            )
+            "#,
+            r#"
+            CREATE TABLE IF NOT EXISTS notifications (
+                id BINARY(16) PRIMARY KEY,
+                user_id BINARY(16) NOT NULL,
+                task_id BINARY(16) NULL,
+                message TEXT NOT NULL,
+                created_at TIMESTAMP(6) NULL,
+                read_at DATETIME(6) NULL,
+                INDEX idx_notifications_user_id (user_id),
+                CONSTRAINT fk_notifications_user FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+                CONSTRAINT fk_notifications_task FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE
+            )
+            "#,
+        ],
+        backfill: &[],
+    },
+    // Existing tasks are ranked in creation order; ranks compare byte by byte
+    Migration {
+        version: 15,
+        description: "add manual positions to tasks",
+        statements: &[
+            r#"
+            ALTER TABLE tasks
+                ADD COLUMN position VARCHAR(64) CHARACTER SET ascii COLLATE ascii_bin NOT NULL DEFAULT '',
+                ADD INDEX idx_tasks_user_id_position (user_id, position)
+            "#,
+        ],
+        backfill: &[
+            r#"
+            UPDATE tasks JOIN (
+                SELECT id, CONCAT(LPAD(LOWER(HEX(
+                    ROW_NUMBER() OVER (PARTITION BY user_id ORDER BY created_at, id)
+                )), 8, '0'), 'i') AS position
+                FROM tasks
+            ) AS ranked ON ranked.id = tasks.id
+            SET tasks.position = ranked.position
+            WHERE tasks.position = ''
+            "#,
+        ],
+    },
+    // Tasks left sharing a position by concurrent moves are put one after the
+    // other first
+    Migration {
+        version: 16,
+        description: "make task positions unique per user",
+        statements: &[
+            r#"
+            UPDATE tasks JOIN (
+                SELECT id, ROW_NUMBER() OVER (PARTITION BY user_id, position ORDER BY created_at, id) AS duplicate
+                FROM tasks
+            ) AS ranked ON ranked.id = tasks.id
+            SET tasks.position = CONCAT(tasks.position, LPAD(LOWER(HEX(ranked.duplicate)), 8, '0'), 'i')
+            WHERE ranked.duplicate > 1
+            "#,
+            r#"
+            ALTER TABLE tasks
+                DROP INDEX idx_tasks_user_id_position,
+                ADD UNIQUE INDEX uq_tasks_user_position (user_id, position)
+            "#,
+        ],
+        backfill: &[],
+    },
+];
@@ -0,0 +1,362 @@
+use sqlx::{postgres::PgPoolOptions, PgPool, Postgres};
+use std::time::Duration;
+
+use super::sql::{sql_repository, Dialect};
+use super::Migration;
+use crate::config::DatabaseConfig;
+use crate::search::SearchQuery;
+
+/// Repository backed by a PostgreSQL connection pool
+#[derive(Clone)]
+pub struct PostgresRepository {
+    pool: PgPool,
+}
+
+impl PostgresRepository {
+    /// Wrap an existing pool
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Create a PostgreSQL connection pool from the database settings
+    #[tracing::instrument(skip_all, fields(db.system = "postgresql", db.operation = "CONNECT"))]
+    pub async fn connect(config: &DatabaseConfig) -> Result<Self, sqlx::Error> {
+        let pool = PgPoolOptions::new()
+            .max_connections(config.max_connections)
+            .acquire_timeout(Duration::from_secs(config.acquire_timeout_secs))
+            .connect(&config.url)
+            .await?;
+
+        Ok(Self::new(pool))
+    }
+
+    /// The underlying connection pool
+    pub fn pool(&self) -> &PgPool {
+        &self.pool
+    }
+}
+
+sql_repository!(PostgresRepository, Postgres, DIALECT);
+
+const DIALECT: Dialect = Dialect {
+    system: "postgresql",
+    // LIKE is case-sensitive in PostgreSQL
+    like: "ILIKE",
+    returning: true,
+    insert_ignore: ["INSERT INTO", " ON CONFLICT DO NOTHING"],
+    // The 'simple' configuration lowercases words without stemming, like the other backends
+    fulltext_query: SearchQuery::to_tsquery,
+    // The document expression must match idx_tasks_text_fts for the index to be used
+    fulltext_match: [
+        "to_tsvector('simple', title || ' ' || coalesce(description, '')) @@ to_tsquery('simple', ",
+        ")",
+    ],
+    fulltext_rank: [
+        "ts_rank(to_tsvector('simple', title || ' ' || coalesce(description, '')), to_tsquery('simple', ",
+        "))::float8",
+    ],
+    create_migrations_table: r#"
+        CREATE TABLE IF NOT EXISTS schema_migrations (
+            version BIGINT PRIMARY KEY,
+            description VARCHAR(255) NOT NULL,
+            applied_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP
+        )
+        "#,
+    migrations_table_exists: r#"
+        SELECT COUNT(*) AS count
+        FROM information_schema.tables
+        WHERE table_schema = current_schema() AND table_name = 'schema_migrations'
+        "#,
+    migrations: MIGRATIONS,
+};
+
+/// Schema migrations in the order they must be applied
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "create users, sessions and tasks tables",
+        statements: &[
+            r#"
+            CREATE TABLE IF NOT EXISTS users (
+                id CHAR(36) PRIMARY KEY,
+                email VARCHAR(255) NOT NULL UNIQUE,
+                password_hash VARCHAR(255) NOT NULL,
+                created_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP,
+                updated_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP
+            )
+            "#,
+            r#"
+            CREATE TABLE IF NOT EXISTS sessions (
+                id CHAR(36) PRIMARY KEY,
+                user_id CHAR(36) NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+                token_hash VARCHAR(255) NOT NULL,
+                expires_at TIMESTAMPTZ NOT NULL,
+                created_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP
+            )
+            "#,
+            "CREATE INDEX IF NOT EXISTS idx_sessions_token_hash ON sessions (token_hash)",
+            "CREATE INDEX IF NOT EXISTS idx_sessions_user_id ON sessions (user_id)",
+            "CREATE INDEX IF NOT EXISTS idx_sessions_expires_at ON sessions (expires_at)",
+            r#"
+            CREATE TABLE IF NOT EXISTS tasks (
+                id CHAR(36) PRIMARY KEY,
+                title VARCHAR(255) NOT NULL,
+                completed BOOLEAN NOT NULL DEFAULT FALSE,
+                user_id CHAR(36) NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+                created_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP,
+                updated_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP
+            )
+            "#,
+            "CREATE INDEX IF NOT EXISTS idx_tasks_user_id ON tasks (user_id)",
+            "CREATE INDEX IF NOT EXISTS idx_tasks_created_at ON tasks (created_at)",
+            "CREATE INDEX IF NOT EXISTS idx_tasks_completed ON tasks (completed)",
+        ],
+        backfill: &[],
+    },
+    Migration {
+        version: 2,
+        description: "add owner for tasks created without a user",
+        statements: &[r#"
+            INSERT INTO users (id, email, password_hash)
+            VALUES ('00000000-0000-0000-0000-000000000000', 'anonymous@localhost', '!')
+            ON CONFLICT (id) DO NOTHING
+            "#],
+        backfill: &[],
+    },
+    Migration {
+        version: 3,
+        description: "store keys as UUID",
+        statements: &[
+            "ALTER TABLE sessions DROP CONSTRAINT IF EXISTS sessions_user_id_fkey",
+            "ALTER TABLE tasks DROP CONSTRAINT IF EXISTS tasks_user_id_fkey",
+            "ALTER TABLE users ALTER COLUMN id TYPE UUID USING id::uuid",
+            r#"
+            ALTER TABLE sessions
+                ALTER COLUMN id TYPE UUID USING id::uuid,
+                ALTER COLUMN user_id TYPE UUID USING user_id::uuid
+            "#,
+            r#"
+            ALTER TABLE tasks
+                ALTER COLUMN id TYPE UUID USING id::uuid,
+                ALTER COLUMN user_id TYPE UUID USING user_id::uuid
+            "#,
+            r#"
+            ALTER TABLE sessions ADD CONSTRAINT sessions_user_id_fkey
+                FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+            "#,
+            r#"
+            ALTER TABLE tasks ADD CONSTRAINT tasks_user_id_fkey
+                FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+            "#,
+        ],
+        backfill: &[],
+    },
+    Migration {
+        version: 4,
+        description: "add full-text index on task titles",
+        statements: &[r#"
+            CREATE INDEX IF NOT EXISTS idx_tasks_title_fts
+            ON tasks USING GIN (to_tsvector('simple', title))
+            "#],
+        backfill: &[],
+    },
+    Migration {
+        version: 5,
+        description: "add task descriptions to tasks and the full-text index",
+        statements: &[
+            "ALTER TABLE tasks ADD COLUMN IF NOT EXISTS description TEXT",
+            "DROP INDEX IF EXISTS idx_tasks_title_fts",
+            r#"
+            CREATE INDEX IF NOT EXISTS idx_tasks_text_fts
+            ON tasks USING GIN (to_tsvector('simple', title || ' ' || coalesce(description, '')))
+            "#,
+        ],
+        backfill: &[],
+    },
+    Migration {
+        version: 6,
+        description: "add due and start dates to tasks and time zones to users",
+        statements: &[
+            "ALTER TABLE tasks ADD COLUMN IF NOT EXISTS due_at TIMESTAMPTZ",
+            "ALTER TABLE tasks ADD COLUMN IF NOT EXISTS due_all_day BOOLEAN NOT NULL DEFAULT FALSE",
+            "ALTER TABLE tasks ADD COLUMN IF NOT EXISTS start_at TIMESTAMPTZ",
+            "CREATE INDEX IF NOT EXISTS idx_tasks_due_at ON tasks (due_at)",
+            "ALTER TABLE users ADD COLUMN IF NOT EXISTS time_zone VARCHAR(64) NOT NULL DEFAULT 'UTC'",
+        ],
+        backfill: &[],
+    },
+    Migration {
+        version: 7,
+        description: "add priorities to tasks",
+        statements: &[
+            "ALTER TABLE tasks ADD COLUMN IF NOT EXISTS priority SMALLINT NOT NULL DEFAULT 0",
+            "CREATE INDEX IF NOT EXISTS idx_tasks_priority ON tasks (priority)",
+        ],
+        backfill: &[],
+    },
+    Migration {
+        version: 8,
+        description: "create tags and task_tags tables",
+        statements: &[
+            r#"
+            CREATE TABLE IF NOT EXISTS tags (
+                id UUID PRIMARY KEY,
+                user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+                name VARCHAR(50) NOT NULL,
+                color CHAR(7),
+                created_at TIMESTAMPTZ,
+                updated_at TIMESTAMPTZ,
+                UNIQUE (user_id, name)
+            )
+            "#,
+            r#"
+            CREATE TABLE IF NOT EXISTS task_tags (
+                task_id UUID NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+                tag_id UUID NOT NULL REFERENCES tags(id) ON DELETE CASCADE,
+                PRIMARY KEY (task_id, tag_id)
+            )
+            "#,
+            "CREATE INDEX IF NOT EXISTS idx_task_tags_tag_id ON task_tags (tag_id)",
+        ],
+        backfill: &[],
+    },
+    Migration {
+        version: 9,
+        description: "create projects table and add projects to tasks",
+        statements: &[
+            r#"
+            CREATE TABLE IF NOT EXISTS projects (
+                id UUID PRIMARY KEY,
+                user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+                name VARCHAR(100) NOT NULL,
+                color CHAR(7),
+                archived BOOLEAN NOT NULL DEFAULT FALSE,
+                position INTEGER NOT NULL DEFAULT 0,
+                created_at TIMESTAMPTZ,
+                updated_at TIMESTAMPTZ,
+                UNIQUE (user_id, name)
+            )
+            "#,
+            "CREATE INDEX IF NOT EXISTS idx_projects_user_position ON projects (user_id, position)",
+            "ALTER TABLE tasks ADD COLUMN project_id UUID REFERENCES projects(id) ON DELETE SET NULL",
+            "CREATE INDEX IF NOT EXISTS idx_tasks_project_id ON tasks (project_id)",
+        ],
+        backfill: &[],
+    },
+    Migration {
+        version: 10,
+        description: "add subtasks to tasks",
+        statements: &[
+            "ALTER TABLE tasks ADD COLUMN parent_id UUID REFERENCES tasks(id) ON DELETE CASCADE",
+            "ALTER TABLE tasks ADD COLUMN auto_complete BOOLEAN NOT NULL DEFAULT FALSE",
+            "CREATE INDEX IF NOT EXISTS idx_tasks_parent_id ON tasks (parent_id)",
+        ],
+        backfill: &[],
+    },
+    Migration {
+        version: 11,
+        description: "create task_dependencies table",
+        statements: &[
+            r#"
+            CREATE TABLE IF NOT EXISTS task_dependencies (
+                task_id UUID NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+                blocker_id UUID NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+                PRIMARY KEY (task_id, blocker_id)
+            )
+            "#,
+            "CREATE INDEX IF NOT EXISTS idx_task_dependencies_blocker_id ON task_dependencies (blocker_id)",
+        ],
+        backfill: &[],
+    },
+    Migration {
+        version: 12,
+        description: "add recurrence rules to tasks",
+        statements: &["ALTER TABLE tasks ADD COLUMN recurrence VARCHAR(255)"],
+        backfill: &[],
+    },
+    Migration {
+        version: 13,
+        description: "create reminders, reminder_deliveries and notifications tables",
+        statements: &[
+            r#"
+            CREATE TABLE IF NOT EXISTS reminders (
+                id UUID PRIMARY KEY,
+                task_id UUID NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+                user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+                channel SMALLINT NOT NULL DEFAULT 0,
+                target VARCHAR(2048),
+                remind_at TIMESTAMPTZ,
+                minutes_before INTEGER,
+                fire_at TIMESTAMPTZ,
+                delivered_at TIMESTAMPTZ,
+                attempts INTEGER NOT NULL DEFAULT 0,
+                last_error TEXT,
+                created_at TIMESTAMPTZ,
+                updated_at TIMESTAMPTZ
+            )
+            "#,
+            "CREATE INDEX IF NOT EXISTS idx_reminders_task_id ON reminders (task_id)",
+            "CREATE INDEX IF NOT EXISTS idx_reminders_fire_at ON reminders (fire_at)",
+            r#"
+            CREATE TABLE IF NOT EXISTS reminder_deliveries (
+                reminder_id UUID NOT NULL REFERENCES reminders(id) ON DELETE CASCADE,
+                fire_at TIMESTAMPTZ NOT NULL,
+                claimed_at TIMESTAMPTZ NOT NULL,
+                PRIMARY KEY (reminder_id, fire_at)
+            )
+            "#,
+            r#"
+            CREATE TABLE IF NOT EXISTS notifications (
+                id UUID PRIMARY KEY,
+                user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+                task_id UUID REFERENCES tasks(id) ON DELETE CASCADE,
+                message TEXT NOT NULL,
+                created_at TIMESTAMPTZ,
+                read_at TIMESTAMPTZ
+            )
+            "#,
+            "CREATE INDEX IF NOT EXISTS idx_notifications_user_id ON notifications (user_id)",
+        ],
+        backfill: &[],
+    },
+    // Existing tasks are ranked in creation order; ranks compare byte by byte
+    Migration {
+        version: 14,
+        description: "add manual positions to tasks",
+        statements: &[
+            r#"ALTER TABLE tasks ADD COLUMN IF NOT EXISTS position VARCHAR(64) COLLATE "C" NOT NULL DEFAULT ''"#,
+            "CREATE INDEX IF NOT EXISTS idx_tasks_user_id_position ON tasks (user_id, position)",
+        ],
+        backfill: &[
+            r#"
+            UPDATE tasks SET position = ranked.position
+            FROM (
+                SELECT id, lpad(to_hex(
+                    row_number() OVER (PARTITION BY user_id ORDER BY created_at, id)
+                ), 8, '0') || 'i' AS position
+                FROM tasks
+            ) AS ranked
+            WHERE tasks.id = ranked.id AND tasks.position = ''
+            "#,
+        ],
+    },
+    // Tasks left sharing a position by concurrent moves are put one after the
+    // other first
+    Migration {
+        version: 15,
+        description: "make task positions unique per user",
+        statements: &[
+            r#"
+            UPDATE tasks SET position = tasks.position || lpad(to_hex(ranked.duplicate), 8, '0') || 'i'
+            FROM (
+                SELECT id, row_number() OVER (PARTITION BY user_id, position ORDER BY created_at, id) AS duplicate
+                FROM tasks
+            ) AS ranked
+            WHERE tasks.id = ranked.id AND ranked.duplicate > 1
+            "#,
+            "DROP INDEX IF EXISTS idx_tasks_user_id_position",
+            "CREATE UNIQUE INDEX IF NOT EXISTS uq_tasks_user_position ON tasks (user_id, position)",
+        ],
+        backfill: &[],
+    },
+];
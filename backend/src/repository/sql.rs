@@ -0,0 +1,1993 @@
+//! SQL implementation shared by the MySQL, PostgreSQL and SQLite repositories.
+//!
+//! sqlx's argument types differ too much between drivers for this code to be
+//! generic over `sqlx::Database`, so [`sql_repository!`] stamps the same
+//! implementation out for each concrete driver. Every query with bound values is
+//! built with `QueryBuilder`, which writes the driver's placeholder style. UUIDs
+//! are bound natively (BINARY(16), UUID or BLOB) and timestamps are bound from
+//! the application rather than using database defaults.
+//! What still differs between databases lives in a [`Dialect`].
+
+use chrono::{DateTime, SubsecRound, Utc};
+use sqlx::{ColumnIndex, Decode, FromRow, Row, Type};
+use uuid::Uuid;
+
+use super::Migration;
+use crate::models::SortOrder;
+use crate::search::SearchQuery;
+
+/// SQL that differs between the supported databases
+pub(super) struct Dialect {
+    /// Value of the `db.system` span attribute
+    pub system: &'static str,
+    /// Case-insensitive pattern match operator
+    pub like: &'static str,
+    /// Translate a full-text query into the backend's syntax
+    pub fulltext_query: fn(&SearchQuery) -> String,
+    /// SQL before and after the bound query in a full-text match condition
+    pub fulltext_match: [&'static str; 2],
+    /// SQL before and after the bound query in a full-text relevance score
+    pub fulltext_rank: [&'static str; 2],
+    /// Whether `UPDATE ... RETURNING` is supported. Without it an update is
+    /// followed by a `SELECT` of the row in the same transaction.
+    pub returning: bool,
+    /// SQL before the table name and after the values of an `INSERT` that
+    /// skips rows whose key is already taken
+    pub insert_ignore: [&'static str; 2],
+    /// Creates the migration tracking table
+    pub create_migrations_table: &'static str,
+    /// Returns a `count` of 1 when the migration tracking table exists
+    pub migrations_table_exists: &'static str,
+    /// Schema migrations in the order they must be applied
+    pub migrations: &'static [Migration],
+}
+
+/// Columns selected whenever a whole task is read
+pub(super) const TASK_COLUMNS: &str =
+    "id, title, description, completed, user_id, created_at, updated_at, due_at, due_all_day, start_at, priority, project_id, parent_id, auto_complete, recurrence, position";
+
+/// Columns selected whenever a whole tag is read, from `tags`
+pub(super) const TAG_COLUMNS: &str = "id, user_id, name, color, created_at, updated_at, \
+    (SELECT COUNT(*) FROM task_tags WHERE task_tags.tag_id = tags.id) AS task_count";
+
+/// Columns selected whenever a whole project is read
+pub(super) const PROJECT_COLUMNS: &str =
+    "id, user_id, name, color, archived, position, created_at, updated_at";
+
+/// Columns selected whenever a whole reminder is read, qualified for joins with `tasks`
+pub(super) const REMINDER_COLUMNS: &str = "reminders.id, reminders.task_id, reminders.user_id, \
+    reminders.channel, reminders.target, reminders.remind_at, reminders.minutes_before, reminders.fire_at, \
+    reminders.delivered_at, reminders.attempts, reminders.last_error, reminders.created_at, reminders.updated_at";
+
+/// Columns selected whenever a whole notification is read
+pub(super) const NOTIFICATION_COLUMNS: &str = "id, user_id, task_id, message, created_at, read_at";
+
+/// Columns selected whenever a whole user is read
+pub(super) const USER_COLUMNS: &str =
+    "id, email, password_hash, time_zone, created_at, updated_at";
+
+/// Columns selected whenever a whole session is read
+pub(super) const SESSION_COLUMNS: &str = "id, user_id, token_hash, expires_at, created_at";
+
+/// Decode a whole row of `table` into a model.
+///
+/// A row that does not decode (e.g. a malformed key or an out-of-range value) is
+/// logged with its id, when that much can be read, and returned as the decode error.
+pub(super) fn decode_row<'r, T, R>(row: &'r R, table: &str) -> Result<T, sqlx::Error>
+where
+    R: Row,
+    T: FromRow<'r, R>,
+    Uuid: Decode<'r, R::Database> + Type<R::Database>,
+    &'static str: ColumnIndex<R>,
+{
+    T::from_row(row).map_err(|error| {
+        let id = row
+            .try_get::<Uuid, _>("id")
+            .map_or_else(|_| "<undecodable>".to_string(), |id| id.to_string());
+        tracing::error!(table, id = %id, "Failed to decode row: {}", error);
+        error
+    })
+}
+
+/// The current time at the microsecond precision every backend stores, so a
+/// model built from bound values matches what a later read returns
+pub(super) fn now() -> DateTime<Utc> {
+    Utc::now().trunc_subsecs(6)
+}
+
+/// How many ranks an append tries before giving up on the ones concurrent
+/// appends keep taking
+pub(super) const MAX_APPEND_ATTEMPTS: usize = 16;
+
+/// Whether `error` means a row with the same unique key already exists
+pub(super) fn is_unique_violation(error: &sqlx::Error) -> bool {
+    error
+        .as_database_error()
+        .is_some_and(|error| error.is_unique_violation())
+}
+
+/// SQL keyword for a sort direction
+pub(super) fn order_keyword(order: SortOrder) -> &'static str {
+    match order {
+        SortOrder::Asc => "ASC",
+        SortOrder::Desc => "DESC",
+    }
+}
+
+/// Implement every repository trait for `$repository`, a struct with a
+/// `pool: sqlx::Pool<$db>` field, using the SQL differences in `$dialect`
+macro_rules! sql_repository {
+    ($repository:ident, $db:ty, $dialect:path) => {
+        impl $repository {
+            /// Append `AND ...` conditions for `filter` to a query ending in a WHERE clause
+            fn push_task_filter(
+                query: &mut sqlx::QueryBuilder<'_, $db>,
+                filter: &$crate::repository::TaskFilter,
+            ) {
+                if let Some(fulltext) = filter.fulltext() {
+                    query.push(format_args!(" AND {}", $dialect.fulltext_match[0]));
+                    query.push_bind(($dialect.fulltext_query)(&fulltext));
+                    query.push($dialect.fulltext_match[1]);
+                } else if let Some(search) = &filter.search {
+                    let pattern = format!("%{}%", search);
+                    query.push(format_args!(" AND (title {} ", $dialect.like));
+                    query.push_bind(pattern.clone());
+                    query.push(format_args!(" OR description {} ", $dialect.like));
+                    query.push_bind(pattern);
+                    query.push(")");
+                }
+
+                if let Some(completed) = filter.completed {
+                    query.push(" AND completed = ");
+                    query.push_bind(completed);
+                }
+
+                if let Some(project_id) = filter.project_id {
+                    query.push(" AND project_id = ");
+                    query.push_bind(project_id);
+                }
+
+                if let Some(parent_id) = filter.parent_id {
+                    query.push(" AND parent_id = ");
+                    query.push_bind(parent_id);
+                }
+
+                for condition in &filter.query.conditions {
+                    query.push(" AND ");
+                    Self::push_condition(query, condition);
+                }
+            }
+
+            /// Append one filter condition, binding its values
+            fn push_condition(
+                query: &mut sqlx::QueryBuilder<'_, $db>,
+                condition: &$crate::query::Condition,
+            ) {
+                match condition {
+                    $crate::query::Condition::Compare(comparison) => {
+                        Self::push_comparison(query, comparison)
+                    }
+                    $crate::query::Condition::All(conditions) => {
+                        query.push("(1=1");
+                        for condition in conditions {
+                            query.push(" AND ");
+                            Self::push_condition(query, condition);
+                        }
+                        query.push(")");
+                    }
+                    $crate::query::Condition::Any(conditions) => {
+                        query.push("(1=0");
+                        for condition in conditions {
+                            query.push(" OR ");
+                            Self::push_condition(query, condition);
+                        }
+                        query.push(")");
+                    }
+                    $crate::query::Condition::Tagged(name) => {
+                        query.push(
+                            "EXISTS (SELECT 1 FROM task_tags JOIN tags ON tags.id = task_tags.tag_id \
+                             WHERE task_tags.task_id = tasks.id AND tags.name = ",
+                        );
+                        query.push_bind(name.clone());
+                        query.push(")");
+                    }
+                    $crate::query::Condition::Blocked => {
+                        query.push(
+                            "EXISTS (SELECT 1 FROM task_dependencies \
+                             JOIN tasks AS blockers ON blockers.id = task_dependencies.blocker_id \
+                             WHERE task_dependencies.task_id = tasks.id AND NOT blockers.completed)",
+                        );
+                    }
+                    $crate::query::Condition::Not(condition) => {
+                        query.push("NOT ");
+                        Self::push_condition(query, condition);
+                    }
+                }
+            }
+
+            /// Append `(column operator value)` for one filter comparison, binding the value
+            fn push_comparison(
+                query: &mut sqlx::QueryBuilder<'_, $db>,
+                comparison: &$crate::query::Comparison,
+            ) {
+                // Columns and operators come from fixed sets, so they are safe to splice in
+                let column = comparison.column.name();
+                let operator = match comparison.operator {
+                    $crate::query::Operator::Contains => $dialect.like,
+                    operator => operator.sql(),
+                };
+                if comparison.operand == $crate::query::Operand::Null {
+                    query.push(format_args!("{} IS NULL", column));
+                    return;
+                }
+                // Spell out that a missing value fails the comparison, so that
+                // NOT of it holds rather than being NULL as well
+                query.push(format_args!("({} IS NOT NULL AND {} {} ", column, column, operator));
+                match &comparison.operand {
+                    $crate::query::Operand::Bool(value) => query.push_bind(*value),
+                    $crate::query::Operand::Time(value) => query.push_bind(*value),
+                    $crate::query::Operand::Priority(value) => query.push_bind(*value),
+                    $crate::query::Operand::Text(value) => match comparison.operator {
+                        $crate::query::Operator::Contains => query.push_bind(format!("%{}%", value)),
+                        _ => query.push_bind(value.clone()),
+                    },
+                    $crate::query::Operand::Null => unreachable!("handled above"),
+                };
+                query.push(")");
+            }
+
+            /// Start a task listing, selecting the relevance score for full-text searches
+            fn select_tasks(filter: &$crate::repository::TaskFilter) -> sqlx::QueryBuilder<'_, $db> {
+                let mut query = sqlx::QueryBuilder::<$db>::new(format!(
+                    "SELECT {}",
+                    $crate::repository::sql::TASK_COLUMNS
+                ));
+                if let Some(fulltext) = filter.fulltext() {
+                    query.push(format_args!(", {}", $dialect.fulltext_rank[0]));
+                    query.push_bind(($dialect.fulltext_query)(&fulltext.ranking()));
+                    query.push(format_args!("{} AS relevance", $dialect.fulltext_rank[1]));
+                }
+                query.push(" FROM tasks WHERE 1=1");
+                Self::push_task_filter(&mut query, filter);
+                query
+            }
+
+            /// Append `ORDER BY` for `sort`, breaking ties by id so the order is total
+            fn push_task_order(
+                query: &mut sqlx::QueryBuilder<'_, $db>,
+                sort: &$crate::repository::TaskSort,
+            ) {
+                // Sort columns come from a fixed set, so they are safe to splice in
+                query.push(" ORDER BY ");
+                for column in &sort.columns {
+                    if column.field.is_nullable() {
+                        // Missing values last in either direction, unless reversed
+                        query.push(format_args!(
+                            "{} IS NULL {}, ",
+                            column.field.column(),
+                            if column.nulls_first { "DESC" } else { "ASC" }
+                        ));
+                    }
+                    query.push(format_args!(
+                        "{} {}, ",
+                        column.field.column(),
+                        $crate::repository::sql::order_keyword(column.order)
+                    ));
+                }
+                query.push(format_args!(
+                    "id {}",
+                    $crate::repository::sql::order_keyword(sort.id_order())
+                ));
+            }
+
+            /// Append `AND ...` keeping the rows that come strictly after `after`
+            /// in `sort` order
+            fn push_keyset_filter(
+                query: &mut sqlx::QueryBuilder<'_, $db>,
+                sort: &$crate::repository::TaskSort,
+                after: &$crate::repository::Keyset,
+            ) {
+                let after_operator = |order| match order {
+                    $crate::models::SortOrder::Asc => ">",
+                    $crate::models::SortOrder::Desc => "<",
+                };
+                let id_order = sort.id_order();
+
+                let single_direction = sort.columns.iter().all(|column| column.order == id_order);
+                let nullable = sort.columns.iter().any(|column| column.field.is_nullable());
+                if single_direction && !nullable {
+                    // A row value comparison matches a single-direction ordering
+                    // exactly and lets the database seek an index on those columns
+                    query.push(" AND (");
+                    for column in &sort.columns {
+                        query.push(format_args!("{}, ", column.field.column()));
+                    }
+                    query.push(format_args!("id) {} (", after_operator(id_order)));
+                    for key in &after.keys {
+                        Self::push_sort_key(query, key);
+                        query.push(", ");
+                    }
+                    query.push_bind(after.id);
+                    query.push(")");
+                    return;
+                }
+
+                // Mixed directions or missing values: ties on every earlier
+                // column, then after on this one
+                query.push(" AND (");
+                for (index, (column, key)) in sort.columns.iter().zip(&after.keys).enumerate() {
+                    query.push("(");
+                    for (earlier, key) in sort.columns[..index].iter().zip(&after.keys) {
+                        Self::push_sort_key_equals(query, earlier.field.column(), key);
+                        query.push(" AND ");
+                    }
+                    let name = column.field.column();
+                    if *key == $crate::repository::SortKey::Null {
+                        // Only ties come after a missing value, or every present
+                        // value when missing values come first
+                        if column.nulls_first {
+                            query.push(format_args!("{} IS NOT NULL", name));
+                        } else {
+                            query.push("1=0");
+                        }
+                    } else if column.field.is_nullable() && !column.nulls_first {
+                        query.push(format_args!("({} {} ", name, after_operator(column.order)));
+                        Self::push_sort_key(query, key);
+                        query.push(format_args!(" OR {} IS NULL)", name));
+                    } else {
+                        query.push(format_args!("{} {} ", name, after_operator(column.order)));
+                        Self::push_sort_key(query, key);
+                    }
+                    query.push(") OR ");
+                }
+                query.push("(");
+                for (column, key) in sort.columns.iter().zip(&after.keys) {
+                    Self::push_sort_key_equals(query, column.field.column(), key);
+                    query.push(" AND ");
+                }
+                query.push(format_args!("id {} ", after_operator(id_order)));
+                query.push_bind(after.id);
+                query.push("))");
+            }
+
+            /// Append `column = key`, where a missing key matches missing values
+            fn push_sort_key_equals(
+                query: &mut sqlx::QueryBuilder<'_, $db>,
+                column: &str,
+                key: &$crate::repository::SortKey,
+            ) {
+                if *key == $crate::repository::SortKey::Null {
+                    query.push(format_args!("{} IS NULL", column));
+                } else {
+                    query.push(format_args!("{} = ", column));
+                    Self::push_sort_key(query, key);
+                }
+            }
+
+            fn push_sort_key(
+                query: &mut sqlx::QueryBuilder<'_, $db>,
+                key: &$crate::repository::SortKey,
+            ) {
+                match key {
+                    $crate::repository::SortKey::Timestamp(value) => query.push_bind(*value),
+                    $crate::repository::SortKey::Text(value) => query.push_bind(value.clone()),
+                    $crate::repository::SortKey::Bool(value) => query.push_bind(*value),
+                    $crate::repository::SortKey::Score(value) => query.push_bind(*value),
+                    $crate::repository::SortKey::Integer(value) => query.push_bind(*value),
+                    $crate::repository::SortKey::Null => query.push("NULL"),
+                };
+            }
+
+            /// Fetch a task, failing with `RowNotFound` when it does not exist
+            async fn fetch_task<'e, E>(executor: E, id: uuid::Uuid) -> Result<$crate::models::Task, sqlx::Error>
+            where
+                E: sqlx::Executor<'e, Database = $db>,
+            {
+                let mut query = sqlx::QueryBuilder::<$db>::new(format!(
+                    "SELECT {} FROM tasks WHERE id = ",
+                    $crate::repository::sql::TASK_COLUMNS
+                ));
+                query.push_bind(id);
+
+                let row = query.build().fetch_one(executor).await?;
+                $crate::repository::sql::decode_row(&row, "tasks")
+            }
+
+            /// The position of the user's last task in their manual order
+            async fn last_position<'e, E>(
+                executor: E,
+                user_id: uuid::Uuid,
+            ) -> Result<Option<String>, sqlx::Error>
+            where
+                E: sqlx::Executor<'e, Database = $db>,
+            {
+                use sqlx::Row;
+
+                let mut query =
+                    sqlx::QueryBuilder::<$db>::new("SELECT MAX(position) AS position FROM tasks WHERE user_id = ");
+                query.push_bind(user_id);
+                let row = query.build().fetch_one(executor).await?;
+                row.try_get("position")
+            }
+
+            /// Insert a pending task, ranked after `last`, the owner's last task,
+            /// unless it has a position
+            async fn insert_task<'e, E>(
+                executor: E,
+                task: &$crate::repository::NewTask<'_>,
+                last: Option<&str>,
+            ) -> Result<$crate::models::Task, sqlx::Error>
+            where
+                E: sqlx::Executor<'e, Database = $db>,
+            {
+                let task = task.into_task($crate::repository::sql::now(), last);
+
+                let mut query = sqlx::QueryBuilder::<$db>::new(
+                    "INSERT INTO tasks (id, title, description, completed, user_id, created_at, updated_at, due_at, due_all_day, start_at, priority, project_id, parent_id, auto_complete, recurrence, position) VALUES (",
+                );
+                query.push_bind(task.id);
+                query.push(", ");
+                query.push_bind(task.title.clone());
+                query.push(", ");
+                query.push_bind(task.description.clone());
+                query.push(", ");
+                query.push_bind(task.completed);
+                query.push(", ");
+                query.push_bind(task.user_id);
+                query.push(", ");
+                query.push_bind(task.created_at);
+                query.push(", ");
+                query.push_bind(task.updated_at);
+                query.push(", ");
+                query.push_bind(task.due_at);
+                query.push(", ");
+                query.push_bind(task.due_all_day);
+                query.push(", ");
+                query.push_bind(task.start_at);
+                query.push(", ");
+                query.push_bind(task.priority);
+                query.push(", ");
+                query.push_bind(task.project_id);
+                query.push(", ");
+                query.push_bind(task.parent_id);
+                query.push(", ");
+                query.push_bind(task.auto_complete);
+                query.push(", ");
+                query.push_bind(task.recurrence.clone());
+                query.push(", ");
+                query.push_bind(task.position.clone());
+                query.push(")");
+                query.build().execute(executor).await?;
+
+                // Every column was bound above, so there is nothing to read back
+                Ok(task)
+            }
+
+            /// Start an `UPDATE` of the task's changed fields and `updated_at`,
+            /// ending in its `WHERE` clause
+            fn update_task_query<'q>(
+                id: uuid::Uuid,
+                changes: &$crate::repository::TaskChanges<'_>,
+            ) -> sqlx::QueryBuilder<'q, $db> {
+                let now = $crate::repository::sql::now();
+
+                // Always update the updated_at timestamp to ensure it changes
+                let mut query = sqlx::QueryBuilder::<$db>::new("UPDATE tasks SET updated_at = ");
+                query.push_bind(now);
+
+                if let Some(title) = changes.title {
+                    query.push(", title = ");
+                    query.push_bind(title.to_string());
+                }
+
+                if let Some(description) = changes.description {
+                    query.push(", description = ");
+                    query.push_bind(description.map(str::to_string));
+                }
+
+                if let Some(completed) = changes.completed {
+                    query.push(", completed = ");
+                    query.push_bind(completed);
+                }
+
+                if let Some(due) = changes.due {
+                    query.push(", due_at = ");
+                    query.push_bind(due.map(|due| due.at));
+                    query.push(", due_all_day = ");
+                    query.push_bind(due.is_some_and(|due| due.all_day));
+                }
+
+                if let Some(start_at) = changes.start_at {
+                    query.push(", start_at = ");
+                    query.push_bind(start_at);
+                }
+
+                if let Some(priority) = changes.priority {
+                    query.push(", priority = ");
+                    query.push_bind(priority);
+                }
+
+                if let Some(project_id) = changes.project_id {
+                    query.push(", project_id = ");
+                    query.push_bind(project_id);
+                }
+
+                if let Some(parent_id) = changes.parent_id {
+                    query.push(", parent_id = ");
+                    query.push_bind(parent_id);
+                }
+
+                if let Some(auto_complete) = changes.auto_complete {
+                    query.push(", auto_complete = ");
+                    query.push_bind(auto_complete);
+                }
+
+                if let Some(recurrence) = changes.recurrence {
+                    query.push(", recurrence = ");
+                    query.push_bind(recurrence.map(str::to_string));
+                }
+
+                if let Some(position) = changes.position {
+                    query.push(", position = ");
+                    query.push_bind(position.to_string());
+                }
+
+                query.push(" WHERE id = ");
+                query.push_bind(id);
+                query
+            }
+
+            /// Hold a write lock on the row of the user owning `task_id` until
+            /// the transaction ends, serializing changes that read the user's
+            /// tasks before writing them. On SQLite this takes the database's
+            /// write lock, so later reads see every committed row.
+            async fn lock_task_owner(
+                transaction: &mut sqlx::Transaction<'_, $db>,
+                task_id: uuid::Uuid,
+            ) -> Result<(), sqlx::Error> {
+                let mut query = sqlx::QueryBuilder::<$db>::new(
+                    "UPDATE users SET updated_at = updated_at WHERE id = (SELECT user_id FROM tasks WHERE id = ",
+                );
+                query.push_bind(task_id);
+                query.push(")");
+                query.build().execute(&mut **transaction).await?;
+                Ok(())
+            }
+
+            /// Ids of the tasks directly blocking any of `task_ids`
+            async fn fetch_blocker_ids<'e, E>(
+                executor: E,
+                task_ids: &[uuid::Uuid],
+            ) -> Result<Vec<uuid::Uuid>, sqlx::Error>
+            where
+                E: sqlx::Executor<'e, Database = $db>,
+            {
+                use sqlx::Row;
+
+                if task_ids.is_empty() {
+                    return Ok(Vec::new());
+                }
+
+                let mut query = sqlx::QueryBuilder::<$db>::new(
+                    "SELECT DISTINCT blocker_id FROM task_dependencies WHERE task_id IN (",
+                );
+                let mut ids = query.separated(", ");
+                for task_id in task_ids {
+                    ids.push_bind(*task_id);
+                }
+                query.push(")");
+
+                query
+                    .build()
+                    .fetch_all(executor)
+                    .await?
+                    .iter()
+                    .map(|row| row.try_get("blocker_id"))
+                    .collect()
+            }
+        }
+
+        #[async_trait::async_trait]
+        impl $crate::repository::TaskRepository for $repository {
+            #[tracing::instrument(skip_all, fields(db.system = $dialect.system, db.operation = "INSERT"))]
+            async fn create_task(
+                &self,
+                task: &$crate::repository::NewTask<'_>,
+            ) -> Result<$crate::models::Task, sqlx::Error> {
+                if task.position.is_some() {
+                    return Self::insert_task(&self.pool, task, None).await;
+                }
+
+                // Appends take no lock: positions are unique per user, so an
+                // append that raced another to the same rank fails, and is
+                // tried again after it
+                let mut attempts = 1;
+                loop {
+                    let last = Self::last_position(&self.pool, task.user_id).await?;
+                    match Self::insert_task(&self.pool, task, last.as_deref()).await {
+                        Err(error)
+                            if $crate::repository::sql::is_unique_violation(&error)
+                                && attempts < $crate::repository::sql::MAX_APPEND_ATTEMPTS =>
+                        {
+                            attempts += 1;
+                        }
+                        result => return result,
+                    }
+                }
+            }
+
+            #[tracing::instrument(skip_all, fields(db.system = $dialect.system, db.operation = "SELECT"))]
+            async fn get_task(&self, id: uuid::Uuid) -> Result<$crate::models::Task, sqlx::Error> {
+                Self::fetch_task(&self.pool, id).await
+            }
+
+            #[tracing::instrument(skip_all, fields(db.system = $dialect.system, db.operation = "SELECT"))]
+            async fn list_all_tasks(&self) -> Result<Vec<$crate::models::Task>, sqlx::Error> {
+                let query = format!(
+                    "SELECT {} FROM tasks ORDER BY created_at DESC",
+                    $crate::repository::sql::TASK_COLUMNS
+                );
+
+                sqlx::query(&query)
+                    .fetch_all(&self.pool)
+                    .await?
+                    .iter()
+                    .map(|row| $crate::repository::sql::decode_row(row, "tasks"))
+                    .collect()
+            }
+
+            #[tracing::instrument(skip_all, fields(db.system = $dialect.system, db.operation = "SELECT"))]
+            async fn count_tasks(
+                &self,
+                filter: &$crate::repository::TaskFilter,
+            ) -> Result<u64, sqlx::Error> {
+                use sqlx::Row;
+
+                let mut query = sqlx::QueryBuilder::<$db>::new(
+                    "SELECT COUNT(*) AS count FROM tasks WHERE 1=1",
+                );
+                Self::push_task_filter(&mut query, filter);
+
+                let row = query.build().fetch_one(&self.pool).await?;
+                let count: i64 = row.try_get("count")?;
+                Ok(count as u64)
+            }
+
+            #[tracing::instrument(skip_all, fields(db.system = $dialect.system, db.operation = "SELECT"))]
+            async fn count_tasks_up_to(
+                &self,
+                filter: &$crate::repository::TaskFilter,
+                limit: u64,
+            ) -> Result<u64, sqlx::Error> {
+                use sqlx::Row;
+
+                // Counting a limited subquery lets the scan stop early
+                let mut query = sqlx::QueryBuilder::<$db>::new(
+                    "SELECT COUNT(*) AS count FROM (SELECT 1 AS matched FROM tasks WHERE 1=1",
+                );
+                Self::push_task_filter(&mut query, filter);
+                query.push(" LIMIT ");
+                query.push_bind(limit as i64);
+                query.push(") AS capped");
+
+                let row = query.build().fetch_one(&self.pool).await?;
+                let count: i64 = row.try_get("count")?;
+                Ok(count as u64)
+            }
+
+            #[tracing::instrument(skip_all, fields(db.system = $dialect.system, db.operation = "SELECT"))]
+            async fn list_tasks(
+                &self,
+                filter: &$crate::repository::TaskFilter,
+                sort: &$crate::repository::TaskSort,
+                offset: u64,
+                limit: u64,
+            ) -> Result<Vec<$crate::models::Task>, sqlx::Error> {
+                let sort = sort.for_filter(filter);
+                let mut query = Self::select_tasks(filter);
+                Self::push_task_order(&mut query, &sort);
+                query.push(" LIMIT ");
+                query.push_bind(limit as i64);
+                query.push(" OFFSET ");
+                query.push_bind(offset as i64);
+
+                query
+                    .build()
+                    .fetch_all(&self.pool)
+                    .await?
+                    .iter()
+                    .map(|row| $crate::repository::sql::decode_row(row, "tasks"))
+                    .collect()
+            }
+
+            #[tracing::instrument(skip_all, fields(db.system = $dialect.system, db.operation = "SELECT"))]
+            async fn list_tasks_after(
+                &self,
+                filter: &$crate::repository::TaskFilter,
+                sort: &$crate::repository::TaskSort,
+                after: Option<&$crate::repository::Keyset>,
+                limit: u64,
+            ) -> Result<Vec<$crate::models::Task>, sqlx::Error> {
+                let sort = sort.for_filter(filter);
+                let mut query = Self::select_tasks(filter);
+                if let Some(after) = after {
+                    Self::push_keyset_filter(&mut query, &sort, after);
+                }
+                Self::push_task_order(&mut query, &sort);
+                query.push(" LIMIT ");
+                query.push_bind(limit as i64);
+
+                query
+                    .build()
+                    .fetch_all(&self.pool)
+                    .await?
+                    .iter()
+                    .map(|row| $crate::repository::sql::decode_row(row, "tasks"))
+                    .collect()
+            }
+
+            #[tracing::instrument(skip_all, fields(db.system = $dialect.system, db.operation = "UPDATE"))]
+            async fn update_task(
+                &self,
+                id: uuid::Uuid,
+                changes: &$crate::repository::TaskChanges<'_>,
+            ) -> Result<$crate::models::Task, sqlx::Error> {
+                let mut query = Self::update_task_query(id, changes);
+
+                if $dialect.returning {
+                    // One round trip; no row means the task does not exist (or was just deleted)
+                    query.push(format_args!(
+                        " RETURNING {}",
+                        $crate::repository::sql::TASK_COLUMNS
+                    ));
+                    let row = query.build().fetch_optional(&self.pool).await?;
+                    return match row {
+                        Some(row) => $crate::repository::sql::decode_row(&row, "tasks"),
+                        None => Err(sqlx::Error::RowNotFound),
+                    };
+                }
+
+                // The UPDATE keeps the row locked until commit, so the task read
+                // back is the one it wrote. MySQL reports the rows matched
+                // rather than changed, so none means the task does not exist.
+                let mut transaction = self.pool.begin().await?;
+                if query.build().execute(&mut *transaction).await?.rows_affected() == 0 {
+                    return Err(sqlx::Error::RowNotFound);
+                }
+                let task = Self::fetch_task(&mut *transaction, id).await?;
+                transaction.commit().await?;
+
+                Ok(task)
+            }
+
+            #[tracing::instrument(skip_all, fields(db.system = $dialect.system, db.operation = "UPDATE"))]
+            async fn complete_recurring_task(
+                &self,
+                id: uuid::Uuid,
+                changes: &$crate::repository::TaskChanges<'_>,
+                next: &$crate::repository::NewTask<'_>,
+            ) -> Result<Option<$crate::models::Task>, sqlx::Error> {
+                let mut transaction = self.pool.begin().await?;
+
+                // Of concurrent requests, only the one that completes the task
+                // creates its next occurrence
+                let mut query = Self::update_task_query(id, changes);
+                query.push(" AND completed = ");
+                query.push_bind(false);
+                let task = if $dialect.returning {
+                    query.push(format_args!(
+                        " RETURNING {}",
+                        $crate::repository::sql::TASK_COLUMNS
+                    ));
+                    match query.build().fetch_optional(&mut *transaction).await? {
+                        Some(row) => $crate::repository::sql::decode_row(&row, "tasks")?,
+                        None => return Ok(None),
+                    }
+                } else {
+                    if query.build().execute(&mut *transaction).await?.rows_affected() == 0 {
+                        return Ok(None);
+                    }
+                    Self::fetch_task(&mut *transaction, id).await?
+                };
+
+                // Unlike create_task, an append that loses a race here fails
+                // the whole change with a unique violation
+                let last = match next.position {
+                    Some(_) => None,
+                    None => Self::last_position(&mut *transaction, next.user_id).await?,
+                };
+                Self::insert_task(&mut *transaction, next, last.as_deref()).await?;
+                transaction.commit().await?;
+                Ok(Some(task))
+            }
+
+            #[tracing::instrument(skip_all, fields(db.system = $dialect.system, db.operation = "DELETE"))]
+            async fn delete_task(&self, id: uuid::Uuid) -> Result<bool, sqlx::Error> {
+                // Subtasks go with it (ON DELETE CASCADE)
+                let mut query = sqlx::QueryBuilder::<$db>::new("DELETE FROM tasks WHERE id = ");
+                query.push_bind(id);
+
+                let result = query.build().execute(&self.pool).await?;
+                Ok(result.rows_affected() > 0)
+            }
+
+            #[tracing::instrument(skip_all, fields(db.system = $dialect.system, db.operation = "SELECT"))]
+            async fn subtask_ids(
+                &self,
+                parent_ids: &[uuid::Uuid],
+            ) -> Result<Vec<uuid::Uuid>, sqlx::Error> {
+                use sqlx::Row;
+
+                if parent_ids.is_empty() {
+                    return Ok(Vec::new());
+                }
+
+                let mut query =
+                    sqlx::QueryBuilder::<$db>::new("SELECT id FROM tasks WHERE parent_id IN (");
+                let mut ids = query.separated(", ");
+                for parent_id in parent_ids {
+                    ids.push_bind(*parent_id);
+                }
+                query.push(")");
+
+                query
+                    .build()
+                    .fetch_all(&self.pool)
+                    .await?
+                    .iter()
+                    .map(|row| row.try_get("id"))
+                    .collect()
+            }
+
+            #[tracing::instrument(skip_all, fields(db.system = $dialect.system, db.operation = "SELECT"))]
+            async fn subtask_progress(
+                &self,
+                parent_ids: &[uuid::Uuid],
+            ) -> Result<
+                std::collections::HashMap<uuid::Uuid, $crate::models::SubtaskProgress>,
+                sqlx::Error,
+            > {
+                use sqlx::Row;
+
+                if parent_ids.is_empty() {
+                    return Ok(std::collections::HashMap::new());
+                }
+
+                // COUNT rather than SUM, which MySQL returns as DECIMAL
+                let mut query = sqlx::QueryBuilder::<$db>::new(
+                    "SELECT parent_id, COUNT(*) AS total, \
+                     COUNT(CASE WHEN completed THEN 1 END) AS completed \
+                     FROM tasks WHERE parent_id IN (",
+                );
+                let mut ids = query.separated(", ");
+                for parent_id in parent_ids {
+                    ids.push_bind(*parent_id);
+                }
+                query.push(") GROUP BY parent_id");
+
+                query
+                    .build()
+                    .fetch_all(&self.pool)
+                    .await?
+                    .iter()
+                    .map(|row| {
+                        let total: i64 = row.try_get("total")?;
+                        let completed: i64 = row.try_get("completed")?;
+                        Ok((
+                            row.try_get("parent_id")?,
+                            $crate::models::SubtaskProgress::new(total as u64, completed as u64),
+                        ))
+                    })
+                    .collect()
+            }
+
+            #[tracing::instrument(skip_all, fields(db.system = $dialect.system, db.operation = "UPDATE"))]
+            async fn reparent_subtasks(
+                &self,
+                parent_id: uuid::Uuid,
+                new_parent_id: Option<uuid::Uuid>,
+            ) -> Result<u64, sqlx::Error> {
+                let mut query = sqlx::QueryBuilder::<$db>::new("UPDATE tasks SET updated_at = ");
+                query.push_bind($crate::repository::sql::now());
+                query.push(", parent_id = ");
+                query.push_bind(new_parent_id);
+                query.push(" WHERE parent_id = ");
+                query.push_bind(parent_id);
+
+                let result = query.build().execute(&self.pool).await?;
+                Ok(result.rows_affected())
+            }
+
+            #[tracing::instrument(skip_all, fields(db.system = $dialect.system, db.operation = "SELECT"))]
+            async fn adjacent_tasks(
+                &self,
+                task: &$crate::models::Task,
+                direction: $crate::models::SortOrder,
+                limit: i64,
+            ) -> Result<Vec<$crate::models::Task>, sqlx::Error> {
+                let (operator, order) = match direction {
+                    $crate::models::SortOrder::Asc => (">", "ASC"),
+                    $crate::models::SortOrder::Desc => ("<", "DESC"),
+                };
+                let mut query = sqlx::QueryBuilder::<$db>::new(format!(
+                    "SELECT {} FROM tasks WHERE user_id = ",
+                    $crate::repository::sql::TASK_COLUMNS
+                ));
+                query.push_bind(task.user_id);
+                query.push(format_args!(" AND (position, id) {} (", operator));
+                query.push_bind(task.position.clone());
+                query.push(", ");
+                query.push_bind(task.id);
+                query.push(format_args!(") ORDER BY position {0}, id {0} LIMIT ", order));
+                query.push_bind(limit);
+
+                let rows = query.build().fetch_all(&self.pool).await?;
+                rows.iter()
+                    .map(|row| $crate::repository::sql::decode_row(row, "tasks"))
+                    .collect()
+            }
+
+            #[tracing::instrument(skip_all, fields(db.system = $dialect.system, db.operation = "UPDATE"))]
+            async fn rebalance_positions(&self, user_id: uuid::Uuid) -> Result<u64, sqlx::Error> {
+                use sqlx::Row;
+
+                let mut transaction = self.pool.begin().await?;
+
+                let mut query = sqlx::QueryBuilder::<$db>::new("SELECT id, position FROM tasks WHERE user_id = ");
+                query.push_bind(user_id);
+                query.push(" ORDER BY position, id");
+                let rows = query.build().fetch_all(&mut *transaction).await?;
+
+                let ranks = $crate::rank::spread(rows.len());
+                let mut changed = Vec::new();
+                for (row, rank) in rows.iter().zip(ranks) {
+                    let id: uuid::Uuid = row.try_get("id")?;
+                    let position: String = row.try_get("position")?;
+                    if position != rank {
+                        changed.push((id, rank));
+                    }
+                }
+
+                // Positions are unique per user, and a new rank may still be
+                // another task's: park the changed tasks on ranks no task can
+                // have (`~` is not a rank digit) before giving them their own
+                for parked in [true, false] {
+                    for (id, rank) in &changed {
+                        let mut update = sqlx::QueryBuilder::<$db>::new("UPDATE tasks SET position = ");
+                        update.push_bind(if parked { format!("~{}", rank) } else { rank.clone() });
+                        update.push(" WHERE id = ");
+                        update.push_bind(*id);
+                        update.build().execute(&mut *transaction).await?;
+                    }
+                }
+
+                transaction.commit().await?;
+                Ok(changed.len() as u64)
+            }
+        }
+
+        #[async_trait::async_trait]
+        impl $crate::repository::TagRepository for $repository {
+            #[tracing::instrument(skip_all, fields(db.system = $dialect.system, db.operation = "INSERT"))]
+            async fn create_tag(
+                &self,
+                tag: &$crate::repository::NewTag<'_>,
+            ) -> Result<$crate::models::Tag, sqlx::Error> {
+                let tag = tag.into_tag($crate::repository::sql::now());
+
+                let mut query = sqlx::QueryBuilder::<$db>::new(
+                    "INSERT INTO tags (id, user_id, name, color, created_at, updated_at) VALUES (",
+                );
+                query.push_bind(tag.id);
+                query.push(", ");
+                query.push_bind(tag.user_id);
+                query.push(", ");
+                query.push_bind(tag.name.clone());
+                query.push(", ");
+                query.push_bind(tag.color.clone());
+                query.push(", ");
+                query.push_bind(tag.created_at);
+                query.push(", ");
+                query.push_bind(tag.updated_at);
+                query.push(")");
+                query.build().execute(&self.pool).await?;
+
+                Ok(tag)
+            }
+
+            #[tracing::instrument(skip_all, fields(db.system = $dialect.system, db.operation = "SELECT"))]
+            async fn get_tag(&self, id: uuid::Uuid) -> Result<$crate::models::Tag, sqlx::Error> {
+                let mut query = sqlx::QueryBuilder::<$db>::new(format!(
+                    "SELECT {} FROM tags WHERE id = ",
+                    $crate::repository::sql::TAG_COLUMNS
+                ));
+                query.push_bind(id);
+
+                let row = query.build().fetch_one(&self.pool).await?;
+                $crate::repository::sql::decode_row(&row, "tags")
+            }
+
+            #[tracing::instrument(skip_all, fields(db.system = $dialect.system, db.operation = "SELECT"))]
+            async fn list_tags(
+                &self,
+                user_id: uuid::Uuid,
+            ) -> Result<Vec<$crate::models::Tag>, sqlx::Error> {
+                let mut query = sqlx::QueryBuilder::<$db>::new(format!(
+                    "SELECT {} FROM tags WHERE user_id = ",
+                    $crate::repository::sql::TAG_COLUMNS
+                ));
+                query.push_bind(user_id);
+                query.push(" ORDER BY name");
+
+                query
+                    .build()
+                    .fetch_all(&self.pool)
+                    .await?
+                    .iter()
+                    .map(|row| $crate::repository::sql::decode_row(row, "tags"))
+                    .collect()
+            }
+
+            #[tracing::instrument(skip_all, fields(db.system = $dialect.system, db.operation = "UPDATE"))]
+            async fn update_tag(
+                &self,
+                id: uuid::Uuid,
+                changes: &$crate::repository::TagChanges<'_>,
+            ) -> Result<$crate::models::Tag, sqlx::Error> {
+                let mut query = sqlx::QueryBuilder::<$db>::new("UPDATE tags SET updated_at = ");
+                query.push_bind($crate::repository::sql::now());
+
+                if let Some(name) = changes.name {
+                    query.push(", name = ");
+                    query.push_bind(name.to_string());
+                }
+
+                if let Some(color) = changes.color {
+                    query.push(", color = ");
+                    query.push_bind(color.map(str::to_string));
+                }
+
+                query.push(" WHERE id = ");
+                query.push_bind(id);
+                query.build().execute(&self.pool).await?;
+
+                // Read back with the usage count; RowNotFound when the tag does not exist
+                self.get_tag(id).await
+            }
+
+            #[tracing::instrument(skip_all, fields(db.system = $dialect.system, db.operation = "DELETE"))]
+            async fn delete_tag(&self, id: uuid::Uuid) -> Result<bool, sqlx::Error> {
+                // task_tags rows go with it (ON DELETE CASCADE)
+                let mut query = sqlx::QueryBuilder::<$db>::new("DELETE FROM tags WHERE id = ");
+                query.push_bind(id);
+
+                let result = query.build().execute(&self.pool).await?;
+                Ok(result.rows_affected() > 0)
+            }
+
+            #[tracing::instrument(skip_all, fields(db.system = $dialect.system, db.operation = "INSERT"))]
+            async fn add_task_tags(
+                &self,
+                task_id: uuid::Uuid,
+                tag_ids: &[uuid::Uuid],
+            ) -> Result<(), sqlx::Error> {
+                if tag_ids.is_empty() {
+                    return Ok(());
+                }
+
+                let mut query = sqlx::QueryBuilder::<$db>::new(format!(
+                    "{} task_tags (task_id, tag_id) ",
+                    $dialect.insert_ignore[0]
+                ));
+                query.push_values(tag_ids, |mut row, tag_id| {
+                    row.push_bind(task_id).push_bind(*tag_id);
+                });
+                query.push($dialect.insert_ignore[1]);
+                query.build().execute(&self.pool).await?;
+
+                Ok(())
+            }
+
+            #[tracing::instrument(skip_all, fields(db.system = $dialect.system, db.operation = "DELETE"))]
+            async fn remove_task_tags(
+                &self,
+                task_id: uuid::Uuid,
+                tag_ids: &[uuid::Uuid],
+            ) -> Result<(), sqlx::Error> {
+                if tag_ids.is_empty() {
+                    return Ok(());
+                }
+
+                let mut query =
+                    sqlx::QueryBuilder::<$db>::new("DELETE FROM task_tags WHERE task_id = ");
+                query.push_bind(task_id);
+                query.push(" AND tag_id IN (");
+                let mut ids = query.separated(", ");
+                for tag_id in tag_ids {
+                    ids.push_bind(*tag_id);
+                }
+                query.push(")");
+                query.build().execute(&self.pool).await?;
+
+                Ok(())
+            }
+
+            #[tracing::instrument(skip_all, fields(db.system = $dialect.system, db.operation = "SELECT"))]
+            async fn task_tag_names(
+                &self,
+                task_ids: &[uuid::Uuid],
+            ) -> Result<std::collections::HashMap<uuid::Uuid, Vec<String>>, sqlx::Error> {
+                use sqlx::Row;
+
+                let mut names: std::collections::HashMap<uuid::Uuid, Vec<String>> =
+                    std::collections::HashMap::new();
+                if task_ids.is_empty() {
+                    return Ok(names);
+                }
+
+                let mut query = sqlx::QueryBuilder::<$db>::new(
+                    "SELECT task_tags.task_id, tags.name FROM task_tags \
+                     JOIN tags ON tags.id = task_tags.tag_id WHERE task_tags.task_id IN (",
+                );
+                let mut ids = query.separated(", ");
+                for task_id in task_ids {
+                    ids.push_bind(*task_id);
+                }
+                query.push(") ORDER BY tags.name");
+
+                for row in query.build().fetch_all(&self.pool).await? {
+                    let task_id: uuid::Uuid = row.try_get("task_id")?;
+                    names.entry(task_id).or_default().push(row.try_get("name")?);
+                }
+                Ok(names)
+            }
+        }
+
+        #[async_trait::async_trait]
+        impl $crate::repository::ProjectRepository for $repository {
+            #[tracing::instrument(skip_all, fields(db.system = $dialect.system, db.operation = "INSERT"))]
+            async fn create_project(
+                &self,
+                project: &$crate::repository::NewProject<'_>,
+            ) -> Result<$crate::models::Project, sqlx::Error> {
+                let project = project.into_project($crate::repository::sql::now());
+
+                let mut query = sqlx::QueryBuilder::<$db>::new(
+                    "INSERT INTO projects (id, user_id, name, color, archived, position, created_at, updated_at) VALUES (",
+                );
+                query.push_bind(project.id);
+                query.push(", ");
+                query.push_bind(project.user_id);
+                query.push(", ");
+                query.push_bind(project.name.clone());
+                query.push(", ");
+                query.push_bind(project.color.clone());
+                query.push(", ");
+                query.push_bind(project.archived);
+                query.push(", ");
+                query.push_bind(project.position);
+                query.push(", ");
+                query.push_bind(project.created_at);
+                query.push(", ");
+                query.push_bind(project.updated_at);
+                query.push(")");
+                query.build().execute(&self.pool).await?;
+
+                Ok(project)
+            }
+
+            #[tracing::instrument(skip_all, fields(db.system = $dialect.system, db.operation = "SELECT"))]
+            async fn get_project(
+                &self,
+                id: uuid::Uuid,
+            ) -> Result<$crate::models::Project, sqlx::Error> {
+                let mut query = sqlx::QueryBuilder::<$db>::new(format!(
+                    "SELECT {} FROM projects WHERE id = ",
+                    $crate::repository::sql::PROJECT_COLUMNS
+                ));
+                query.push_bind(id);
+
+                let row = query.build().fetch_one(&self.pool).await?;
+                $crate::repository::sql::decode_row(&row, "projects")
+            }
+
+            #[tracing::instrument(skip_all, fields(db.system = $dialect.system, db.operation = "SELECT"))]
+            async fn list_projects(
+                &self,
+                user_id: uuid::Uuid,
+            ) -> Result<Vec<$crate::models::Project>, sqlx::Error> {
+                let mut query = sqlx::QueryBuilder::<$db>::new(format!(
+                    "SELECT {} FROM projects WHERE user_id = ",
+                    $crate::repository::sql::PROJECT_COLUMNS
+                ));
+                query.push_bind(user_id);
+                // Ids are time-ordered, so ties on position keep creation order
+                query.push(" ORDER BY position, id");
+
+                query
+                    .build()
+                    .fetch_all(&self.pool)
+                    .await?
+                    .iter()
+                    .map(|row| $crate::repository::sql::decode_row(row, "projects"))
+                    .collect()
+            }
+
+            #[tracing::instrument(skip_all, fields(db.system = $dialect.system, db.operation = "UPDATE"))]
+            async fn update_project(
+                &self,
+                id: uuid::Uuid,
+                changes: &$crate::repository::ProjectChanges<'_>,
+            ) -> Result<$crate::models::Project, sqlx::Error> {
+                let mut query =
+                    sqlx::QueryBuilder::<$db>::new("UPDATE projects SET updated_at = ");
+                query.push_bind($crate::repository::sql::now());
+
+                if let Some(name) = changes.name {
+                    query.push(", name = ");
+                    query.push_bind(name.to_string());
+                }
+
+                if let Some(color) = changes.color {
+                    query.push(", color = ");
+                    query.push_bind(color.map(str::to_string));
+                }
+
+                if let Some(archived) = changes.archived {
+                    query.push(", archived = ");
+                    query.push_bind(archived);
+                }
+
+                if let Some(position) = changes.position {
+                    query.push(", position = ");
+                    query.push_bind(position);
+                }
+
+                query.push(" WHERE id = ");
+                query.push_bind(id);
+                query.build().execute(&self.pool).await?;
+
+                // RowNotFound when the project does not exist
+                self.get_project(id).await
+            }
+
+            #[tracing::instrument(skip_all, fields(db.system = $dialect.system, db.operation = "DELETE"))]
+            async fn delete_project(&self, id: uuid::Uuid) -> Result<bool, sqlx::Error> {
+                // Its tasks stay, without a project (ON DELETE SET NULL)
+                let mut query =
+                    sqlx::QueryBuilder::<$db>::new("DELETE FROM projects WHERE id = ");
+                query.push_bind(id);
+
+                let result = query.build().execute(&self.pool).await?;
+                Ok(result.rows_affected() > 0)
+            }
+        }
+
+        #[async_trait::async_trait]
+        impl $crate::repository::DependencyRepository for $repository {
+            #[tracing::instrument(skip_all, fields(db.system = $dialect.system, db.operation = "INSERT"))]
+            async fn add_blocker(
+                &self,
+                task_id: uuid::Uuid,
+                blocker_id: uuid::Uuid,
+            ) -> Result<bool, sqlx::Error> {
+                if blocker_id == task_id {
+                    return Ok(false);
+                }
+
+                // Concurrent additions for the same owner could each pass the
+                // check below and together close a cycle
+                let mut transaction = self.pool.begin().await?;
+                Self::lock_task_owner(&mut transaction, task_id).await?;
+
+                // The new edge closes a cycle when the task already blocks the
+                // blocker, directly or through other tasks
+                let mut seen = std::collections::HashSet::from([blocker_id]);
+                let mut level = vec![blocker_id];
+                while !level.is_empty() {
+                    level = Self::fetch_blocker_ids(&mut *transaction, &level).await?;
+                    if level.contains(&task_id) {
+                        return Ok(false);
+                    }
+                    level.retain(|id| seen.insert(*id));
+                }
+
+                let mut query = sqlx::QueryBuilder::<$db>::new(format!(
+                    "{} task_dependencies (task_id, blocker_id) VALUES (",
+                    $dialect.insert_ignore[0]
+                ));
+                query.push_bind(task_id);
+                query.push(", ");
+                query.push_bind(blocker_id);
+                query.push(")");
+                query.push($dialect.insert_ignore[1]);
+                query.build().execute(&mut *transaction).await?;
+
+                transaction.commit().await?;
+                Ok(true)
+            }
+
+            #[tracing::instrument(skip_all, fields(db.system = $dialect.system, db.operation = "DELETE"))]
+            async fn remove_blocker(
+                &self,
+                task_id: uuid::Uuid,
+                blocker_id: uuid::Uuid,
+            ) -> Result<bool, sqlx::Error> {
+                let mut query =
+                    sqlx::QueryBuilder::<$db>::new("DELETE FROM task_dependencies WHERE task_id = ");
+                query.push_bind(task_id);
+                query.push(" AND blocker_id = ");
+                query.push_bind(blocker_id);
+
+                let result = query.build().execute(&self.pool).await?;
+                Ok(result.rows_affected() > 0)
+            }
+
+            #[tracing::instrument(skip_all, fields(db.system = $dialect.system, db.operation = "SELECT"))]
+            async fn list_blockers(
+                &self,
+                task_id: uuid::Uuid,
+            ) -> Result<Vec<$crate::models::Task>, sqlx::Error> {
+                let mut query = sqlx::QueryBuilder::<$db>::new(format!(
+                    "SELECT {} FROM tasks WHERE id IN \
+                     (SELECT blocker_id FROM task_dependencies WHERE task_id = ",
+                    $crate::repository::sql::TASK_COLUMNS
+                ));
+                query.push_bind(task_id);
+                query.push(") ORDER BY created_at, id");
+
+                query
+                    .build()
+                    .fetch_all(&self.pool)
+                    .await?
+                    .iter()
+                    .map(|row| $crate::repository::sql::decode_row(row, "tasks"))
+                    .collect()
+            }
+
+            #[tracing::instrument(skip_all, fields(db.system = $dialect.system, db.operation = "SELECT"))]
+            async fn blocker_ids(
+                &self,
+                task_ids: &[uuid::Uuid],
+            ) -> Result<Vec<uuid::Uuid>, sqlx::Error> {
+                Self::fetch_blocker_ids(&self.pool, task_ids).await
+            }
+
+            #[tracing::instrument(skip_all, fields(db.system = $dialect.system, db.operation = "SELECT"))]
+            async fn blocked_task_ids(
+                &self,
+                task_ids: &[uuid::Uuid],
+            ) -> Result<std::collections::HashSet<uuid::Uuid>, sqlx::Error> {
+                use sqlx::Row;
+
+                if task_ids.is_empty() {
+                    return Ok(std::collections::HashSet::new());
+                }
+
+                let mut query = sqlx::QueryBuilder::<$db>::new(
+                    "SELECT DISTINCT task_dependencies.task_id FROM task_dependencies \
+                     JOIN tasks ON tasks.id = task_dependencies.blocker_id \
+                     WHERE NOT tasks.completed AND task_dependencies.task_id IN (",
+                );
+                let mut ids = query.separated(", ");
+                for task_id in task_ids {
+                    ids.push_bind(*task_id);
+                }
+                query.push(")");
+
+                query
+                    .build()
+                    .fetch_all(&self.pool)
+                    .await?
+                    .iter()
+                    .map(|row| row.try_get("task_id"))
+                    .collect()
+            }
+        }
+
+        #[async_trait::async_trait]
+        impl $crate::repository::ReminderRepository for $repository {
+            #[tracing::instrument(skip_all, fields(db.system = $dialect.system, db.operation = "INSERT"))]
+            async fn create_reminder(
+                &self,
+                reminder: &$crate::repository::NewReminder<'_>,
+            ) -> Result<$crate::models::Reminder, sqlx::Error> {
+                let reminder = reminder.into_reminder($crate::repository::sql::now());
+
+                let mut query = sqlx::QueryBuilder::<$db>::new(
+                    "INSERT INTO reminders (id, task_id, user_id, channel, target, remind_at, minutes_before, fire_at, attempts, created_at, updated_at) VALUES (",
+                );
+                let mut values = query.separated(", ");
+                values.push_bind(reminder.id);
+                values.push_bind(reminder.task_id);
+                values.push_bind(reminder.user_id);
+                values.push_bind(reminder.channel);
+                values.push_bind(reminder.target.clone());
+                values.push_bind(reminder.remind_at);
+                values.push_bind(reminder.minutes_before);
+                values.push_bind(reminder.fire_at);
+                values.push_bind(reminder.attempts);
+                values.push_bind(reminder.created_at);
+                values.push_bind(reminder.updated_at);
+                query.push(")");
+                query.build().execute(&self.pool).await?;
+
+                Ok(reminder)
+            }
+
+            #[tracing::instrument(skip_all, fields(db.system = $dialect.system, db.operation = "SELECT"))]
+            async fn get_reminder(
+                &self,
+                id: uuid::Uuid,
+            ) -> Result<$crate::models::Reminder, sqlx::Error> {
+                let mut query = sqlx::QueryBuilder::<$db>::new(format!(
+                    "SELECT {} FROM reminders WHERE id = ",
+                    $crate::repository::sql::REMINDER_COLUMNS
+                ));
+                query.push_bind(id);
+
+                let row = query.build().fetch_one(&self.pool).await?;
+                $crate::repository::sql::decode_row(&row, "reminders")
+            }
+
+            #[tracing::instrument(skip_all, fields(db.system = $dialect.system, db.operation = "SELECT"))]
+            async fn list_reminders(
+                &self,
+                task_id: uuid::Uuid,
+            ) -> Result<Vec<$crate::models::Reminder>, sqlx::Error> {
+                let mut query = sqlx::QueryBuilder::<$db>::new(format!(
+                    "SELECT {} FROM reminders WHERE task_id = ",
+                    $crate::repository::sql::REMINDER_COLUMNS
+                ));
+                query.push_bind(task_id);
+                query.push(" ORDER BY id");
+
+                query
+                    .build()
+                    .fetch_all(&self.pool)
+                    .await?
+                    .iter()
+                    .map(|row| $crate::repository::sql::decode_row(row, "reminders"))
+                    .collect()
+            }
+
+            #[tracing::instrument(skip_all, fields(db.system = $dialect.system, db.operation = "DELETE"))]
+            async fn delete_reminder(&self, id: uuid::Uuid) -> Result<bool, sqlx::Error> {
+                // Its delivery claims go with it (ON DELETE CASCADE)
+                let mut query =
+                    sqlx::QueryBuilder::<$db>::new("DELETE FROM reminders WHERE id = ");
+                query.push_bind(id);
+
+                let result = query.build().execute(&self.pool).await?;
+                Ok(result.rows_affected() > 0)
+            }
+
+            #[tracing::instrument(skip_all, fields(db.system = $dialect.system, db.operation = "UPDATE"))]
+            async fn schedule_reminder(
+                &self,
+                id: uuid::Uuid,
+                fire_at: Option<chrono::DateTime<chrono::Utc>>,
+            ) -> Result<$crate::models::Reminder, sqlx::Error> {
+                let mut transaction = self.pool.begin().await?;
+
+                // Moving back to an earlier fire time must not run into its old claim
+                let mut query = sqlx::QueryBuilder::<$db>::new(
+                    "DELETE FROM reminder_deliveries WHERE reminder_id = ",
+                );
+                query.push_bind(id);
+                query.build().execute(&mut *transaction).await?;
+
+                let mut query = sqlx::QueryBuilder::<$db>::new("UPDATE reminders SET fire_at = ");
+                query.push_bind(fire_at);
+                query.push(", delivered_at = NULL, attempts = 0, last_error = NULL, updated_at = ");
+                query.push_bind($crate::repository::sql::now());
+                query.push(" WHERE id = ");
+                query.push_bind(id);
+                query.build().execute(&mut *transaction).await?;
+
+                transaction.commit().await?;
+
+                // RowNotFound when the reminder does not exist
+                self.get_reminder(id).await
+            }
+
+            #[tracing::instrument(skip_all, fields(db.system = $dialect.system, db.operation = "SELECT"))]
+            async fn due_reminders(
+                &self,
+                now: chrono::DateTime<chrono::Utc>,
+                max_attempts: i32,
+                limit: i64,
+            ) -> Result<Vec<$crate::models::Reminder>, sqlx::Error> {
+                let mut query = sqlx::QueryBuilder::<$db>::new(format!(
+                    "SELECT {} FROM reminders JOIN tasks ON tasks.id = reminders.task_id \
+                     WHERE reminders.delivered_at IS NULL AND NOT tasks.completed AND reminders.fire_at <= ",
+                    $crate::repository::sql::REMINDER_COLUMNS
+                ));
+                query.push_bind(now);
+                query.push(" AND reminders.attempts < ");
+                query.push_bind(max_attempts);
+                query.push(" ORDER BY reminders.fire_at, reminders.id LIMIT ");
+                query.push_bind(limit);
+
+                query
+                    .build()
+                    .fetch_all(&self.pool)
+                    .await?
+                    .iter()
+                    .map(|row| $crate::repository::sql::decode_row(row, "reminders"))
+                    .collect()
+            }
+
+            #[tracing::instrument(skip_all, fields(db.system = $dialect.system, db.operation = "INSERT"))]
+            async fn claim_delivery(
+                &self,
+                id: uuid::Uuid,
+                fire_at: chrono::DateTime<chrono::Utc>,
+            ) -> Result<bool, sqlx::Error> {
+                let mut query = sqlx::QueryBuilder::<$db>::new(format!(
+                    "{} reminder_deliveries (reminder_id, fire_at, claimed_at) VALUES (",
+                    $dialect.insert_ignore[0]
+                ));
+                query.push_bind(id);
+                query.push(", ");
+                query.push_bind(fire_at);
+                query.push(", ");
+                query.push_bind($crate::repository::sql::now());
+                query.push(")");
+                query.push($dialect.insert_ignore[1]);
+
+                // Nothing is inserted when another scheduler got there first
+                let result = query.build().execute(&self.pool).await?;
+                Ok(result.rows_affected() > 0)
+            }
+
+            #[tracing::instrument(skip_all, fields(db.system = $dialect.system, db.operation = "UPDATE"))]
+            async fn mark_delivered(
+                &self,
+                id: uuid::Uuid,
+                delivered_at: chrono::DateTime<chrono::Utc>,
+            ) -> Result<(), sqlx::Error> {
+                let mut query =
+                    sqlx::QueryBuilder::<$db>::new("UPDATE reminders SET delivered_at = ");
+                query.push_bind(delivered_at);
+                query.push(", last_error = NULL, updated_at = ");
+                query.push_bind($crate::repository::sql::now());
+                query.push(" WHERE id = ");
+                query.push_bind(id);
+                query.build().execute(&self.pool).await?;
+
+                Ok(())
+            }
+
+            #[tracing::instrument(skip_all, fields(db.system = $dialect.system, db.operation = "UPDATE"))]
+            async fn record_failure(
+                &self,
+                id: uuid::Uuid,
+                fire_at: chrono::DateTime<chrono::Utc>,
+                error: &str,
+            ) -> Result<(), sqlx::Error> {
+                let mut transaction = self.pool.begin().await?;
+
+                let mut query = sqlx::QueryBuilder::<$db>::new(
+                    "UPDATE reminders SET attempts = attempts + 1, last_error = ",
+                );
+                query.push_bind(error.to_string());
+                query.push(", updated_at = ");
+                query.push_bind($crate::repository::sql::now());
+                query.push(" WHERE id = ");
+                query.push_bind(id);
+                query.build().execute(&mut *transaction).await?;
+
+                let mut query = sqlx::QueryBuilder::<$db>::new(
+                    "DELETE FROM reminder_deliveries WHERE reminder_id = ",
+                );
+                query.push_bind(id);
+                query.push(" AND fire_at = ");
+                query.push_bind(fire_at);
+                query.build().execute(&mut *transaction).await?;
+
+                transaction.commit().await
+            }
+        }
+
+        #[async_trait::async_trait]
+        impl $crate::repository::NotificationRepository for $repository {
+            #[tracing::instrument(skip_all, fields(db.system = $dialect.system, db.operation = "INSERT"))]
+            async fn create_notification(
+                &self,
+                notification: &$crate::models::Notification,
+            ) -> Result<(), sqlx::Error> {
+                let mut query = sqlx::QueryBuilder::<$db>::new(
+                    "INSERT INTO notifications (id, user_id, task_id, message, created_at, read_at) VALUES (",
+                );
+                let mut values = query.separated(", ");
+                values.push_bind(notification.id);
+                values.push_bind(notification.user_id);
+                values.push_bind(notification.task_id);
+                values.push_bind(notification.message.clone());
+                values.push_bind(notification.created_at);
+                values.push_bind(notification.read_at);
+                query.push(")");
+                query.build().execute(&self.pool).await?;
+
+                Ok(())
+            }
+
+            #[tracing::instrument(skip_all, fields(db.system = $dialect.system, db.operation = "SELECT"))]
+            async fn list_notifications(
+                &self,
+                user_id: uuid::Uuid,
+                unread_only: bool,
+            ) -> Result<Vec<$crate::models::Notification>, sqlx::Error> {
+                let mut query = sqlx::QueryBuilder::<$db>::new(format!(
+                    "SELECT {} FROM notifications WHERE user_id = ",
+                    $crate::repository::sql::NOTIFICATION_COLUMNS
+                ));
+                query.push_bind(user_id);
+                if unread_only {
+                    query.push(" AND read_at IS NULL");
+                }
+                query.push(" ORDER BY id DESC");
+
+                query
+                    .build()
+                    .fetch_all(&self.pool)
+                    .await?
+                    .iter()
+                    .map(|row| $crate::repository::sql::decode_row(row, "notifications"))
+                    .collect()
+            }
+
+            #[tracing::instrument(skip_all, fields(db.system = $dialect.system, db.operation = "UPDATE"))]
+            async fn mark_notification_read(
+                &self,
+                id: uuid::Uuid,
+                read_at: chrono::DateTime<chrono::Utc>,
+            ) -> Result<bool, sqlx::Error> {
+                use sqlx::Row;
+
+                // Keeps the time it was first read
+                let mut query = sqlx::QueryBuilder::<$db>::new(
+                    "UPDATE notifications SET read_at = COALESCE(read_at, ",
+                );
+                query.push_bind(read_at);
+                query.push(") WHERE id = ");
+                query.push_bind(id);
+                query.build().execute(&self.pool).await?;
+
+                // MySQL reports unchanged rows as unaffected, so count instead
+                let mut query = sqlx::QueryBuilder::<$db>::new(
+                    "SELECT COUNT(*) AS count FROM notifications WHERE id = ",
+                );
+                query.push_bind(id);
+                let row = query.build().fetch_one(&self.pool).await?;
+                let count: i64 = row.try_get("count")?;
+                Ok(count > 0)
+            }
+        }
+
+        #[async_trait::async_trait]
+        impl $crate::repository::UserRepository for $repository {
+            #[tracing::instrument(skip_all, fields(db.system = $dialect.system, db.operation = "INSERT"))]
+            async fn create_user(
+                &self,
+                id: uuid::Uuid,
+                email: &str,
+                password_hash: &str,
+                time_zone: &str,
+            ) -> Result<$crate::models::User, sqlx::Error> {
+                let now = $crate::repository::sql::now();
+
+                let mut query = sqlx::QueryBuilder::<$db>::new(
+                    "INSERT INTO users (id, email, password_hash, time_zone, created_at, updated_at) VALUES (",
+                );
+                query.push_bind(id);
+                query.push(", ");
+                query.push_bind(email.to_string());
+                query.push(", ");
+                query.push_bind(password_hash.to_string());
+                query.push(", ");
+                query.push_bind(time_zone.to_string());
+                query.push(", ");
+                query.push_bind(now);
+                query.push(", ");
+                query.push_bind(now);
+                query.push(")");
+                query.build().execute(&self.pool).await?;
+
+                Ok($crate::models::User {
+                    id,
+                    email: email.to_string(),
+                    password_hash: password_hash.to_string(),
+                    time_zone: time_zone.to_string(),
+                    created_at: Some(now),
+                    updated_at: Some(now),
+                })
+            }
+
+            #[tracing::instrument(skip_all, fields(db.system = $dialect.system, db.operation = "SELECT"))]
+            async fn get_user_by_id(
+                &self,
+                user_id: uuid::Uuid,
+            ) -> Result<$crate::models::User, sqlx::Error> {
+                let mut query = sqlx::QueryBuilder::<$db>::new(format!(
+                    "SELECT {} FROM users WHERE id = ",
+                    $crate::repository::sql::USER_COLUMNS
+                ));
+                query.push_bind(user_id);
+
+                let row = query.build().fetch_one(&self.pool).await?;
+                $crate::repository::sql::decode_row(&row, "users")
+            }
+
+            #[tracing::instrument(skip_all, fields(db.system = $dialect.system, db.operation = "SELECT"))]
+            async fn get_user_by_email(
+                &self,
+                email: &str,
+            ) -> Result<Option<$crate::models::User>, sqlx::Error> {
+                let mut query = sqlx::QueryBuilder::<$db>::new(format!(
+                    "SELECT {} FROM users WHERE email = ",
+                    $crate::repository::sql::USER_COLUMNS
+                ));
+                query.push_bind(email.to_string());
+
+                query
+                    .build()
+                    .fetch_optional(&self.pool)
+                    .await?
+                    .as_ref()
+                    .map(|row| $crate::repository::sql::decode_row(row, "users"))
+                    .transpose()
+            }
+
+            #[tracing::instrument(skip_all, fields(db.system = $dialect.system, db.operation = "SELECT"))]
+            async fn count_users(&self) -> Result<u64, sqlx::Error> {
+                use sqlx::Row;
+
+                let row = sqlx::query("SELECT COUNT(*) AS count FROM users")
+                    .fetch_one(&self.pool)
+                    .await?;
+
+                let count: i64 = row.try_get("count")?;
+                Ok(count as u64)
+            }
+
+            #[tracing::instrument(skip_all, fields(db.system = $dialect.system, db.operation = "UPDATE"))]
+            async fn update_user_time_zone(
+                &self,
+                user_id: uuid::Uuid,
+                time_zone: &str,
+            ) -> Result<$crate::models::User, sqlx::Error> {
+                let mut query = sqlx::QueryBuilder::<$db>::new("UPDATE users SET time_zone = ");
+                query.push_bind(time_zone.to_string());
+                query.push(", updated_at = ");
+                query.push_bind(chrono::Utc::now());
+                query.push(" WHERE id = ");
+                query.push_bind(user_id);
+
+                if query.build().execute(&self.pool).await?.rows_affected() == 0 {
+                    return Err(sqlx::Error::RowNotFound);
+                }
+                self.get_user_by_id(user_id).await
+            }
+        }
+
+        #[async_trait::async_trait]
+        impl $crate::repository::SessionRepository for $repository {
+            #[tracing::instrument(skip_all, fields(db.system = $dialect.system, db.operation = "INSERT"))]
+            async fn create_session(
+                &self,
+                id: uuid::Uuid,
+                user_id: uuid::Uuid,
+                token_hash: &str,
+                expires_at: chrono::DateTime<chrono::Utc>,
+            ) -> Result<$crate::models::Session, sqlx::Error> {
+                let now = $crate::repository::sql::now();
+                let expires_at = chrono::SubsecRound::trunc_subsecs(expires_at, 6);
+
+                let mut query = sqlx::QueryBuilder::<$db>::new(
+                    "INSERT INTO sessions (id, user_id, token_hash, expires_at, created_at) VALUES (",
+                );
+                query.push_bind(id);
+                query.push(", ");
+                query.push_bind(user_id);
+                query.push(", ");
+                query.push_bind(token_hash.to_string());
+                query.push(", ");
+                query.push_bind(expires_at);
+                query.push(", ");
+                query.push_bind(now);
+                query.push(")");
+                query.build().execute(&self.pool).await?;
+
+                Ok($crate::models::Session {
+                    id,
+                    user_id,
+                    token_hash: token_hash.to_string(),
+                    expires_at,
+                    created_at: Some(now),
+                })
+            }
+
+            #[tracing::instrument(skip_all, fields(db.system = $dialect.system, db.operation = "SELECT"))]
+            async fn get_session_by_id(
+                &self,
+                session_id: uuid::Uuid,
+            ) -> Result<$crate::models::Session, sqlx::Error> {
+                let mut query = sqlx::QueryBuilder::<$db>::new(format!(
+                    "SELECT {} FROM sessions WHERE id = ",
+                    $crate::repository::sql::SESSION_COLUMNS
+                ));
+                query.push_bind(session_id);
+
+                let row = query.build().fetch_one(&self.pool).await?;
+                $crate::repository::sql::decode_row(&row, "sessions")
+            }
+
+            #[tracing::instrument(skip_all, fields(db.system = $dialect.system, db.operation = "SELECT"))]
+            async fn get_session_by_token(
+                &self,
+                token_hash: &str,
+            ) -> Result<Option<$crate::models::Session>, sqlx::Error> {
+                let mut query = sqlx::QueryBuilder::<$db>::new(format!(
+                    "SELECT {} FROM sessions WHERE token_hash = ",
+                    $crate::repository::sql::SESSION_COLUMNS
+                ));
+                query.push_bind(token_hash.to_string());
+                query.push(" AND expires_at > ");
+                query.push_bind(chrono::Utc::now());
+
+                query
+                    .build()
+                    .fetch_optional(&self.pool)
+                    .await?
+                    .as_ref()
+                    .map(|row| $crate::repository::sql::decode_row(row, "sessions"))
+                    .transpose()
+            }
+
+            #[tracing::instrument(skip_all, fields(db.system = $dialect.system, db.operation = "DELETE"))]
+            async fn delete_session(&self, session_id: uuid::Uuid) -> Result<(), sqlx::Error> {
+                let mut query = sqlx::QueryBuilder::<$db>::new("DELETE FROM sessions WHERE id = ");
+                query.push_bind(session_id);
+                query.build().execute(&self.pool).await?;
+
+                Ok(())
+            }
+
+            #[tracing::instrument(skip_all, fields(db.system = $dialect.system, db.operation = "DELETE"))]
+            async fn cleanup_expired_sessions(&self) -> Result<u64, sqlx::Error> {
+                let mut query =
+                    sqlx::QueryBuilder::<$db>::new("DELETE FROM sessions WHERE expires_at <= ");
+                query.push_bind(chrono::Utc::now());
+
+                let result = query.build().execute(&self.pool).await?;
+                Ok(result.rows_affected())
+            }
+        }
+
+        #[async_trait::async_trait]
+        impl $crate::repository::Repository for $repository {
+            fn backend(&self) -> &'static str {
+                $dialect.system
+            }
+
+            fn pool_stats(&self) -> $crate::repository::PoolStats {
+                $crate::repository::PoolStats {
+                    size: self.pool.size(),
+                    idle: self.pool.num_idle(),
+                    max_connections: self.pool.options().get_max_connections(),
+                }
+            }
+
+            #[tracing::instrument(skip_all, fields(db.system = $dialect.system, db.operation = "SELECT"))]
+            async fn ping(&self) -> Result<(), sqlx::Error> {
+                sqlx::query("SELECT 1").execute(&self.pool).await?;
+                Ok(())
+            }
+
+            async fn acquire_wait(&self) -> Result<std::time::Duration, sqlx::Error> {
+                let started = std::time::Instant::now();
+                let connection = self.pool.acquire().await?;
+                let waited = started.elapsed();
+                drop(connection);
+
+                Ok(waited)
+            }
+
+            fn migrations(&self) -> &'static [$crate::repository::Migration] {
+                $dialect.migrations
+            }
+
+            #[tracing::instrument(skip_all, fields(db.system = $dialect.system, db.operation = "MIGRATE"))]
+            async fn migrate_to(&self, target: Option<i64>) -> Result<(), sqlx::Error> {
+                tracing::info!("Initializing database schema");
+
+                // Track which migrations have been applied
+                sqlx::query($dialect.create_migrations_table)
+                    .execute(&self.pool)
+                    .await?;
+
+                let pending: Vec<_> = self
+                    .pending_migrations()
+                    .await?
+                    .into_iter()
+                    .filter(|migration| target.is_none_or(|target| migration.version <= target))
+                    .collect();
+
+                // Some statements (e.g. SQLite's foreign_keys pragma) only affect
+                // their own connection, so run everything on the same one
+                let mut connection = self.pool.acquire().await?;
+
+                for migration in pending {
+                    tracing::info!(
+                        "Applying migration {}: {}",
+                        migration.version,
+                        migration.description
+                    );
+
+                    for statement in migration.statements {
+                        sqlx::query(statement).execute(&mut *connection).await?;
+                    }
+
+                    for statement in migration.backfill {
+                        let mut total = 0;
+                        loop {
+                            let result = sqlx::query(statement).execute(&mut *connection).await?;
+                            if result.rows_affected() == 0 {
+                                break;
+                            }
+                            total += result.rows_affected();
+                        }
+                        tracing::info!("Backfilled {} rows", total);
+                    }
+
+                    let mut query = sqlx::QueryBuilder::<$db>::new(
+                        "INSERT INTO schema_migrations (version, description, applied_at) VALUES (",
+                    );
+                    query.push_bind(migration.version);
+                    query.push(", ");
+                    query.push_bind(migration.description);
+                    query.push(", ");
+                    query.push_bind(chrono::Utc::now());
+                    query.push(")");
+                    query.build().execute(&mut *connection).await?;
+                }
+
+                tracing::info!("Database schema initialized successfully");
+                Ok(())
+            }
+
+            #[tracing::instrument(skip_all, fields(db.system = $dialect.system, db.operation = "SELECT"))]
+            async fn pending_migrations(
+                &self,
+            ) -> Result<Vec<&'static $crate::repository::Migration>, sqlx::Error> {
+                use sqlx::Row;
+
+                let row = sqlx::query($dialect.migrations_table_exists)
+                    .fetch_one(&self.pool)
+                    .await?;
+
+                // Nothing has been applied if the tracking table doesn't exist yet
+                if row.try_get::<i64, _>("count")? == 0 {
+                    return Ok($dialect.migrations.iter().collect());
+                }
+
+                let applied = sqlx::query("SELECT version FROM schema_migrations")
+                    .fetch_all(&self.pool)
+                    .await?
+                    .iter()
+                    .map(|row| row.try_get::<i64, _>("version"))
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                Ok($dialect
+                    .migrations
+                    .iter()
+                    .filter(|migration| !applied.contains(&migration.version))
+                    .collect())
+            }
+        }
+    };
+}
+
+pub(super) use sql_repository;
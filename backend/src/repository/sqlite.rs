@@ -0,0 +1,1473 @@
+use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePool, SqlitePoolOptions};
+use sqlx::Sqlite;
+use std::str::FromStr;
+use std::time::Duration;
+
+use super::sql::{sql_repository, Dialect};
+use super::Migration;
+use crate::config::DatabaseConfig;
+use crate::search::SearchQuery;
+
+/// Repository backed by a SQLite database file, for single-binary deployments
+#[derive(Clone)]
+pub struct SqliteRepository {
+    pool: SqlitePool,
+}
+
+impl SqliteRepository {
+    /// Wrap an existing pool
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Open (creating if needed) the SQLite database from the database settings
+    #[tracing::instrument(skip_all, fields(db.system = "sqlite", db.operation = "CONNECT"))]
+    pub async fn connect(config: &DatabaseConfig) -> Result<Self, sqlx::Error> {
+        let options = SqliteConnectOptions::from_str(&config.url)?
+            .create_if_missing(true)
+            .foreign_keys(true)
+            // Let readers proceed while a write is in progress
+            .journal_mode(SqliteJournalMode::Wal);
+
+        let pool = SqlitePoolOptions::new()
+            .max_connections(config.max_connections)
+            .acquire_timeout(Duration::from_secs(config.acquire_timeout_secs))
+            .connect_with(options)
+            .await?;
+
+        Ok(Self::new(pool))
+    }
+
+    /// The underlying connection pool
+    pub fn pool(&self) -> &SqlitePool {
+        &self.pool
+    }
+}
+
+sql_repository!(SqliteRepository, Sqlite, DIALECT);
+
+const DIALECT: Dialect = Dialect {
+    system: "sqlite",
+    // LIKE is case-insensitive for ASCII in SQLite
+    like: "LIKE",
+    returning: true,
+    insert_ignore: ["INSERT INTO", " ON CONFLICT DO NOTHING"],
+    fulltext_query: SearchQuery::to_fts5,
+    fulltext_match: ["rowid IN (SELECT rowid FROM tasks_fts WHERE tasks_fts MATCH ", ")"],
+    // bm25() is lower for better matches
+    fulltext_rank: [
+        "(SELECT -bm25(tasks_fts) FROM tasks_fts WHERE tasks_fts MATCH ",
+        " AND tasks_fts.rowid = tasks.rowid)",
+    ],
+    create_migrations_table: r#"
+        CREATE TABLE IF NOT EXISTS schema_migrations (
+            version INTEGER PRIMARY KEY,
+            description TEXT NOT NULL,
+            applied_at TEXT
+        )
+        "#,
+    migrations_table_exists: r#"
+        SELECT COUNT(*) AS count
+        FROM sqlite_master
+        WHERE type = 'table' AND name = 'schema_migrations'
+        "#,
+    migrations: MIGRATIONS,
+};
+
+/// Schema migrations in the order they must be applied.
+///
+/// Timestamps are stored as RFC 3339 text, which sorts chronologically.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "create users, sessions and tasks tables",
+        statements: &[
+            r#"
+            CREATE TABLE IF NOT EXISTS users (
+                id TEXT PRIMARY KEY,
+                email TEXT NOT NULL UNIQUE,
+                password_hash TEXT NOT NULL,
+                created_at TEXT,
+                updated_at TEXT
+            )
+            "#,
+            r#"
+            CREATE TABLE IF NOT EXISTS sessions (
+                id TEXT PRIMARY KEY,
+                user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+                token_hash TEXT NOT NULL,
+                expires_at TEXT NOT NULL,
+                created_at TEXT
+            )
+            "#,
+            "CREATE INDEX IF NOT EXISTS idx_sessions_token_hash ON sessions (token_hash)",
+            "CREATE INDEX IF NOT EXISTS idx_sessions_user_id ON sessions (user_id)",
+            "CREATE INDEX IF NOT EXISTS idx_sessions_expires_at ON sessions (expires_at)",
+            r#"
+            CREATE TABLE IF NOT EXISTS tasks (
+                id TEXT PRIMARY KEY,
+                -- Match MySQL's case-insensitive ordering of titles
+                title TEXT NOT NULL COLLATE NOCASE,
+                completed BOOLEAN NOT NULL DEFAULT FALSE,
+                user_id TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+                created_at TEXT,
+                updated_at TEXT
+            )
+            "#,
+            "CREATE INDEX IF NOT EXISTS idx_tasks_user_id ON tasks (user_id)",
+            "CREATE INDEX IF NOT EXISTS idx_tasks_created_at ON tasks (created_at)",
+            "CREATE INDEX IF NOT EXISTS idx_tasks_completed ON tasks (completed)",
+        ],
+        backfill: &[],
+    },
+    Migration {
+        version: 2,
+        description: "add owner for tasks created without a user",
+        statements: &[r#"
+            INSERT OR IGNORE INTO users (id, email, password_hash)
+            VALUES ('00000000-0000-0000-0000-000000000000', 'anonymous@localhost', '!')
+            "#],
+        backfill: &[],
+    },
+    // SQLite cannot change a column's type, so each table is rebuilt with BLOB
+    // keys and its rows copied across. Foreign keys are off while the tables are
+    // swapped so dropping `users` does not cascade.
+    Migration {
+        version: 3,
+        description: "store keys as 16-byte blobs",
+        statements: &[
+            "PRAGMA foreign_keys = OFF",
+            r#"
+            CREATE TABLE users_new (
+                id BLOB PRIMARY KEY,
+                email TEXT NOT NULL UNIQUE,
+                password_hash TEXT NOT NULL,
+                created_at TEXT,
+                updated_at TEXT
+            )
+            "#,
+            r#"
+            INSERT INTO users_new (id, email, password_hash, created_at, updated_at)
+            SELECT unhex(replace(id, '-', '')), email, password_hash, created_at, updated_at
+            FROM users
+            "#,
+            "DROP TABLE users",
+            "ALTER TABLE users_new RENAME TO users",
+            r#"
+            CREATE TABLE sessions_new (
+                id BLOB PRIMARY KEY,
+                user_id BLOB NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+                token_hash TEXT NOT NULL,
+                expires_at TEXT NOT NULL,
+                created_at TEXT
+            )
+            "#,
+            r#"
+            INSERT INTO sessions_new (id, user_id, token_hash, expires_at, created_at)
+            SELECT unhex(replace(id, '-', '')), unhex(replace(user_id, '-', '')),
+                token_hash, expires_at, created_at
+            FROM sessions
+            "#,
+            "DROP TABLE sessions",
+            "ALTER TABLE sessions_new RENAME TO sessions",
+            "CREATE INDEX idx_sessions_token_hash ON sessions (token_hash)",
+            "CREATE INDEX idx_sessions_user_id ON sessions (user_id)",
+            "CREATE INDEX idx_sessions_expires_at ON sessions (expires_at)",
+            r#"
+            CREATE TABLE tasks_new (
+                id BLOB PRIMARY KEY,
+                title TEXT NOT NULL COLLATE NOCASE,
+                completed BOOLEAN NOT NULL DEFAULT FALSE,
+                user_id BLOB NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+                created_at TEXT,
+                updated_at TEXT
+            )
+            "#,
+            r#"
+            INSERT INTO tasks_new (id, title, completed, user_id, created_at, updated_at)
+            SELECT unhex(replace(id, '-', '')), title, completed, unhex(replace(user_id, '-', '')),
+                created_at, updated_at
+            FROM tasks
+            "#,
+            "DROP TABLE tasks",
+            "ALTER TABLE tasks_new RENAME TO tasks",
+            "CREATE INDEX idx_tasks_user_id ON tasks (user_id)",
+            "CREATE INDEX idx_tasks_created_at ON tasks (created_at)",
+            "CREATE INDEX idx_tasks_completed ON tasks (completed)",
+            "PRAGMA foreign_keys = ON",
+        ],
+        backfill: &[],
+    },
+    // An external-content FTS5 table indexes the titles without storing them
+    // twice; triggers keep it in step with `tasks`
+    Migration {
+        version: 4,
+        description: "add full-text index on task titles",
+        statements: &[
+            "CREATE VIRTUAL TABLE tasks_fts USING fts5(title, content = 'tasks', content_rowid = 'rowid')",
+            r#"
+            CREATE TRIGGER tasks_fts_insert AFTER INSERT ON tasks BEGIN
+                INSERT INTO tasks_fts (rowid, title) VALUES (new.rowid, new.title);
+            END
+            "#,
+            r#"
+            CREATE TRIGGER tasks_fts_delete AFTER DELETE ON tasks BEGIN
+                INSERT INTO tasks_fts (tasks_fts, rowid, title) VALUES ('delete', old.rowid, old.title);
+            END
+            "#,
+            r#"
+            CREATE TRIGGER tasks_fts_update AFTER UPDATE OF title ON tasks BEGIN
+                INSERT INTO tasks_fts (tasks_fts, rowid, title) VALUES ('delete', old.rowid, old.title);
+                INSERT INTO tasks_fts (rowid, title) VALUES (new.rowid, new.title);
+            END
+            "#,
+            "INSERT INTO tasks_fts (tasks_fts) VALUES ('rebuild')",
+        ],
+        backfill: &[],
+    },
+    // FTS5 tables cannot gain columns, so the index is rebuilt over both
+    Migration {
+        version: 5,
+        description: "add task descriptions to tasks and the full-text index",
+        statements: &[
+            "ALTER TABLE tasks ADD COLUMN description TEXT",
+            "DROP TRIGGER tasks_fts_insert",
+            "DROP TRIGGER tasks_fts_delete",
+            "DROP TRIGGER tasks_fts_update",
+            "DROP TABLE tasks_fts",
+            r#"
+            CREATE VIRTUAL TABLE tasks_fts
+            USING fts5(title, description, content = 'tasks', content_rowid = 'rowid')
+            "#,
+            r#"
+            CREATE TRIGGER tasks_fts_insert AFTER INSERT ON tasks BEGIN
+                INSERT INTO tasks_fts (rowid, title, description)
+                VALUES (new.rowid, new.title, new.description);
+            END
+            "#,
+            r#"
+            CREATE TRIGGER tasks_fts_delete AFTER DELETE ON tasks BEGIN
+                INSERT INTO tasks_fts (tasks_fts, rowid, title, description)
+                VALUES ('delete', old.rowid, old.title, old.description);
+            END
+            "#,
+            r#"
+            CREATE TRIGGER tasks_fts_update AFTER UPDATE OF title, description ON tasks BEGIN
+                INSERT INTO tasks_fts (tasks_fts, rowid, title, description)
+                VALUES ('delete', old.rowid, old.title, old.description);
+                INSERT INTO tasks_fts (rowid, title, description)
+                VALUES (new.rowid, new.title, new.description);
+            END
+            "#,
+            "INSERT INTO tasks_fts (tasks_fts) VALUES ('rebuild')",
+        ],
+        backfill: &[],
+    },
+    Migration {
+        version: 6,
+        description: "add due and start dates to tasks and time zones to users",
+        statements: &[
+            "ALTER TABLE tasks ADD COLUMN due_at TEXT",
+            "ALTER TABLE tasks ADD COLUMN due_all_day BOOLEAN NOT NULL DEFAULT FALSE",
+            "ALTER TABLE tasks ADD COLUMN start_at TEXT",
+            "CREATE INDEX IF NOT EXISTS idx_tasks_due_at ON tasks (due_at)",
+            "ALTER TABLE users ADD COLUMN time_zone TEXT NOT NULL DEFAULT 'UTC'",
+        ],
+        backfill: &[],
+    },
+    Migration {
+        version: 7,
+        description: "add priorities to tasks",
+        statements: &[
+            "ALTER TABLE tasks ADD COLUMN priority SMALLINT NOT NULL DEFAULT 0",
+            "CREATE INDEX IF NOT EXISTS idx_tasks_priority ON tasks (priority)",
+        ],
+        backfill: &[],
+    },
+    Migration {
+        version: 8,
+        description: "create tags and task_tags tables",
+        statements: &[
+            r#"
+            CREATE TABLE IF NOT EXISTS tags (
+                id BLOB PRIMARY KEY,
+                user_id BLOB NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+                name TEXT NOT NULL,
+                color TEXT,
+                created_at TEXT,
+                updated_at TEXT,
+                UNIQUE (user_id, name)
+            )
+            "#,
+            r#"
+            CREATE TABLE IF NOT EXISTS task_tags (
+                task_id BLOB NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+                tag_id BLOB NOT NULL REFERENCES tags(id) ON DELETE CASCADE,
+                PRIMARY KEY (task_id, tag_id)
+            )
+            "#,
+            "CREATE INDEX IF NOT EXISTS idx_task_tags_tag_id ON task_tags (tag_id)",
+        ],
+        backfill: &[],
+    },
+    Migration {
+        version: 9,
+        description: "create projects table and add projects to tasks",
+        statements: &[
+            r#"
+            CREATE TABLE IF NOT EXISTS projects (
+                id BLOB PRIMARY KEY,
+                user_id BLOB NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+                name TEXT NOT NULL,
+                color TEXT,
+                archived BOOLEAN NOT NULL DEFAULT FALSE,
+                position INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT,
+                updated_at TEXT,
+                UNIQUE (user_id, name)
+            )
+            "#,
+            "CREATE INDEX IF NOT EXISTS idx_projects_user_position ON projects (user_id, position)",
+            "ALTER TABLE tasks ADD COLUMN project_id BLOB REFERENCES projects(id) ON DELETE SET NULL",
+            "CREATE INDEX IF NOT EXISTS idx_tasks_project_id ON tasks (project_id)",
+        ],
+        backfill: &[],
+    },
+    Migration {
+        version: 10,
+        description: "add subtasks to tasks",
+        statements: &[
+            "ALTER TABLE tasks ADD COLUMN parent_id BLOB REFERENCES tasks(id) ON DELETE CASCADE",
+            "ALTER TABLE tasks ADD COLUMN auto_complete BOOLEAN NOT NULL DEFAULT FALSE",
+            "CREATE INDEX IF NOT EXISTS idx_tasks_parent_id ON tasks (parent_id)",
+        ],
+        backfill: &[],
+    },
+    Migration {
+        version: 11,
+        description: "create task_dependencies table",
+        statements: &[
+            r#"
+            CREATE TABLE IF NOT EXISTS task_dependencies (
+                task_id BLOB NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+                blocker_id BLOB NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+                PRIMARY KEY (task_id, blocker_id)
+            )
+            "#,
+            "CREATE INDEX IF NOT EXISTS idx_task_dependencies_blocker_id ON task_dependencies (blocker_id)",
+        ],
+        backfill: &[],
+    },
+    Migration {
+        version: 12,
+        description: "add recurrence rules to tasks",
+        statements: &["ALTER TABLE tasks ADD COLUMN recurrence TEXT"],
+        backfill: &[],
+    },
+    Migration {
+        version: 13,
+        description: "create reminders, reminder_deliveries and notifications tables",
+        statements: &[
+            r#"
+            CREATE TABLE IF NOT EXISTS reminders (
+                id BLOB PRIMARY KEY,
+                task_id BLOB NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+                user_id BLOB NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+                channel SMALLINT NOT NULL DEFAULT 0,
+                target TEXT,
+                remind_at TEXT,
+                minutes_before INTEGER,
+                fire_at TEXT,
+                delivered_at TEXT,
+                attempts INTEGER NOT NULL DEFAULT 0,
+                last_error TEXT,
+                created_at TEXT,
+                updated_at TEXT
+            )
+            "#,
+            "CREATE INDEX IF NOT EXISTS idx_reminders_task_id ON reminders (task_id)",
+            "CREATE INDEX IF NOT EXISTS idx_reminders_fire_at ON reminders (fire_at)",
+            r#"
+            CREATE TABLE IF NOT EXISTS reminder_deliveries (
+                reminder_id BLOB NOT NULL REFERENCES reminders(id) ON DELETE CASCADE,
+                fire_at TEXT NOT NULL,
+                claimed_at TEXT NOT NULL,
+                PRIMARY KEY (reminder_id, fire_at)
+            )
+            "#,
+            r#"
+            CREATE TABLE IF NOT EXISTS notifications (
+                id BLOB PRIMARY KEY,
+                user_id BLOB NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+                task_id BLOB REFERENCES tasks(id) ON DELETE CASCADE,
+                message TEXT NOT NULL,
+                created_at TEXT,
+                read_at TEXT
+            )
+            "#,
+            "CREATE INDEX IF NOT EXISTS idx_notifications_user_id ON notifications (user_id)",
+        ],
+        backfill: &[],
+    },
+    // Existing tasks are ranked in creation order
+    Migration {
+        version: 14,
+        description: "add manual positions to tasks",
+        statements: &[
+            "ALTER TABLE tasks ADD COLUMN position TEXT NOT NULL DEFAULT ''",
+            "CREATE INDEX IF NOT EXISTS idx_tasks_user_id_position ON tasks (user_id, position)",
+        ],
+        backfill: &[
+            r#"
+            UPDATE tasks SET position = ranked.position
+            FROM (
+                SELECT id, printf('%08xi', row_number() OVER (PARTITION BY user_id ORDER BY created_at, id)) AS position
+                FROM tasks
+            ) AS ranked
+            WHERE tasks.id = ranked.id AND tasks.position = ''
+            "#,
+        ],
+    },
+    // Tasks left sharing a position by concurrent moves are put one after the
+    // other first
+    Migration {
+        version: 15,
+        description: "make task positions unique per user",
+        statements: &[
+            r#"
+            UPDATE tasks SET position = tasks.position || printf('%08xi', ranked.duplicate)
+            FROM (
+                SELECT id, row_number() OVER (PARTITION BY user_id, position ORDER BY created_at, id) AS duplicate
+                FROM tasks
+            ) AS ranked
+            WHERE tasks.id = ranked.id AND ranked.duplicate > 1
+            "#,
+            "DROP INDEX IF EXISTS idx_tasks_user_id_position",
+            "CREATE UNIQUE INDEX IF NOT EXISTS uq_tasks_user_position ON tasks (user_id, position)",
+        ],
+        backfill: &[],
+    },
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Notification, Priority, ReminderChannel, SortOrder, SubtaskProgress, Task};
+    use crate::query::{Condition, TaskQuery};
+    use crate::schedule::{Calendar, Due};
+    use crate::repository::{
+        DependencyRepository, Keyset, NewProject, NewReminder, NewTag, NewTask, NotificationRepository, ProjectChanges,
+        ProjectRepository, ReminderRepository, Repository, SearchMode, SessionRepository, TaskChanges, TaskFilter,
+        TagRepository, TaskRepository, TaskSort, TaskSortField, UserRepository,
+        ANONYMOUS_USER_ID,
+    };
+    use chrono::{Duration as ChronoDuration, Utc};
+    use uuid::Uuid;
+
+    /// A migrated repository in a temporary file, with several connections
+    /// so that concurrent calls really overlap
+    async fn file_repository() -> (SqliteRepository, std::path::PathBuf) {
+        let path = std::env::temp_dir().join(format!("tasks-{}.db", Uuid::now_v7()));
+        let repository = SqliteRepository::connect(&DatabaseConfig {
+            url: format!("sqlite:{}", path.display()),
+            max_connections: 4,
+            acquire_timeout_secs: 3,
+        })
+        .await
+        .unwrap();
+        repository.migrate().await.unwrap();
+        (repository, path)
+    }
+
+    async fn remove_file_repository(repository: SqliteRepository, path: &std::path::Path) {
+        repository.pool().close().await;
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{}", path.display(), suffix));
+        }
+    }
+
+    fn complete() -> TaskChanges<'static> {
+        TaskChanges {
+            completed: Some(true),
+            ..Default::default()
+        }
+    }
+
+    fn retitle(title: &str) -> TaskChanges<'_> {
+        TaskChanges {
+            title: Some(title),
+            ..Default::default()
+        }
+    }
+
+    async fn repository() -> SqliteRepository {
+        let repository = SqliteRepository::connect(&DatabaseConfig {
+            url: "sqlite::memory:".to_string(),
+            max_connections: 1,
+            acquire_timeout_secs: 3,
+        })
+        .await
+        .unwrap();
+        repository.migrate().await.unwrap();
+        repository
+    }
+
+    #[tokio::test]
+    async fn test_migrations_are_applied_once() {
+        let repository = repository().await;
+
+        assert!(repository.pending_migrations().await.unwrap().is_empty());
+        // Running again is a no-op
+        repository.migrate().await.unwrap();
+        repository.ping().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_text_keys_are_converted_to_blobs() {
+        let repository = SqliteRepository::connect(&DatabaseConfig {
+            url: "sqlite::memory:".to_string(),
+            max_connections: 1,
+            acquire_timeout_secs: 3,
+        })
+        .await
+        .unwrap();
+        repository.migrate_to(Some(2)).await.unwrap();
+
+        let id = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO tasks (id, title, completed, user_id) VALUES (?, 'Old task', TRUE, ?)",
+        )
+        .bind(id.to_string())
+        .bind(ANONYMOUS_USER_ID.to_string())
+        .execute(repository.pool())
+        .await
+        .unwrap();
+
+        repository.migrate().await.unwrap();
+
+        let task = repository.get_task(id).await.unwrap();
+        assert_eq!(task.title, "Old task");
+        assert_eq!(task.user_id, ANONYMOUS_USER_ID);
+        assert!(task.completed);
+    }
+
+    #[tokio::test]
+    async fn test_task_lifecycle() {
+        let repository = repository().await;
+        let id = Uuid::now_v7();
+
+        let task = repository
+            .create_task(&NewTask::new(id, "Buy milk", ANONYMOUS_USER_ID))
+            .await
+            .unwrap();
+        assert_eq!(task.id, id);
+        assert!(!task.completed);
+        assert!(task.created_at.is_some());
+
+        let updated = repository.update_task(id, &complete()).await.unwrap();
+        assert_eq!(updated.title, "Buy milk");
+        assert!(updated.completed);
+        assert!(updated.updated_at > task.updated_at);
+
+        assert!(repository.delete_task(id).await.unwrap());
+        assert!(!repository.delete_task(id).await.unwrap());
+        assert!(matches!(
+            repository.get_task(id).await,
+            Err(sqlx::Error::RowNotFound)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_writes_return_the_stored_rows() {
+        let repository = repository().await;
+        let id = Uuid::now_v7();
+
+        let created = repository
+            .create_task(&NewTask::new(id, "Buy milk", ANONYMOUS_USER_ID))
+            .await
+            .unwrap();
+        let stored = repository.get_task(id).await.unwrap();
+        assert_eq!(created.created_at, stored.created_at);
+        assert_eq!(created.updated_at, stored.updated_at);
+
+        let updated = repository
+            .update_task(id, &retitle("Buy oat milk"))
+            .await
+            .unwrap();
+        let stored = repository.get_task(id).await.unwrap();
+        assert_eq!(updated.title, stored.title);
+        assert_eq!(updated.updated_at, stored.updated_at);
+
+        repository.delete_task(id).await.unwrap();
+        assert!(matches!(
+            repository.update_task(id, &complete()).await,
+            Err(sqlx::Error::RowNotFound)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_descriptions_are_stored_and_searched() {
+        let repository = repository().await;
+        let id = Uuid::now_v7();
+        repository
+            .create_task(&NewTask {
+                description: Some("Ask about *oat* milk"),
+                ..NewTask::new(id, "Corner shop", ANONYMOUS_USER_ID)
+            })
+            .await
+            .unwrap();
+        repository
+            .create_task(&NewTask::new(Uuid::now_v7(), "Walk dog", ANONYMOUS_USER_ID))
+            .await
+            .unwrap();
+
+        let substring = TaskFilter::from_params(Some("OAT"), None);
+        let fulltext = TaskFilter::from_params(Some("+oat"), None).with_search_mode(SearchMode::FullText);
+        assert_eq!(repository.count_tasks(&substring).await.unwrap(), 1);
+        assert_eq!(repository.count_tasks(&fulltext).await.unwrap(), 1);
+
+        // Clearing the description takes it out of the full-text index too
+        let cleared = repository
+            .update_task(
+                id,
+                &TaskChanges {
+                    description: Some(None),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(cleared.description, None);
+        assert_eq!(repository.get_task(id).await.unwrap().description, None);
+        assert_eq!(repository.count_tasks(&fulltext).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_corrupted_rows_are_decode_errors() {
+        let repository = repository().await;
+        sqlx::query(
+            "INSERT INTO tasks (id, title, completed, user_id) VALUES (X'0102', 'Broken', FALSE, ?)",
+        )
+        .bind(ANONYMOUS_USER_ID)
+        .execute(repository.pool())
+        .await
+        .unwrap();
+
+        assert!(matches!(
+            repository.list_all_tasks().await,
+            Err(sqlx::Error::ColumnDecode { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_filtering_and_sorting() {
+        let repository = repository().await;
+        for title in ["Charlie", "alpha", "Bravo milk"] {
+            repository
+                .create_task(&NewTask::new(Uuid::new_v4(), title, ANONYMOUS_USER_ID))
+                .await
+                .unwrap();
+        }
+        let bravo = repository.list_all_tasks().await.unwrap()[0].id;
+        repository.update_task(bravo, &complete()).await.unwrap();
+
+        let by_title = TaskSort::by(TaskSortField::Title, SortOrder::Asc);
+        let titles: Vec<String> = repository
+            .list_tasks(&TaskFilter::default(), &by_title, 0, 10)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|task| task.title)
+            .collect();
+        assert_eq!(titles, ["alpha", "Bravo milk", "Charlie"]);
+
+        let search = TaskFilter::from_params(Some("MILK"), None);
+        assert_eq!(repository.count_tasks(&search).await.unwrap(), 1);
+
+        let pending = TaskFilter::from_params(None, Some("pending"));
+        assert_eq!(repository.count_tasks(&pending).await.unwrap(), 2);
+
+        let page = repository
+            .list_tasks(&TaskFilter::default(), &TaskSort::default(), 2, 2)
+            .await
+            .unwrap();
+        assert_eq!(page.len(), 1);
+        assert_eq!(page[0].title, "Charlie");
+    }
+
+    #[tokio::test]
+    async fn test_fulltext_search_ranks_matches() {
+        let repository = repository().await;
+        for title in ["Buy milk", "Buy oat milk", "Milk the cow, buy a bucket", "Walk dog"] {
+            repository
+                .create_task(&NewTask::new(Uuid::now_v7(), title, ANONYMOUS_USER_ID))
+                .await
+                .unwrap();
+        }
+        // The index follows updates
+        let dog = repository.list_all_tasks().await.unwrap()[0].id;
+        repository
+            .update_task(dog, &retitle("Walk dog to buy milk"))
+            .await
+            .unwrap();
+
+        let search = |query: &str| {
+            TaskFilter::from_params(Some(query), None).with_search_mode(SearchMode::FullText)
+        };
+        let by_relevance = TaskSort::by(TaskSortField::Relevance, SortOrder::Desc);
+
+        assert_eq!(repository.count_tasks(&search("milk")).await.unwrap(), 4);
+        assert_eq!(repository.count_tasks(&search("+milk -oat")).await.unwrap(), 3);
+        assert_eq!(repository.count_tasks(&search("\"buy milk\"")).await.unwrap(), 2);
+        assert_eq!(repository.count_tasks(&search("bu*")).await.unwrap(), 4);
+
+        let tasks = repository
+            .list_tasks(&search("+milk bucket"), &by_relevance, 0, 10)
+            .await
+            .unwrap();
+        assert_eq!(tasks.len(), 4);
+        assert_eq!(tasks[0].title, "Milk the cow, buy a bucket");
+        assert!(tasks.iter().all(|task| task.relevance.is_some()));
+    }
+
+    #[tokio::test]
+    async fn test_filter_query_binds_typed_values() {
+        let repository = repository().await;
+        for title in ["Release notes", "Release party", "Walk dog"] {
+            repository
+                .create_task(&NewTask::new(Uuid::now_v7(), title, ANONYMOUS_USER_ID))
+                .await
+                .unwrap();
+        }
+        let party = repository.list_all_tasks().await.unwrap()[1].id;
+        repository.update_task(party, &complete()).await.unwrap();
+
+        let count = |filter: &str| {
+            let filter = TaskFilter::default().with_query(TaskQuery::parse(filter, &Calendar::for_time_zone("UTC")).unwrap());
+            let repository = &repository;
+            async move { repository.count_tasks(&filter).await.unwrap() }
+        };
+        let today = Utc::now().date_naive();
+
+        assert_eq!(count("release completed:false").await, 1);
+        assert_eq!(count("-title:release").await, 1);
+        assert_eq!(count(&format!("created:{today}")).await, 3);
+        assert_eq!(count(&format!("created:>{today}")).await, 0);
+        assert_eq!(count(&format!("created:<={today} -completed:true")).await, 2);
+        assert_eq!(count(r#"title:"'; DROP TABLE tasks; --""#).await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_keyset_pages_break_ties_by_id() {
+        let repository = repository().await;
+        for title in ["b", "A", "a", "B", "a"] {
+            repository
+                .create_task(&NewTask::new(Uuid::now_v7(), title, ANONYMOUS_USER_ID))
+                .await
+                .unwrap();
+        }
+
+        let tasks = repository.list_all_tasks().await.unwrap();
+        for task in &tasks[..2] {
+            repository.update_task(task.id, &complete()).await.unwrap();
+        }
+
+        // Single-direction orderings compare row values; mixed ones expand them
+        for sort in ["title", "-completed,title", "completed,-title"] {
+            let sort = TaskSort::parse(sort).unwrap();
+            let all = repository
+                .list_tasks(&TaskFilter::default(), &sort, 0, 10)
+                .await
+                .unwrap();
+
+            let mut paged = Vec::new();
+            let mut after = None;
+            loop {
+                let page = repository
+                    .list_tasks_after(&TaskFilter::default(), &sort, after.as_ref(), 2)
+                    .await
+                    .unwrap();
+                let Some(last) = page.last() else { break };
+                after = Some(Keyset::of(last, &sort));
+                paged.extend(page);
+            }
+
+            let ids = |tasks: &[crate::models::Task]| tasks.iter().map(|task| task.id).collect::<Vec<_>>();
+            assert_eq!(ids(&paged), ids(&all), "{}", sort.to_param());
+            assert_eq!(all.len(), 5);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tasks_without_due_dates_sort_last() {
+        let repository = repository().await;
+        let now = Utc::now();
+        let due = |hours| {
+            Some(Due {
+                at: now + ChronoDuration::hours(hours),
+                all_day: false,
+            })
+        };
+        for (title, due) in [("a", None), ("b", due(-2)), ("c", due(5)), ("d", None), ("e", due(-2))] {
+            repository
+                .create_task(&NewTask {
+                    due,
+                    ..NewTask::new(Uuid::now_v7(), title, ANONYMOUS_USER_ID)
+                })
+                .await
+                .unwrap();
+        }
+
+        for (sort, expected) in [
+            ("due_at,title", "becad"),
+            ("-due_at,title", "cbead"),
+            ("due_at,-title", "ebcda"),
+        ] {
+            let sort = TaskSort::parse(sort).unwrap();
+            let all = repository
+                .list_tasks(&TaskFilter::default(), &sort, 0, 10)
+                .await
+                .unwrap();
+            let titles: String = all.iter().map(|task| task.title.as_str()).collect();
+            assert_eq!(titles, expected, "{}", sort.to_param());
+
+            // Keyset pages step over the missing values too
+            let mut paged = Vec::new();
+            let mut after = None;
+            loop {
+                let page = repository
+                    .list_tasks_after(&TaskFilter::default(), &sort, after.as_ref(), 2)
+                    .await
+                    .unwrap();
+                let Some(last) = page.last() else { break };
+                after = Some(Keyset::of(last, &sort));
+                paged.extend(page);
+            }
+            let ids = |tasks: &[crate::models::Task]| tasks.iter().map(|task| task.id).collect::<Vec<_>>();
+            assert_eq!(ids(&paged), ids(&all), "{}", sort.to_param());
+
+            // And so do pages read backwards from the end
+            let reversed = sort.reversed();
+            let mut paged = Vec::new();
+            let mut before = None;
+            loop {
+                let page = repository
+                    .list_tasks_after(&TaskFilter::default(), &reversed, before.as_ref(), 2)
+                    .await
+                    .unwrap();
+                let Some(first) = page.last() else { break };
+                before = Some(Keyset::of(first, &reversed));
+                paged.extend(page);
+            }
+            paged.reverse();
+            assert_eq!(ids(&paged), ids(&all), "{}", sort.to_param());
+        }
+
+        let count = |filter: &str| {
+            let filter = TaskFilter::default()
+                .with_query(TaskQuery::parse(filter, &Calendar::for_time_zone("UTC")).unwrap());
+            let repository = &repository;
+            async move { repository.count_tasks(&filter).await.unwrap() }
+        };
+        assert_eq!(count("due:overdue").await, 2);
+        assert_eq!(count("-due:overdue").await, 3);
+        assert_eq!(count("due:none").await, 2);
+        assert_eq!(count("-due:none").await, 3);
+    }
+
+    #[tokio::test]
+    async fn test_priorities_are_stored_filtered_and_sorted() {
+        let repository = repository().await;
+        for (title, priority) in [
+            ("a", Priority::High),
+            ("b", Priority::None),
+            ("c", Priority::Urgent),
+            ("d", Priority::High),
+        ] {
+            repository
+                .create_task(&NewTask {
+                    priority,
+                    ..NewTask::new(Uuid::now_v7(), title, ANONYMOUS_USER_ID)
+                })
+                .await
+                .unwrap();
+        }
+        let b = repository.list_all_tasks().await.unwrap()[2].id;
+        let changes = TaskChanges {
+            priority: Some(Priority::Low),
+            ..Default::default()
+        };
+        assert_eq!(repository.update_task(b, &changes).await.unwrap().priority, Priority::Low);
+        assert_eq!(repository.get_task(b).await.unwrap().priority, Priority::Low);
+
+        let sort = TaskSort::parse("-priority,title").unwrap();
+        let mut titles = String::new();
+        let mut after = None;
+        loop {
+            let page = repository
+                .list_tasks_after(&TaskFilter::default(), &sort, after.as_ref(), 3)
+                .await
+                .unwrap();
+            let Some(last) = page.last() else { break };
+            after = Some(Keyset::of(last, &sort));
+            titles.extend(page.iter().map(|task| task.title.as_str()));
+        }
+        assert_eq!(titles, "cadb");
+
+        let calendar = Calendar::for_time_zone("UTC");
+        let filter = TaskFilter::default()
+            .with_query(TaskQuery::parse("priority:>=high -priority:urgent", &calendar).unwrap());
+        assert_eq!(repository.count_tasks(&filter).await.unwrap(), 2);
+        let filter = TaskFilter::default().with_query(
+            TaskQuery::default().and(Condition::priority_in(&[Priority::Low, Priority::Urgent])),
+        );
+        assert_eq!(repository.count_tasks(&filter).await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_tags_link_tasks_and_count_usage() {
+        let repository = repository().await;
+        let tag = |name| NewTag {
+            id: Uuid::now_v7(),
+            user_id: ANONYMOUS_USER_ID,
+            name,
+            color: None,
+        };
+        let work = repository.create_tag(&tag("work")).await.unwrap();
+        let home = repository.create_tag(&tag("home")).await.unwrap();
+        assert!(matches!(
+            repository.create_tag(&tag("work")).await,
+            Err(sqlx::Error::Database(error)) if error.is_unique_violation()
+        ));
+
+        let mut tasks = Vec::new();
+        for title in ["Report", "Laundry", "Plan week"] {
+            let id = Uuid::now_v7();
+            repository
+                .create_task(&NewTask::new(id, title, ANONYMOUS_USER_ID))
+                .await
+                .unwrap();
+            tasks.push(id);
+        }
+        repository.add_task_tags(tasks[0], &[work.id]).await.unwrap();
+        repository.add_task_tags(tasks[1], &[home.id]).await.unwrap();
+        // Tags already on a task are skipped
+        repository.add_task_tags(tasks[2], &[work.id, home.id]).await.unwrap();
+        repository.add_task_tags(tasks[2], &[work.id]).await.unwrap();
+
+        let names = repository.task_tag_names(&tasks).await.unwrap();
+        assert_eq!(names[&tasks[2]], ["home", "work"]);
+        let counts: Vec<(String, i64)> = repository
+            .list_tags(ANONYMOUS_USER_ID)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|tag| (tag.name, tag.task_count))
+            .collect();
+        assert_eq!(counts, [("home".to_string(), 2), ("work".to_string(), 2)]);
+
+        let count = |condition| {
+            let filter = TaskFilter::default().with_query(TaskQuery::default().and(condition));
+            let repository = &repository;
+            async move { repository.count_tasks(&filter).await.unwrap() }
+        };
+        let tagged = |name: &str| Condition::Tagged(name.to_string());
+        assert_eq!(count(tagged("work")).await, 2);
+        assert_eq!(count(Condition::All(vec![tagged("work"), tagged("home")])).await, 1);
+        assert_eq!(count(Condition::Not(Box::new(tagged("home")))).await, 1);
+
+        repository.remove_task_tags(tasks[2], &[work.id]).await.unwrap();
+        assert_eq!(repository.get_tag(work.id).await.unwrap().task_count, 1);
+
+        // Deleting a tag or a task drops its links
+        assert!(repository.delete_tag(home.id).await.unwrap());
+        repository.delete_task(tasks[0]).await.unwrap();
+        assert!(repository.task_tag_names(&tasks).await.unwrap().is_empty());
+        assert_eq!(repository.get_tag(work.id).await.unwrap().task_count, 0);
+    }
+
+    #[tokio::test]
+    async fn test_projects_group_tasks_and_release_them_when_deleted() {
+        let repository = repository().await;
+        let project = |name, position| NewProject {
+            id: Uuid::now_v7(),
+            user_id: ANONYMOUS_USER_ID,
+            name,
+            color: None,
+            position,
+        };
+        let work = repository.create_project(&project("Work", 1)).await.unwrap();
+        let home = repository.create_project(&project("Home", 0)).await.unwrap();
+        assert!(matches!(
+            repository.create_project(&project("Work", 2)).await,
+            Err(sqlx::Error::Database(error)) if error.is_unique_violation()
+        ));
+        let names: Vec<String> = repository
+            .list_projects(ANONYMOUS_USER_ID)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|project| project.name)
+            .collect();
+        assert_eq!(names, ["Home", "Work"]);
+
+        let report = Uuid::now_v7();
+        repository
+            .create_task(&NewTask {
+                project_id: Some(work.id),
+                ..NewTask::new(report, "Report", ANONYMOUS_USER_ID)
+            })
+            .await
+            .unwrap();
+        let laundry = Uuid::now_v7();
+        repository
+            .create_task(&NewTask::new(laundry, "Laundry", ANONYMOUS_USER_ID))
+            .await
+            .unwrap();
+        let moved = repository
+            .update_task(
+                laundry,
+                &TaskChanges {
+                    project_id: Some(Some(home.id)),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(moved.project_id, Some(home.id));
+
+        let in_work = TaskFilter::default().in_project(work.id);
+        assert_eq!(repository.count_tasks(&in_work).await.unwrap(), 1);
+        let tasks = repository
+            .list_tasks(&in_work, &TaskSort::default(), 0, 10)
+            .await
+            .unwrap();
+        assert_eq!(tasks[0].id, report);
+        assert_eq!(tasks[0].project_id, Some(work.id));
+
+        let archived = repository
+            .update_project(
+                work.id,
+                &ProjectChanges {
+                    archived: Some(true),
+                    position: Some(5),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert!(archived.archived);
+        assert_eq!(archived.position, 5);
+        assert_eq!(archived.name, "Work");
+
+        // The tasks stay, outside any project
+        assert!(repository.delete_project(work.id).await.unwrap());
+        assert!(!repository.delete_project(work.id).await.unwrap());
+        assert_eq!(repository.get_task(report).await.unwrap().project_id, None);
+        assert!(matches!(
+            repository.get_project(work.id).await,
+            Err(sqlx::Error::RowNotFound)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_subtasks_are_counted_moved_and_deleted_with_their_parent() {
+        let repository = repository().await;
+        let create = |title, parent_id| {
+            let repository = &repository;
+            async move {
+                let id = Uuid::now_v7();
+                repository
+                    .create_task(&NewTask {
+                        parent_id,
+                        ..NewTask::new(id, title, ANONYMOUS_USER_ID)
+                    })
+                    .await
+                    .unwrap();
+                id
+            }
+        };
+        let release = create("Release", None).await;
+        let build = create("Build", Some(release)).await;
+        let test = create("Test", Some(release)).await;
+        let unit = create("Unit tests", Some(test)).await;
+        repository.update_task(build, &complete()).await.unwrap();
+
+        let progress = repository.subtask_progress(&[release, test, unit]).await.unwrap();
+        assert_eq!(progress[&release], SubtaskProgress::new(2, 1));
+        assert_eq!(progress[&release].percent, 50);
+        assert_eq!(progress[&test], SubtaskProgress::new(1, 0));
+        assert!(!progress.contains_key(&unit));
+
+        let mut children = repository.subtask_ids(&[release, test]).await.unwrap();
+        children.sort();
+        assert_eq!(children, [build, test, unit]);
+        let filter = TaskFilter::default().children_of(release);
+        assert_eq!(repository.count_tasks(&filter).await.unwrap(), 2);
+
+        assert_eq!(repository.reparent_subtasks(test, Some(release)).await.unwrap(), 1);
+        assert_eq!(repository.get_task(unit).await.unwrap().parent_id, Some(release));
+        assert_eq!(repository.reparent_subtasks(release, None).await.unwrap(), 3);
+        assert_eq!(repository.get_task(unit).await.unwrap().parent_id, None);
+
+        // Deleting a task takes its whole subtree along
+        repository
+            .update_task(
+                unit,
+                &TaskChanges {
+                    parent_id: Some(Some(test)),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert!(repository.delete_task(test).await.unwrap());
+        assert!(matches!(repository.get_task(unit).await, Err(sqlx::Error::RowNotFound)));
+        assert_eq!(repository.count_tasks(&TaskFilter::default()).await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_dependencies_block_tasks_until_their_blockers_are_completed() {
+        let repository = repository().await;
+        let create = |title| {
+            let repository = &repository;
+            async move {
+                let id = Uuid::now_v7();
+                repository.create_task(&NewTask::new(id, title, ANONYMOUS_USER_ID)).await.unwrap();
+                id
+            }
+        };
+        let design = create("Design").await;
+        let build = create("Build").await;
+        let ship = create("Ship").await;
+        repository.add_blocker(build, design).await.unwrap();
+        repository.add_blocker(ship, build).await.unwrap();
+        repository.add_blocker(ship, design).await.unwrap();
+        // Adding the same dependency twice is a no-op
+        assert!(repository.add_blocker(ship, design).await.unwrap());
+        // Nor can a task block itself or what blocks it
+        assert!(!repository.add_blocker(design, design).await.unwrap());
+        assert!(!repository.add_blocker(design, ship).await.unwrap());
+
+        let blockers: Vec<Uuid> = repository.list_blockers(ship).await.unwrap().iter().map(|task| task.id).collect();
+        assert_eq!(blockers, [design, build]);
+        let mut ids = repository.blocker_ids(&[ship, build]).await.unwrap();
+        ids.sort();
+        assert_eq!(ids, [design, build]);
+        assert_eq!(
+            repository.blocked_task_ids(&[design, build, ship]).await.unwrap(),
+            [build, ship].into()
+        );
+
+        let blocked = TaskFilter::default().with_query(TaskQuery::default().and(Condition::Blocked));
+        assert_eq!(repository.count_tasks(&blocked).await.unwrap(), 2);
+        repository.update_task(design, &complete()).await.unwrap();
+        assert_eq!(repository.blocked_task_ids(&[build, ship]).await.unwrap(), [ship].into());
+        assert_eq!(repository.count_tasks(&blocked).await.unwrap(), 1);
+
+        assert!(repository.remove_blocker(ship, build).await.unwrap());
+        assert!(!repository.remove_blocker(ship, build).await.unwrap());
+        assert_eq!(repository.count_tasks(&blocked).await.unwrap(), 0);
+
+        // Deleting a task drops the dependencies on both sides
+        repository.add_blocker(ship, build).await.unwrap();
+        assert!(repository.delete_task(build).await.unwrap());
+        assert_eq!(repository.blocker_ids(&[ship]).await.unwrap(), [design]);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_dependencies_cannot_close_a_cycle() {
+        let (repository, path) = file_repository().await;
+
+        for _ in 0..20 {
+            let first = Uuid::now_v7();
+            let second = Uuid::now_v7();
+            for id in [first, second] {
+                repository.create_task(&NewTask::new(id, "Task", ANONYMOUS_USER_ID)).await.unwrap();
+            }
+            let (forward, backward) = tokio::join!(
+                repository.add_blocker(first, second),
+                repository.add_blocker(second, first),
+            );
+            assert!(forward.unwrap() ^ backward.unwrap());
+        }
+
+        remove_file_repository(repository, &path).await;
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_appends_take_distinct_positions() {
+        let (repository, path) = file_repository().await;
+
+        let tasks: Vec<NewTask> = (0..8)
+            .map(|_| NewTask::new(Uuid::now_v7(), "Task", ANONYMOUS_USER_ID))
+            .collect();
+        let created = futures::future::join_all(tasks.iter().map(|task| repository.create_task(task))).await;
+        let created: Vec<Task> = created.into_iter().map(Result::unwrap).collect();
+        let mut positions: Vec<&str> = created.iter().map(|task| task.position.as_str()).collect();
+        positions.sort();
+        positions.dedup();
+        assert_eq!(positions.len(), tasks.len());
+
+        // A move to a taken position is refused rather than tying the two
+        let moved = TaskChanges {
+            position: Some(&created[0].position),
+            ..Default::default()
+        };
+        let error = repository.update_task(created[1].id, &moved).await.unwrap_err();
+        assert!(error.as_database_error().is_some_and(|error| error.is_unique_violation()));
+
+        remove_file_repository(repository, &path).await;
+    }
+
+    #[tokio::test]
+    async fn test_a_recurring_task_is_completed_and_repeated_once() {
+        let repository = repository().await;
+        let id = Uuid::now_v7();
+        let due = Due { at: Utc::now(), all_day: false };
+        let task = repository
+            .create_task(&NewTask {
+                due: Some(due),
+                recurrence: Some("FREQ=DAILY"),
+                ..NewTask::new(id, "Stretch", ANONYMOUS_USER_ID)
+            })
+            .await
+            .unwrap();
+
+        let changes = TaskChanges {
+            completed: Some(true),
+            recurrence: Some(None),
+            ..Default::default()
+        };
+        let position = crate::rank::after(Some(&task.position));
+        let next_id = Uuid::now_v7();
+        let next = NewTask {
+            due: Some(Due { at: due.at + ChronoDuration::days(1), ..due }),
+            recurrence: Some("FREQ=DAILY"),
+            position: Some(&position),
+            ..NewTask::new(next_id, "Stretch", ANONYMOUS_USER_ID)
+        };
+        let completed = repository
+            .complete_recurring_task(id, &changes, &next)
+            .await
+            .unwrap()
+            .expect("The task was pending");
+        assert!(completed.completed);
+        assert_eq!(completed.recurrence, None);
+        assert_eq!(repository.get_task(next_id).await.unwrap().position, position);
+
+        // A second completion, such as a concurrent request's, creates nothing
+        let again = NewTask { id: Uuid::now_v7(), ..next };
+        assert!(repository.complete_recurring_task(id, &changes, &again).await.unwrap().is_none());
+        assert!(repository
+            .complete_recurring_task(Uuid::now_v7(), &changes, &again)
+            .await
+            .unwrap()
+            .is_none());
+        assert_eq!(repository.count_tasks(&TaskFilter::default()).await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_recurrence_rules_are_stored_and_cleared() {
+        let repository = repository().await;
+        let id = Uuid::now_v7();
+        let task = repository
+            .create_task(&NewTask {
+                recurrence: Some("FREQ=WEEKLY;BYDAY=MO"),
+                ..NewTask::new(id, "Water plants", ANONYMOUS_USER_ID)
+            })
+            .await
+            .unwrap();
+        assert_eq!(task.recurrence.as_deref(), Some("FREQ=WEEKLY;BYDAY=MO"));
+        assert_eq!(repository.get_task(id).await.unwrap().recurrence, task.recurrence);
+
+        let changes = TaskChanges {
+            recurrence: Some(None),
+            ..Default::default()
+        };
+        assert_eq!(repository.update_task(id, &changes).await.unwrap().recurrence, None);
+    }
+
+    #[tokio::test]
+    async fn test_positions_are_backfilled_appended_and_respaced() {
+        let repository = SqliteRepository::connect(&DatabaseConfig {
+            url: "sqlite::memory:".to_string(),
+            max_connections: 1,
+            acquire_timeout_secs: 3,
+        })
+        .await
+        .unwrap();
+        repository.migrate_to(Some(13)).await.unwrap();
+
+        // Existing tasks are ranked by creation, whatever their ids
+        let now = Utc::now();
+        for (title, age) in [("b", 1), ("a", 2)] {
+            sqlx::query("INSERT INTO tasks (id, title, completed, user_id, created_at) VALUES (?, ?, FALSE, ?, ?)")
+                .bind(Uuid::now_v7())
+                .bind(title)
+                .bind(ANONYMOUS_USER_ID)
+                .bind(now - ChronoDuration::hours(age))
+                .execute(repository.pool())
+                .await
+                .unwrap();
+        }
+        repository.migrate().await.unwrap();
+
+        let sort = TaskSort::parse("position").unwrap();
+        let titles = |tasks: Vec<Task>| tasks.iter().map(|task| task.title.clone()).collect::<String>();
+        let list = || async {
+            repository
+                .list_tasks_after(&TaskFilter::default(), &sort, None, 10)
+                .await
+                .unwrap()
+        };
+        let tasks = list().await;
+        assert_eq!(titles(tasks.clone()), "ab");
+        assert_eq!(tasks[0].position, "00000001i");
+
+        // New tasks go last, and moving one rewrites only its position
+        let c = repository
+            .create_task(&NewTask::new(Uuid::now_v7(), "c", ANONYMOUS_USER_ID))
+            .await
+            .unwrap();
+        assert_eq!(c.position, "00000002j");
+        let a = &tasks[0];
+        let position = crate::rank::between(None, Some(&a.position)).unwrap();
+        let changes = TaskChanges {
+            position: Some(&position),
+            ..Default::default()
+        };
+        repository.update_task(c.id, &changes).await.unwrap();
+        assert_eq!(titles(list().await), "cab");
+        assert_eq!(titles(repository.adjacent_tasks(a, SortOrder::Asc, 5).await.unwrap()), "b");
+        assert_eq!(titles(repository.adjacent_tasks(a, SortOrder::Desc, 5).await.unwrap()), "c");
+
+        let before = list().await;
+        assert_eq!(repository.rebalance_positions(ANONYMOUS_USER_ID).await.unwrap(), 3);
+        let after = list().await;
+        assert_eq!(titles(after.clone()), "cab");
+        assert!(after.iter().all(|task| task.position.len() <= 3));
+        assert_eq!(
+            after.iter().map(|task| task.updated_at).collect::<Vec<_>>(),
+            before.iter().map(|task| task.updated_at).collect::<Vec<_>>()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_reminder_deliveries_are_claimed_once_per_fire_time() {
+        let repository = repository().await;
+        let task_id = Uuid::now_v7();
+        repository
+            .create_task(&NewTask::new(task_id, "Pay rent", ANONYMOUS_USER_ID))
+            .await
+            .unwrap();
+        let now = Utc::now();
+        let fire_at = now - ChronoDuration::minutes(1);
+        let reminder = repository
+            .create_reminder(&NewReminder {
+                id: Uuid::now_v7(),
+                task_id,
+                user_id: ANONYMOUS_USER_ID,
+                channel: ReminderChannel::Email,
+                target: Some("jane@example.com"),
+                remind_at: Some(fire_at),
+                minutes_before: None,
+                fire_at: Some(fire_at),
+            })
+            .await
+            .unwrap();
+        assert_eq!(reminder.channel, ReminderChannel::Email);
+        let due = || async { repository.due_reminders(now, 2, 10).await.unwrap() };
+        assert_eq!(due().await.len(), 1);
+
+        assert!(repository.claim_delivery(reminder.id, fire_at).await.unwrap());
+        assert!(!repository.claim_delivery(reminder.id, fire_at).await.unwrap());
+        repository.record_failure(reminder.id, fire_at, "timed out").await.unwrap();
+        let failed = repository.get_reminder(reminder.id).await.unwrap();
+        assert_eq!((failed.attempts, failed.last_error.as_deref()), (1, Some("timed out")));
+
+        // The failure released the claim for a retry
+        assert!(repository.claim_delivery(reminder.id, fire_at).await.unwrap());
+        repository.mark_delivered(reminder.id, now).await.unwrap();
+        assert!(due().await.is_empty());
+
+        // Rescheduling forgets the delivery and the failures
+        let snoozed = repository.schedule_reminder(reminder.id, Some(fire_at)).await.unwrap();
+        assert_eq!((snoozed.delivered_at, snoozed.attempts, snoozed.last_error), (None, 0, None));
+        assert!(repository.claim_delivery(reminder.id, fire_at).await.unwrap());
+        repository.record_failure(reminder.id, fire_at, "timed out").await.unwrap();
+        repository.record_failure(reminder.id, fire_at, "timed out").await.unwrap();
+        assert!(due().await.is_empty());
+        repository.schedule_reminder(reminder.id, Some(fire_at)).await.unwrap();
+        assert_eq!(due().await.len(), 1);
+
+        // Reminders on completed tasks are not sent
+        repository.update_task(task_id, &complete()).await.unwrap();
+        assert!(due().await.is_empty());
+        assert_eq!(repository.list_reminders(task_id).await.unwrap().len(), 1);
+        assert!(repository.delete_task(task_id).await.unwrap());
+        assert!(repository.list_reminders(task_id).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_notifications_are_listed_newest_first_and_marked_read() {
+        let repository = repository().await;
+        let now = Utc::now();
+        let mut ids = Vec::new();
+        for (age, message) in [(2, "older"), (1, "newer")] {
+            let notification = Notification {
+                id: Uuid::now_v7(),
+                user_id: ANONYMOUS_USER_ID,
+                task_id: None,
+                message: message.to_string(),
+                created_at: Some(now - ChronoDuration::minutes(age)),
+                read_at: None,
+            };
+            repository.create_notification(&notification).await.unwrap();
+            ids.push(notification.id);
+        }
+
+        let messages = |notifications: Vec<Notification>| {
+            notifications.into_iter().map(|notification| notification.message).collect::<Vec<_>>()
+        };
+        let listed = repository.list_notifications(ANONYMOUS_USER_ID, false).await.unwrap();
+        assert_eq!(messages(listed), ["newer", "older"]);
+
+        assert!(repository.mark_notification_read(ids[1], now).await.unwrap());
+        assert!(repository.mark_notification_read(ids[1], now).await.unwrap());
+        assert!(!repository.mark_notification_read(Uuid::now_v7(), now).await.unwrap());
+        let unread = repository.list_notifications(ANONYMOUS_USER_ID, true).await.unwrap();
+        assert_eq!(messages(unread), ["older"]);
+    }
+
+    #[tokio::test]
+    async fn test_expired_sessions_are_ignored_and_cleaned_up() {
+        let repository = repository().await;
+        let user = repository
+            .create_user(Uuid::new_v4(), "jane@example.com", "hash", "UTC")
+            .await
+            .unwrap();
+        assert_eq!(
+            repository
+                .get_user_by_email("jane@example.com")
+                .await
+                .unwrap()
+                .map(|found| found.id),
+            Some(user.id)
+        );
+
+        let now = Utc::now();
+        repository
+            .create_session(Uuid::new_v4(), user.id, "live", now + ChronoDuration::hours(1))
+            .await
+            .unwrap();
+        repository
+            .create_session(Uuid::new_v4(), user.id, "stale", now - ChronoDuration::hours(1))
+            .await
+            .unwrap();
+
+        assert!(repository.get_session_by_token("live").await.unwrap().is_some());
+        assert!(repository.get_session_by_token("stale").await.unwrap().is_none());
+        assert_eq!(repository.cleanup_expired_sessions().await.unwrap(), 1);
+    }
+}
@@ -1,31 +1,62 @@
 use axum::{
     extract::{Path, Query, State},
     http::StatusCode,
+    middleware::from_fn_with_state,
     response::Json,
-    routing::{get, post},
-    Router,
+    routing::{delete, get, post},
+    Extension, Router,
 };
 use serde_json::{json, Value};
-use sqlx::mysql::MySqlRow;
-use sqlx::Row;
+use std::time::{Duration, Instant};
 use uuid::Uuid;
 
 use crate::{
-    auth::{hash_password, verify_password, generate_session_token, hash_session_token},
+    auth::{hash_password, verify_password, hash_session_token},
+    cursor::{Cursor, Direction},
     db::AppState,
     errors::AppError,
+    markdown,
+    middleware::{auth_middleware, AuthUser},
     models::{
-        AuthResponse, CreateTaskPayload, LoginPayload, PaginatedResponse, PaginationMeta, 
-        RegisterPayload, Task, TaskQueryParams, UpdateTaskPayload, UserResponse
+        AddBlockerPayload, AuthResponse, CreateProjectPayload, CreateReminderPayload, CreateTagPayload,
+        CreateTaskPayload, DeleteTaskParams, LoginPayload, MoveTaskPayload, Notification, NotificationListParams, OccurrencesParams,
+        PaginatedResponse, PaginationMeta, Priority, Project, SortOrder, ProjectListParams, RegisterPayload,
+        Reminder, ReminderChannel, RenderParams, SearchParams, SnoozeReminderPayload, Tag, Task, TaskQueryParams, TotalCount, UpdateProjectPayload,
+        SubtaskProgress, UpdateProfilePayload, UpdateTagPayload, UpdateTaskPayload, UserResponse
     },
-    middleware::AuthUser,
+    query::{Column, Condition, Operator, TaskQuery},
+    rank,
+    recurrence::Recurrence,
+    redact,
+    reminders,
+    repository::{
+        NewProject, NewReminder, NewTag, NewTask, ProjectChanges, Repository, SearchMode, TagChanges,
+        TaskChanges, TaskFilter, TaskSort, TaskSortField, ANONYMOUS_USER_ID,
+    },
+    schedule::{self, Calendar, DateOrTime, Due},
 };
+use chrono::{DateTime, SubsecRound, Utc};
+
+/// How long the readiness probe waits on each database check
+const READINESS_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Longest accepted tag name, in characters
+const MAX_TAG_NAME_LENGTH: usize = 50;
+
+/// Longest accepted project name, in characters
+const MAX_PROJECT_NAME_LENGTH: usize = 100;
 
-/// Maximum allowed title length
-const MAX_TITLE_LENGTH: usize = 255;
+/// Most occurrences of a recurring task previewed at once
+const MAX_PREVIEWED_OCCURRENCES: u32 = 100;
 
-/// Helper function to validate task title
-fn validate_title(title: &str) -> Result<String, AppError> {
+/// Furthest a reminder can be set before its task is due, or snoozed, in minutes (a year)
+const MAX_REMINDER_OFFSET_MINUTES: i32 = 365 * 24 * 60;
+
+/// Longest accepted reminder email address or webhook URL
+const MAX_REMINDER_TARGET_LENGTH: usize = 2048;
+
+/// Helper function to validate task title against the configured maximum length
+fn validate_title(title: &str, max_length: usize) -> Result<String, AppError> {
     let trimmed = title.trim();
 
     if trimmed.is_empty() {
@@ -34,274 +65,1827 @@ fn validate_title(title: &str) -> Result<String, AppError> {
         ));
     }
 
-    if trimmed.len() > MAX_TITLE_LENGTH {
+    if trimmed.len() > max_length {
         return Err(AppError::ValidationError(format!(
             "Task title cannot exceed {} characters",
-            MAX_TITLE_LENGTH
+            max_length
         )));
     }
 
     Ok(trimmed.to_string())
 }
 
-/// Helper function to convert a database row to a Task
-fn row_to_task(row: &MySqlRow) -> Result<Task, sqlx::Error> {
-    Ok(Task {
-        id: Uuid::parse_str(row.try_get("id")?).unwrap(),
-        title: row.try_get("title")?,
-        completed: row.try_get::<i8, _>("completed")? != 0,
-        user_id: Uuid::parse_str(row.try_get("user_id")?).unwrap(),
-        created_at: row.try_get("created_at")?,
-        updated_at: row.try_get("updated_at")?,
+/// Validate a Markdown description against the configured maximum length.
+/// Blank descriptions become `None`.
+fn validate_description(description: &str, max_length: usize) -> Result<Option<String>, AppError> {
+    let trimmed = description.trim();
+
+    if trimmed.len() > max_length {
+        return Err(AppError::ValidationError(format!(
+            "Task description cannot exceed {} bytes",
+            max_length
+        )));
+    }
+
+    Ok(Some(trimmed.to_string()).filter(|description| !description.is_empty()))
+}
+
+/// Normalize a tag name to lowercase. Names cannot contain whitespace, commas
+/// or quotes, so they can be listed in query parameters and filters.
+fn validate_tag_name(name: &str) -> Result<String, AppError> {
+    let name = name.trim().to_lowercase();
+
+    if name.is_empty() {
+        return Err(AppError::ValidationError("Tag name cannot be empty".to_string()));
+    }
+    if name.chars().count() > MAX_TAG_NAME_LENGTH {
+        return Err(AppError::ValidationError(format!(
+            "Tag name cannot exceed {} characters",
+            MAX_TAG_NAME_LENGTH
+        )));
+    }
+    if name.chars().any(|c| c.is_whitespace() || c == ',' || c == '"') {
+        return Err(AppError::ValidationError(
+            "Invalid tag name; spaces, commas and quotes are not allowed".to_string(),
+        ));
+    }
+
+    Ok(name)
+}
+
+/// Trim a project name, which unlike a tag name may contain any characters
+fn validate_project_name(name: &str) -> Result<String, AppError> {
+    let name = name.trim();
+
+    if name.is_empty() {
+        return Err(AppError::ValidationError("Project name cannot be empty".to_string()));
+    }
+    if name.chars().count() > MAX_PROJECT_NAME_LENGTH {
+        return Err(AppError::ValidationError(format!(
+            "Project name cannot exceed {} characters",
+            MAX_PROJECT_NAME_LENGTH
+        )));
+    }
+
+    Ok(name.to_string())
+}
+
+/// Normalize a `#rrggbb` color to lowercase
+fn validate_color(color: &str) -> Result<String, AppError> {
+    let color = color.trim();
+    match color.strip_prefix('#') {
+        Some(hex) if hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit()) => {
+            Ok(color.to_lowercase())
+        }
+        _ => Err(AppError::ValidationError(format!(
+            "Invalid color {:?}; expected #rrggbb",
+            color
+        ))),
+    }
+}
+
+/// Look up the acting user's tags by name, failing on names that do not exist
+async fn resolve_tags(app_state: &AppState, names: &[String]) -> Result<Vec<Uuid>, AppError> {
+    if names.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let tags = app_state.repository.list_tags(ANONYMOUS_USER_ID).await?;
+    names
+        .iter()
+        .map(|name| {
+            let name = name.trim().to_lowercase();
+            tags.iter()
+                .find(|tag| tag.name == name)
+                .map(|tag| tag.id)
+                .ok_or_else(|| {
+                    AppError::ValidationError(
+                        "Unknown tag; create it with POST /tags first".to_string(),
+                    )
+                })
+        })
+        .collect()
+}
+
+/// Parse a recurrence rule; rules are stored in their canonical `to_string()` form
+fn parse_recurrence(rule: &str) -> Result<Recurrence, AppError> {
+    Recurrence::parse(rule).map_err(|error| AppError::ValidationError(error.to_string()))
+}
+
+/// Recurrence rules count from the due date, so a recurring task must keep one
+fn recurrence_needs_due_date() -> AppError {
+    AppError::ValidationError("A recurring task needs a due date to recur from".to_string())
+}
+
+/// A rank right after `task` in its owner's manual order, before the task
+/// that follows it, respacing the positions when there is no room
+async fn position_after(app_state: &AppState, task: &Task) -> Result<String, AppError> {
+    let mut task = task.clone();
+    for _ in 0..2 {
+        let next = app_state.repository.adjacent_tasks(&task, SortOrder::Asc, 1).await?;
+        let upper = next.first().map(|next| next.position.as_str());
+        if let Some(rank) = rank::between(Some(&task.position), upper) {
+            return Ok(rank);
+        }
+        app_state.repository.rebalance_positions(task.user_id).await?;
+        task.position = app_state.repository.get_task(task.id).await?.position;
+    }
+    Err(AppError::Conflict("Tasks were reordered concurrently; try again".to_string()))
+}
+
+/// The occurrence of the recurring `task` after it, due at `due` and
+/// repeating by `recurrence`, with the same details
+fn following_occurrence<'a>(
+    task: &'a Task,
+    due: Due,
+    recurrence: &'a str,
+    position: &'a str,
+) -> NewTask<'a> {
+    NewTask {
+        id: Uuid::now_v7(),
+        title: &task.title,
+        description: task.description.as_deref(),
+        due: Some(due),
+        // The start date keeps its distance to the due date
+        start_at: task
+            .start_at
+            .zip(task.due_at)
+            .map(|(start_at, due_at)| due.at + (start_at - due_at)),
+        priority: task.priority,
+        project_id: task.project_id,
+        parent_id: task.parent_id,
+        auto_complete: task.auto_complete,
+        recurrence: Some(recurrence),
+        position: Some(position),
+        user_id: task.user_id,
+    }
+}
+
+/// Give the next occurrence `next` the tags of `task`, and its reminders
+/// relative to the due date
+async fn carry_over_details(app_state: &AppState, task: &Task, next: &NewTask<'_>) -> Result<(), AppError> {
+    let mut names = app_state.repository.task_tag_names(&[task.id]).await?;
+    let tag_ids = resolve_tags(app_state, &names.remove(&task.id).unwrap_or_default()).await?;
+    app_state.repository.add_task_tags(next.id, &tag_ids).await?;
+
+    // Fixed-time reminders have passed
+    for reminder in app_state.repository.list_reminders(task.id).await? {
+        let Some(minutes_before) = reminder.minutes_before else {
+            continue;
+        };
+        app_state
+            .repository
+            .create_reminder(&NewReminder {
+                id: Uuid::now_v7(),
+                task_id: next.id,
+                user_id: reminder.user_id,
+                channel: reminder.channel,
+                target: reminder.target.as_deref(),
+                remind_at: None,
+                minutes_before: Some(minutes_before),
+                fire_at: offset_fire_at(next.due.map(|due| due.at), minutes_before),
+            })
+            .await?;
+    }
+    Ok(())
+}
+
+/// When a reminder `minutes_before` a task's due date fires
+fn offset_fire_at(due_at: Option<DateTime<Utc>>, minutes_before: i32) -> Option<DateTime<Utc>> {
+    due_at.map(|due_at| due_at - chrono::Duration::minutes(i64::from(minutes_before)))
+}
+
+/// Move the reminders set relative to a task's due date along with it;
+/// they wait without a fire time while the task has no due date
+async fn reschedule_reminders(app_state: &AppState, task: &Task) -> Result<(), AppError> {
+    for reminder in app_state.repository.list_reminders(task.id).await? {
+        if let Some(minutes_before) = reminder.minutes_before {
+            let fire_at = offset_fire_at(task.due_at, minutes_before);
+            if fire_at != reminder.fire_at {
+                app_state.repository.schedule_reminder(reminder.id, fire_at).await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Respace the owner's task positions once `task`'s has grown too long,
+/// updating `task` to match
+async fn shorten_positions(app_state: &AppState, task: &mut Task) -> Result<(), AppError> {
+    if task.position.len() <= rank::MAX_LENGTH {
+        return Ok(());
+    }
+    let renumbered = app_state.repository.rebalance_positions(task.user_id).await?;
+    tracing::info!("Respaced {} task positions", renumbered);
+    task.position = app_state.repository.get_task(task.id).await?.position;
+    Ok(())
+}
+
+/// A task the moved task is placed next to, which must have the same owner
+async fn move_anchor(app_state: &AppState, owner: Uuid, id: Uuid) -> Result<Task, AppError> {
+    match app_state.repository.get_task(id).await {
+        Ok(task) if task.user_id == owner => Ok(task),
+        Ok(_) | Err(sqlx::Error::RowNotFound) => {
+            Err(AppError::ValidationError(format!("Unknown task {}", id)))
+        }
+        Err(error) => Err(error.into()),
+    }
+}
+
+/// The positions `task` goes between when moved right after `after` and
+/// right before `before`; with only one of them, the other side is that
+/// task's current neighbour
+async fn move_bounds(
+    app_state: &AppState,
+    task: &Task,
+    after: Option<Uuid>,
+    before: Option<Uuid>,
+) -> Result<(Option<String>, Option<String>), AppError> {
+    let after = match after {
+        Some(id) => Some(move_anchor(app_state, task.user_id, id).await?),
+        None => None,
+    };
+    let before = match before {
+        Some(id) => Some(move_anchor(app_state, task.user_id, id).await?),
+        None => None,
+    };
+    // The moved task may be the neighbour itself
+    let neighbour = |tasks: Vec<Task>| tasks.into_iter().find(|other| other.id != task.id);
+
+    match (after, before) {
+        (Some(after), Some(before)) => {
+            if (&after.position, after.id) >= (&before.position, before.id) {
+                return Err(AppError::Conflict(format!(
+                    "Task {} does not come before task {}; reload the list and try again",
+                    after.id, before.id
+                )));
+            }
+            Ok((Some(after.position), Some(before.position)))
+        }
+        (Some(after), None) => {
+            let next = app_state.repository.adjacent_tasks(&after, SortOrder::Asc, 2).await?;
+            Ok((Some(after.position), neighbour(next).map(|next| next.position)))
+        }
+        (None, Some(before)) => {
+            let previous = app_state.repository.adjacent_tasks(&before, SortOrder::Desc, 2).await?;
+            Ok((neighbour(previous).map(|previous| previous.position), Some(before.position)))
+        }
+        (None, None) => Err(AppError::ValidationError(
+            "Give the task to move it after, before, or both".to_string(),
+        )),
+    }
+}
+
+/// Fill in `tags`, `subtasks` and `blocked` for each of `tasks`
+async fn attach_details(app_state: &AppState, tasks: &mut [Task]) -> Result<(), AppError> {
+    let ids: Vec<Uuid> = tasks.iter().map(|task| task.id).collect();
+    let mut names = app_state.repository.task_tag_names(&ids).await?;
+    let progress = app_state.repository.subtask_progress(&ids).await?;
+    let blocked = app_state.repository.blocked_task_ids(&ids).await?;
+    for task in tasks {
+        task.tags = names.remove(&task.id).unwrap_or_default();
+        task.subtasks = progress.get(&task.id).copied();
+        task.blocked = blocked.contains(&task.id);
+    }
+    Ok(())
+}
+
+/// Refuse to complete a task while tasks blocking it are still open
+async fn check_unblocked(app_state: &AppState, task_id: Uuid) -> Result<(), AppError> {
+    let open = app_state
+        .repository
+        .list_blockers(task_id)
+        .await?
+        .into_iter()
+        .filter(|blocker| !blocker.completed)
+        .count();
+    if open > 0 {
+        return Err(AppError::Conflict(format!(
+            "Task is blocked by {} open task(s); complete them or remove them as blockers first",
+            open
+        )));
+    }
+    Ok(())
+}
+
+/// `Tagged` conditions for a comma-separated list of tag names, or `None` when it is empty
+fn tag_conditions(names: &str) -> Option<Vec<Condition>> {
+    let conditions: Vec<Condition> = names
+        .split(',')
+        .map(|name| name.trim().to_lowercase())
+        .filter(|name| !name.is_empty())
+        .map(Condition::Tagged)
+        .collect();
+    Some(conditions).filter(|conditions| !conditions.is_empty())
+}
+
+/// Whether `error` means another tag or project already has the name
+fn is_duplicate_name(error: &sqlx::Error) -> bool {
+    matches!(error, sqlx::Error::Database(db_error) if db_error.is_unique_violation())
+}
+
+/// Positions are unique per user, so a task given a rank that a concurrent
+/// move or append took first is refused
+fn position_taken(error: sqlx::Error) -> AppError {
+    match error {
+        sqlx::Error::Database(db_error) if db_error.is_unique_violation() => {
+            AppError::Conflict("Tasks were reordered concurrently; try again".to_string())
+        }
+        error => error.into(),
+    }
+}
+
+/// Check that tasks can be put in the project: it must be one of the acting
+/// user's and not archived
+async fn check_task_project(app_state: &AppState, project_id: Uuid) -> Result<(), AppError> {
+    let project = match app_state.repository.get_project(project_id).await {
+        Ok(project) if project.user_id == ANONYMOUS_USER_ID => project,
+        Ok(_) | Err(sqlx::Error::RowNotFound) => {
+            return Err(AppError::ValidationError(format!("Unknown project {}", project_id)))
+        }
+        Err(error) => return Err(error.into()),
+    };
+    if project.archived {
+        return Err(AppError::ValidationError(format!(
+            "Project {:?} is archived",
+            project.name
+        )));
+    }
+    Ok(())
+}
+
+/// Check that `task_id` (`None` for a new task) can become a subtask of
+/// `parent_id`: the parent must exist and not be the task or one of its
+/// subtasks, and no subtask may end up deeper than `tasks.max_subtask_depth`
+async fn check_parent(
+    app_state: &AppState,
+    task_id: Option<Uuid>,
+    parent_id: Uuid,
+) -> Result<(), AppError> {
+    let max_depth = app_state.config.tasks.max_subtask_depth;
+    let too_deep = || {
+        AppError::ValidationError(format!(
+            "Subtasks cannot be nested more than {} levels deep",
+            max_depth
+        ))
+    };
+
+    let mut ancestor = match app_state.repository.get_task(parent_id).await {
+        Ok(parent) => parent,
+        Err(sqlx::Error::RowNotFound) => {
+            return Err(AppError::ValidationError(format!("Unknown parent task {}", parent_id)))
+        }
+        Err(error) => return Err(error.into()),
+    };
+    // Levels above the task once it is moved: the parent and its ancestors
+    let mut depth = 1;
+    loop {
+        if Some(ancestor.id) == task_id {
+            return Err(AppError::ValidationError(
+                "A task cannot be a subtask of itself or of its own subtasks".to_string(),
+            ));
+        }
+        match ancestor.parent_id {
+            Some(id) if depth < max_depth => {
+                ancestor = app_state.repository.get_task(id).await?;
+                depth += 1;
+            }
+            Some(_) => return Err(too_deep()),
+            None => break,
+        }
+    }
+
+    // Levels of the task's own subtasks, which move along with it
+    let mut level = Vec::from_iter(task_id);
+    while depth <= max_depth {
+        level = app_state.repository.subtask_ids(&level).await?;
+        if level.is_empty() {
+            return Ok(());
+        }
+        depth += 1;
+    }
+    Err(too_deep())
+}
+
+/// Complete `parent_id`, then its ancestors in turn, for as long as they
+/// auto-complete and all of their subtasks are completed
+async fn complete_parents(app_state: &AppState, mut parent_id: Option<Uuid>) -> Result<(), AppError> {
+    while let Some(id) = parent_id {
+        let parent = app_state.repository.get_task(id).await?;
+        if parent.completed || !parent.auto_complete {
+            break;
+        }
+        if !app_state.repository.blocked_task_ids(&[id]).await?.is_empty() {
+            tracing::info!("Not completing task {} while it is blocked", id);
+            break;
+        }
+        let progress = app_state.repository.subtask_progress(&[id]).await?;
+        if !progress.get(&id).is_some_and(SubtaskProgress::is_done) {
+            break;
+        }
+
+        let changes = TaskChanges {
+            completed: Some(true),
+            ..Default::default()
+        };
+        app_state.repository.update_task(id, &changes).await?;
+        tracing::info!("Completed task {} as all of its subtasks are", id);
+        parent_id = parent.parent_id;
+    }
+    Ok(())
+}
+
+/// Validate an IANA time zone name, returning its canonical spelling
+fn validate_time_zone(name: &str) -> Result<&'static str, AppError> {
+    schedule::parse_time_zone(name.trim())
+        .map(|time_zone| time_zone.name())
+        .ok_or_else(|| {
+            AppError::ValidationError(format!(
+                "Unknown time zone {:?}; expected an IANA name such as Europe/Paris",
+                name
+            ))
+        })
+}
+
+/// Dates as seen by the requesting user, whose time zone decides what "today" is
+///
+/// Requests without a valid bearer token see the anonymous user's calendar.
+async fn user_calendar(app_state: &AppState, auth_user: Option<&AuthUser>) -> Result<Calendar, AppError> {
+    let user_id = auth_user.map_or(ANONYMOUS_USER_ID, |auth_user| auth_user.user_id);
+    owner_calendar(app_state, user_id).await
+}
+
+/// Dates as seen by a task's owner, whose time zone decides which days the
+/// task recurs on, whoever is asking
+async fn owner_calendar(app_state: &AppState, user_id: Uuid) -> Result<Calendar, AppError> {
+    let user = app_state.repository.get_user_by_id(user_id).await?;
+    Ok(Calendar::for_time_zone(&user.time_zone))
+}
+
+/// Parse a date or RFC 3339 timestamp given for `field`
+fn parse_date_or_time(field: &str, value: &str) -> Result<DateOrTime, AppError> {
+    DateOrTime::parse(value.trim()).ok_or_else(|| {
+        AppError::ValidationError(format!(
+            "Invalid {} {:?}; expected a date (YYYY-MM-DD) or RFC 3339 timestamp",
+            field, value
+        ))
     })
 }
 
+/// Parse a due date; a date makes the task due all day in the user's time zone
+fn parse_due(value: &str, calendar: &Calendar) -> Result<Due, AppError> {
+    Ok(calendar.due(parse_date_or_time("due_at", value)?))
+}
+
+/// Parse a start date; a date starts at the beginning of that day in the user's time zone
+fn parse_start(value: &str, calendar: &Calendar) -> Result<DateTime<Utc>, AppError> {
+    Ok(calendar.instant(parse_date_or_time("start_at", value)?))
+}
+
+/// Whether `?render=` asks for HTML renderings of descriptions
+fn wants_html(params: &RenderParams) -> Result<bool, AppError> {
+    match params.render.as_deref() {
+        None => Ok(false),
+        Some("html") => Ok(true),
+        Some(other) => Err(AppError::ValidationError(format!(
+            "Invalid render {:?}; expected html",
+            other
+        ))),
+    }
+}
+
+/// Fill in `description_html` for `task`
+fn render_description(task: &mut Task) {
+    task.description_html = task.description.as_deref().map(markdown::render_html);
+}
+
 pub async fn health_check() -> (StatusCode, Json<Value>) {
     tracing::info!("Health check endpoint called");
     (StatusCode::OK, Json(json!({ "status": "ok" })))
 }
 
+/// Liveness probe: the process is up and able to serve requests
+pub async fn liveness() -> (StatusCode, Json<Value>) {
+    (StatusCode::OK, Json(json!({ "status": "ok" })))
+}
+
+/// Readiness probe: the database answers in time and the schema is up to date
+#[tracing::instrument(skip_all)]
+pub async fn readiness(State(app_state): State<AppState>) -> (StatusCode, Json<Value>) {
+    let database = check_database(app_state.repository.as_ref()).await;
+    let migrations = check_migrations(app_state.repository.as_ref()).await;
+
+    let ready = database["status"] == "ok" && migrations["status"] == "ok";
+    let (status, label) = if ready {
+        (StatusCode::OK, "ok")
+    } else {
+        tracing::warn!("Readiness check failed: database={}, migrations={}", database, migrations);
+        (StatusCode::SERVICE_UNAVAILABLE, "unavailable")
+    };
+
+    (
+        status,
+        Json(json!({
+            "status": label,
+            "checks": {
+                "database": database,
+                "migrations": migrations,
+            }
+        })),
+    )
+}
+
+/// Ping the pool and report its statistics
+async fn check_database(repository: &dyn Repository) -> Value {
+    let pool = repository.pool_stats();
+    let stats = json!({
+        "size": pool.size,
+        "idle": pool.idle,
+        "max_connections": pool.max_connections,
+    });
+
+    let started = Instant::now();
+    match tokio::time::timeout(READINESS_TIMEOUT, repository.ping()).await {
+        Ok(Ok(())) => json!({
+            "status": "ok",
+            "latency_ms": started.elapsed().as_millis() as u64,
+            "pool": stats,
+        }),
+        Ok(Err(error)) => json!({
+            "status": "error",
+            "error": error.to_string(),
+            "pool": stats,
+        }),
+        Err(_) => json!({
+            "status": "error",
+            "error": format!("timed out after {}ms", READINESS_TIMEOUT.as_millis()),
+            "pool": stats,
+        }),
+    }
+}
+
+/// Report migrations that have not been applied yet
+async fn check_migrations(repository: &dyn Repository) -> Value {
+    match tokio::time::timeout(READINESS_TIMEOUT, repository.pending_migrations()).await {
+        Ok(Ok(pending)) => {
+            let pending: Vec<Value> = pending
+                .iter()
+                .map(|migration| {
+                    json!({
+                        "version": migration.version,
+                        "description": migration.description,
+                    })
+                })
+                .collect();
+
+            json!({
+                "status": if pending.is_empty() { "ok" } else { "error" },
+                "latest_version": repository.migrations().last().map(|migration| migration.version),
+                "pending": pending,
+            })
+        }
+        Ok(Err(error)) => json!({
+            "status": "error",
+            "error": error.to_string(),
+        }),
+        Err(_) => json!({
+            "status": "error",
+            "error": format!("timed out after {}ms", READINESS_TIMEOUT.as_millis()),
+        }),
+    }
+}
+
 /// Create a new task
+#[tracing::instrument(skip_all)]
 pub async fn create_task(
     State(app_state): State<AppState>,
+    auth_user: Option<Extension<AuthUser>>,
     Json(payload): Json<CreateTaskPayload>,
 ) -> Result<(StatusCode, Json<Task>), AppError> {
-    tracing::info!("Creating new task with title: {}", payload.title);
+    tracing::info!("Creating new task with title: {}", redact::text(&payload.title));
 
     // Validate input
-    let validated_title = validate_title(&payload.title)?;
+    let validated_title =
+        validate_title(&payload.title, app_state.config.tasks.max_title_length)?;
+    let validated_description = match &payload.description {
+        Some(description) => {
+            validate_description(description, app_state.config.tasks.max_description_length)?
+        }
+        None => None,
+    };
 
-    let task_id = Uuid::new_v4();
+    let calendar = match (&payload.due_at, &payload.start_at) {
+        (None, None) => None,
+        _ => Some(user_calendar(&app_state, auth_user.as_deref()).await?),
+    };
+    let due = match (&payload.due_at, &calendar) {
+        (Some(due_at), Some(calendar)) => Some(parse_due(due_at, calendar)?),
+        _ => None,
+    };
+    let start_at = match (&payload.start_at, &calendar) {
+        (Some(start_at), Some(calendar)) => Some(parse_start(start_at, calendar)?),
+        _ => None,
+    };
 
-    // Insert the task into the database
-    sqlx::query(
-        r#"
-        INSERT INTO tasks (id, title, completed, user_id)
-        VALUES (?, ?, FALSE, ?)
-        "#,
-    )
-    .bind(task_id.to_string())
-    .bind(&validated_title)
-    .bind("00000000-0000-0000-0000-000000000000")
-    .execute(&app_state.pool)
-    .await?;
-
-    // Fetch the created task
-    let row = sqlx::query(
-        r#"
-        SELECT id, title, completed, user_id, created_at, updated_at
-        FROM tasks
-        WHERE id = ?
-        "#,
-    )
-    .bind(task_id.to_string())
-    .fetch_one(&app_state.pool)
-    .await?;
+    let recurrence = match &payload.recurrence {
+        Some(_) if due.is_none() => return Err(recurrence_needs_due_date()),
+        Some(rule) => Some(parse_recurrence(rule)?.to_string()),
+        None => None,
+    };
 
-    let task = row_to_task(&row)?;
+    if let Some(project_id) = payload.project_id {
+        check_task_project(&app_state, project_id).await?;
+    }
+    if let Some(parent_id) = payload.parent_id {
+        check_parent(&app_state, None, parent_id).await?;
+    }
+    let tag_ids = resolve_tags(&app_state, &payload.tags).await?;
+
+    let task_id = Uuid::now_v7();
+
+    // Insert the task into the database
+    let mut task = app_state
+        .repository
+        .create_task(&NewTask {
+            id: task_id,
+            title: &validated_title,
+            description: validated_description.as_deref(),
+            due,
+            start_at,
+            priority: payload.priority,
+            project_id: payload.project_id,
+            parent_id: payload.parent_id,
+            auto_complete: payload.auto_complete,
+            recurrence: recurrence.as_deref(),
+            position: None,
+            user_id: ANONYMOUS_USER_ID,
+        })
+        .await?;
+    shorten_positions(&app_state, &mut task).await?;
+    if !tag_ids.is_empty() {
+        app_state.repository.add_task_tags(task_id, &tag_ids).await?;
+        attach_details(&app_state, std::slice::from_mut(&mut task)).await?;
+    }
 
     tracing::info!("Task created successfully with id: {}", task_id);
     Ok((StatusCode::CREATED, Json(task)))
 }
 
 /// Get a task by ID
+#[tracing::instrument(skip_all, fields(task_id = %task_id))]
 pub async fn get_task(
     State(app_state): State<AppState>,
     Path(task_id): Path<Uuid>,
+    Query(render): Query<RenderParams>,
 ) -> Result<Json<Task>, AppError> {
     tracing::info!("Fetching task with id: {}", task_id);
 
-    let row = sqlx::query(
-        r#"
-        SELECT id, title, completed, user_id, created_at, updated_at
-        FROM tasks
-        WHERE id = ?
-        "#,
-    )
-    .bind(task_id.to_string())
-    .fetch_one(&app_state.pool)
-    .await?;
-
-    let task = row_to_task(&row)?;
+    let render_html = wants_html(&render)?;
+    let mut task = app_state.repository.get_task(task_id).await?;
+    attach_details(&app_state, std::slice::from_mut(&mut task)).await?;
+    if render_html {
+        render_description(&mut task);
+    }
 
     Ok(Json(task))
 }
 
 /// List all tasks with pagination and search support
+#[tracing::instrument(skip_all)]
 pub async fn list_tasks(
     State(app_state): State<AppState>,
-    Query(mut query_params): Query<TaskQueryParams>,
+    auth_user: Option<Extension<AuthUser>>,
+    Query(query_params): Query<TaskQueryParams>,
+) -> Result<Json<PaginatedResponse<Task>>, AppError> {
+    search_tasks(&app_state, auth_user.as_deref(), query_params, |filter| filter).await
+}
+
+/// Paginate, search, filter and sort tasks, within the tasks `scope` narrows the filter to
+async fn search_tasks(
+    app_state: &AppState,
+    auth_user: Option<&AuthUser>,
+    mut query_params: TaskQueryParams,
+    scope: impl FnOnce(TaskFilter) -> TaskFilter,
 ) -> Result<Json<PaginatedResponse<Task>>, AppError> {
-    tracing::info!("Listing tasks with search/pagination: page={}, page_size={}, q={:?}, status={:?}", 
+    tracing::info!("Listing tasks with search/pagination: page={}, page_size={}, q={:?}, status={:?}, filter={:?}", 
         query_params.pagination.page, 
         query_params.pagination.page_size,
-        query_params.search.q,
-        query_params.search.status
+        query_params.search.q.as_deref().map(redact::text),
+        query_params.search.status,
+        query_params.search.filter.as_deref().map(redact::text)
     );
 
     // Validate and adjust pagination parameters
     query_params.pagination.validate();
+    let render_html = wants_html(&query_params.render)?;
+
+    if let Some(status) = query_params.search.status.as_deref() {
+        if !matches!(status, "completed" | "pending" | "actionable" | "all") {
+            return Err(AppError::ValidationError(format!(
+                "Invalid status {:?}; expected completed, pending, actionable or all",
+                status
+            )));
+        }
+    }
+
+    let search = &query_params.search;
+    let mut task_query = if search.filter.is_some()
+        || search.due_before.is_some()
+        || search.due_after.is_some()
+        || search.overdue.is_some()
+    {
+        let calendar = user_calendar(app_state, auth_user).await?;
+        schedule_query(search, &calendar)?
+    } else {
+        TaskQuery::default()
+    };
+    if search.status.as_deref() == Some("actionable") {
+        task_query = task_query.and(Condition::Not(Box::new(Condition::Blocked)));
+    }
+    if let Some(priorities) = &search.priority {
+        task_query = task_query.and(Condition::priority_in(&parse_priorities(priorities)?));
+    }
+    if let Some(tagged) = search.tags_any.as_deref().and_then(tag_conditions) {
+        task_query = task_query.and(Condition::Any(tagged));
+    }
+    if let Some(tagged) = search.tags_all.as_deref().and_then(tag_conditions) {
+        task_query = task_query.and(Condition::All(tagged));
+    }
+    if let Some(tagged) = search.tags_none.as_deref().and_then(tag_conditions) {
+        task_query = task_query.and(Condition::Not(Box::new(Condition::Any(tagged))));
+    }
 
-    // Get total count with search filters
-    let total_items = crate::db::count_tasks_with_search(
-        &app_state.pool,
-        query_params.search.q.as_deref(),
-        query_params.search.status.as_deref(),
-    ).await?;
-
-    // Get paginated tasks with search filters
-    let tasks = crate::db::get_paginated_tasks_with_search(
-        &app_state.pool,
-        query_params.pagination.offset(),
-        query_params.pagination.limit(),
+    let filter = scope(
+        TaskFilter::from_params(
+            query_params.search.q.as_deref(),
+            query_params.search.status.as_deref(),
+        )
+        .with_search_mode(SearchMode::from_param(query_params.search.search_mode.as_deref()))
+        .with_query(task_query),
+    );
+    let fulltext = filter.fulltext();
+    if fulltext.as_ref().is_some_and(|query| !query.has_positive_terms()) {
+        return Err(AppError::ValidationError(
+            "Full-text search needs at least one word that is not excluded".to_string(),
+        ));
+    }
+
+    let sort = TaskSort::from_params(
+        query_params.pagination.sort.as_deref(),
         query_params.pagination.sort_by.as_deref(),
         query_params.pagination.sort_order.as_ref(),
-        query_params.search.q.as_deref(),
-        query_params.search.status.as_deref(),
-    ).await?;
+    )
+    .map_err(|error| AppError::ValidationError(error.to_string()))?
+    .for_filter(&filter);
+
+    let mut response = if query_params.pagination.is_cursor_mode() {
+        // Scores depend on the query, so they cannot be resumed from a cursor
+        if sort.contains(TaskSortField::Relevance) {
+            return Err(AppError::ValidationError(
+                "Sorting by relevance is not supported with cursor pagination".to_string(),
+            ));
+        }
+        list_tasks_by_cursor(app_state, &query_params, &filter, &sort).await?
+    } else {
+        list_tasks_by_page(app_state, &query_params, &filter, &sort).await?
+    };
+
+    if let Some(query) = fulltext {
+        for task in &mut response.data {
+            task.snippet = Some(query.snippet(&task.title, task.description.as_deref()));
+        }
+    }
+    attach_details(app_state, &mut response.data).await?;
+    if render_html {
+        response.data.iter_mut().for_each(render_description);
+    }
+
+    Ok(Json(response))
+}
+
+/// Parse the comma-separated `priority` parameter
+fn parse_priorities(priorities: &str) -> Result<Vec<Priority>, AppError> {
+    priorities
+        .split(',')
+        .map(|name| {
+            Priority::from_name(name.trim()).ok_or_else(|| {
+                AppError::ValidationError(format!(
+                    "Invalid priority {:?}; expected none, low, medium, high or urgent",
+                    name
+                ))
+            })
+        })
+        .collect()
+}
+
+/// Conditions from the `filter` language and the `due_before`, `due_after`
+/// and `overdue` parameters, with dates read in `calendar`'s time zone
+fn schedule_query(search: &SearchParams, calendar: &Calendar) -> Result<TaskQuery, AppError> {
+    let mut query = TaskQuery::parse(search.filter.as_deref().unwrap_or_default(), calendar)
+        .map_err(|error| AppError::ValidationError(error.to_string()))?;
+
+    // Before a date is before it starts; after a date is once it is over
+    if let Some(value) = &search.due_before {
+        let value = parse_date_or_time("due_before", value)?;
+        query = query.and(Condition::time(Column::DueAt, Operator::Lt, value, calendar));
+    }
+    if let Some(value) = &search.due_after {
+        let value = parse_date_or_time("due_after", value)?;
+        query = query.and(Condition::time(Column::DueAt, Operator::Gt, value, calendar));
+    }
+    match search.overdue {
+        Some(true) => query = query.and(Condition::overdue(calendar)),
+        Some(false) => query = query.and(Condition::Not(Box::new(Condition::overdue(calendar)))),
+        None => {}
+    }
+
+    Ok(query)
+}
+
+/// Page-numbered listing, the default mode
+async fn list_tasks_by_page(
+    app_state: &AppState,
+    query_params: &TaskQueryParams,
+    filter: &TaskFilter,
+    sort: &TaskSort,
+) -> Result<PaginatedResponse<Task>, AppError> {
+    // Get paginated tasks with search filters, plus one row to tell whether there is a next page
+    let limit = query_params.pagination.limit();
+    let mut tasks = app_state
+        .repository
+        .list_tasks(filter, sort, query_params.pagination.offset(), limit + 1)
+        .await?;
+    let has_next = tasks.len() as u64 > limit;
+    tasks.truncate(limit as usize);
+
+    let total = count_total(app_state, filter, query_params.pagination.include_total).await?;
 
     // Create pagination metadata
-    let pagination = PaginationMeta::new(query_params.pagination.page, query_params.pagination.page_size, total_items);
+    let pagination = PaginationMeta::new(query_params.pagination.page, query_params.pagination.page_size, total, has_next);
 
     let response = PaginatedResponse {
         data: tasks,
         pagination,
     };
 
-    tracing::info!("Found {} tasks (total: {}, page: {}/{})", 
+    tracing::info!("Found {} tasks (total: {:?}, page: {}/{:?})", 
         response.data.len(), 
-        total_items, 
+        response.pagination.total_items, 
         query_params.pagination.page, 
         response.pagination.total_pages
     );
 
-    Ok(Json(response))
+    Ok(response)
+}
+
+/// Count the tasks matching `filter` for the pagination metadata, stopping at
+/// `tasks.total_count_cap` when one is configured. `None` when not requested.
+async fn count_total(
+    app_state: &AppState,
+    filter: &TaskFilter,
+    include_total: bool,
+) -> Result<Option<TotalCount>, AppError> {
+    if !include_total {
+        return Ok(None);
+    }
+
+    let total = match app_state.config.tasks.total_count_cap {
+        Some(cap) => {
+            // Counting one past the cap tells whether it was reached
+            let items = app_state.repository.count_tasks_up_to(filter, cap.saturating_add(1)).await?;
+            TotalCount {
+                items: items.min(cap),
+                exact: items <= cap,
+            }
+        }
+        None => TotalCount {
+            items: app_state.repository.count_tasks(filter).await?,
+            exact: true,
+        },
+    };
+
+    Ok(Some(total))
+}
+
+/// Keyset-paginated listing for `?cursor=...&limit=...`. A cursor carries its
+/// own ordering, so `sort`/`sort_by`/`sort_order` only apply to the first page.
+async fn list_tasks_by_cursor(
+    app_state: &AppState,
+    query_params: &TaskQueryParams,
+    filter: &TaskFilter,
+    sort: &TaskSort,
+) -> Result<PaginatedResponse<Task>, AppError> {
+    let cursor = query_params
+        .pagination
+        .cursor
+        .as_deref()
+        .map(|encoded| {
+            Cursor::decode(encoded)
+                .ok_or_else(|| AppError::ValidationError("Invalid cursor".to_string()))
+        })
+        .transpose()?;
+
+    let limit = query_params.pagination.cursor_limit();
+    let sort = cursor.as_ref().map_or(sort, |cursor| &cursor.sort);
+    let direction = cursor
+        .as_ref()
+        .map_or(Direction::Next, |cursor| cursor.direction);
+
+    // Paging backwards reads the preceding tasks in reverse order. One extra row
+    // tells whether there is anything beyond this page.
+    let query_sort = match direction {
+        Direction::Next => sort.clone(),
+        Direction::Previous => sort.reversed(),
+    };
+    let mut tasks = app_state
+        .repository
+        .list_tasks_after(
+            filter,
+            &query_sort,
+            cursor.as_ref().map(|cursor| &cursor.position),
+            limit as u64 + 1,
+        )
+        .await?;
+    let has_more = tasks.len() > limit as usize;
+    tasks.truncate(limit as usize);
+    if direction == Direction::Previous {
+        tasks.reverse();
+    }
+
+    let (has_next, has_previous) = match direction {
+        Direction::Next => (has_more, cursor.is_some()),
+        Direction::Previous => (true, has_more),
+    };
+    let next_cursor = tasks
+        .last()
+        .filter(|_| has_next)
+        .map(|task| Cursor::next(sort, task).encode());
+    let prev_cursor = tasks
+        .first()
+        .filter(|_| has_previous)
+        .map(|task| Cursor::previous(sort, task).encode());
+
+    let total = count_total(app_state, filter, query_params.pagination.include_total).await?;
+
+    tracing::info!("Found {} tasks (total: {:?}, cursor mode)", tasks.len(), total);
+
+    Ok(PaginatedResponse {
+        data: tasks,
+        pagination: PaginationMeta::with_cursors(limit, total, next_cursor, prev_cursor),
+    })
 }
 
 /// List all tasks (legacy endpoint for backwards compatibility)
+#[tracing::instrument(skip_all)]
 pub async fn list_all_tasks(State(app_state): State<AppState>) -> Result<Json<Vec<Task>>, AppError> {
     tracing::info!("Listing all tasks (legacy endpoint)");
 
-    let rows = sqlx::query(
-        r#"
-        SELECT id, title, completed, user_id, created_at, updated_at
-        FROM tasks
-        ORDER BY created_at DESC
-        "#,
-    )
-    .fetch_all(&app_state.pool)
-    .await?;
-
-    let tasks: Vec<Task> = rows
-        .iter()
-        .map(row_to_task)
-        .collect::<Result<Vec<_>, sqlx::Error>>()?;
+    let tasks = app_state.repository.list_all_tasks().await?;
 
     tracing::info!("Found {} tasks", tasks.len());
     Ok(Json(tasks))
 }
 
 /// Update a task
+#[tracing::instrument(skip_all, fields(task_id = %task_id))]
 pub async fn update_task(
     State(app_state): State<AppState>,
+    auth_user: Option<Extension<AuthUser>>,
     Path(task_id): Path<Uuid>,
     Json(payload): Json<UpdateTaskPayload>,
 ) -> Result<Json<Task>, AppError> {
     tracing::info!("Updating task with id: {}", task_id);
 
-    // Check if task exists
-    let _ = sqlx::query(r#"SELECT id FROM tasks WHERE id = ?"#)
-        .bind(task_id.to_string())
-        .fetch_one(&app_state.pool)
-        .await?;
-
     // Validate title if provided
     let validated_title = if let Some(title) = &payload.title {
-        Some(validate_title(title)?)
+        Some(validate_title(
+            title,
+            app_state.config.tasks.max_title_length,
+        )?)
     } else {
         None
     };
+    let validated_description = match &payload.description {
+        Some(description) => Some(validate_description(
+            description,
+            app_state.config.tasks.max_description_length,
+        )?),
+        None => None,
+    };
 
-    // Build dynamic update query based on provided fields
-    let mut query_builder = sqlx::QueryBuilder::new("UPDATE tasks SET ");
-    let mut has_updates = false;
+    let calendar = match (&payload.due_at, &payload.start_at) {
+        (Some(Some(_)), _) | (_, Some(Some(_))) => Some(user_calendar(&app_state, auth_user.as_deref()).await?),
+        _ => None,
+    };
+    let due = match (&payload.due_at, &calendar) {
+        (Some(Some(due_at)), Some(calendar)) => Some(Some(parse_due(due_at, calendar)?)),
+        (Some(_), _) => Some(None),
+        (None, _) => None,
+    };
+    let start_at = match (&payload.start_at, &calendar) {
+        (Some(Some(start_at)), Some(calendar)) => Some(Some(parse_start(start_at, calendar)?)),
+        (Some(_), _) => Some(None),
+        (None, _) => None,
+    };
+
+    let recurrence = match &payload.recurrence {
+        Some(Some(rule)) => Some(Some(parse_recurrence(rule)?.to_string())),
+        Some(None) => Some(None),
+        None => None,
+    };
 
-    if let Some(title) = &validated_title {
-        query_builder.push("title = ");
-        query_builder.push_bind(title);
-        has_updates = true;
+    let mut changes = TaskChanges {
+        title: validated_title.as_deref(),
+        description: validated_description.as_ref().map(Option::as_deref),
+        completed: payload.completed,
+        due,
+        start_at,
+        priority: payload.priority,
+        project_id: payload.project_id,
+        parent_id: payload.parent_id,
+        auto_complete: payload.auto_complete,
+        recurrence: recurrence.as_ref().map(Option::as_deref),
+        position: None,
+    };
+    if changes.is_empty() && payload.add_tags.is_empty() && payload.remove_tags.is_empty() {
+        return Err(AppError::NoFieldsToUpdate);
+    }
+    if let Some(Some(project_id)) = payload.project_id {
+        check_task_project(&app_state, project_id).await?;
+    }
+    if changes.completed == Some(true) {
+        check_unblocked(&app_state, task_id).await?;
+    }
+    // The parent the task is moved away from, which may now have only completed subtasks
+    let previous_parent = match payload.parent_id {
+        Some(parent_id) => {
+            let task = app_state.repository.get_task(task_id).await?;
+            if let Some(parent_id) = parent_id {
+                check_parent(&app_state, Some(task_id), parent_id).await?;
+            }
+            task.parent_id.filter(|previous| Some(*previous) != parent_id)
+        }
+        None => None,
+    };
+    let added_tags = resolve_tags(&app_state, &payload.add_tags).await?;
+    let removed_tags = resolve_tags(&app_state, &payload.remove_tags).await?;
+
+    // Recurring tasks need a due date to recur from. Completing one hands its
+    // rule on to a new task for the next occurrence, if there is one.
+    let mut next_occurrence = None;
+    if changes.completed == Some(true) || changes.recurrence.is_some() || changes.due == Some(None) {
+        let mut task = app_state.repository.get_task(task_id).await?;
+        let was_completed = task.completed;
+        changes.apply(&mut task);
+        if let Some(rule) = &task.recurrence {
+            let due = task
+                .due_at
+                .map(|at| Due {
+                    at,
+                    all_day: task.due_all_day,
+                })
+                .ok_or_else(recurrence_needs_due_date)?;
+            if task.completed && !was_completed {
+                let rule = parse_recurrence(rule)?;
+                let calendar = owner_calendar(&app_state, task.user_id).await?;
+                if let Some(next) = rule.occurrences(due, &calendar).next() {
+                    next_occurrence = Some((task.clone(), next, rule.following().to_string()));
+                }
+                changes.recurrence = Some(None);
+            }
+        }
     }
 
-    if let Some(completed) = payload.completed {
-        if has_updates {
-            query_builder.push(", ");
+    // Completing a recurring task creates its next occurrence along with it,
+    // right after the completed one, which it replaces
+    let position = match &next_occurrence {
+        Some((completed, _, _)) => Some(position_after(&app_state, completed).await?),
+        None => None,
+    };
+    let next = match (&next_occurrence, &position) {
+        (Some((completed, due, rule)), Some(position)) => {
+            Some(following_occurrence(completed, *due, rule, position))
         }
-        query_builder.push("completed = ");
-        query_builder.push_bind(completed);
-        has_updates = true;
+        _ => None,
+    };
+    let updated = match &next {
+        Some(next) => app_state
+            .repository
+            .complete_recurring_task(task_id, &changes, next)
+            .await
+            .map_err(position_taken)?,
+        None => None,
+    };
+    // Not created when a concurrent request completed the task first
+    let created = updated.is_some();
+
+    // Fails with RowNotFound (404) if the task does not exist or is deleted
+    // concurrently. Tag changes alone still bump updated_at.
+    let mut task = match updated {
+        Some(task) => task,
+        None => app_state.repository.update_task(task_id, &changes).await?,
+    };
+    app_state.repository.remove_task_tags(task_id, &removed_tags).await?;
+    app_state.repository.add_task_tags(task_id, &added_tags).await?;
+    if changes.due.is_some() {
+        reschedule_reminders(&app_state, &task).await?;
+    }
+    // After the tag changes, which carry over too
+    if let (Some((completed, _, _)), Some(next), true) = (&next_occurrence, &next, created) {
+        carry_over_details(&app_state, completed, next).await?;
+        tracing::info!("Created task {} as the next occurrence", next.id);
     }
 
-    if !has_updates {
-        return Err(AppError::NoFieldsToUpdate);
+    if changes.auto_complete == Some(true) {
+        // Subtasks may all be completed already; this also rolls up to the parents
+        complete_parents(&app_state, Some(task_id)).await?;
+        task = app_state.repository.get_task(task_id).await?;
+    } else if changes.completed == Some(true) || changes.parent_id.is_some() {
+        complete_parents(&app_state, task.parent_id).await?;
     }
+    complete_parents(&app_state, previous_parent).await?;
+    attach_details(&app_state, std::slice::from_mut(&mut task)).await?;
+
+    tracing::info!("Task updated successfully");
+    Ok(Json(task))
+}
 
-    // Always update the updated_at timestamp to ensure it changes
-    if has_updates {
-        query_builder.push(", ");
+/// Delete a task, along with its subtasks unless `?subtasks=promote` asks
+/// to move them up to its parent instead
+#[tracing::instrument(skip_all, fields(task_id = %task_id))]
+pub async fn delete_task(
+    State(app_state): State<AppState>,
+    Path(task_id): Path<Uuid>,
+    Query(params): Query<DeleteTaskParams>,
+) -> Result<StatusCode, AppError> {
+    tracing::info!("Deleting task with id: {}", task_id);
+
+    let promote = match params.subtasks.as_deref() {
+        None | Some("delete") => false,
+        Some("promote") => true,
+        Some(other) => {
+            return Err(AppError::ValidationError(format!(
+                "Invalid subtasks {:?}; expected delete or promote",
+                other
+            )))
+        }
+    };
+
+    let task = app_state.repository.get_task(task_id).await?;
+    if promote {
+        let moved = app_state
+            .repository
+            .reparent_subtasks(task_id, task.parent_id)
+            .await?;
+        tracing::info!("Moved {} subtasks up a level", moved);
     }
-    query_builder.push("updated_at = CURRENT_TIMESTAMP(6)");
 
-    query_builder.push(" WHERE id = ");
-    query_builder.push_bind(task_id.to_string());
+    let deleted = app_state.repository.delete_task(task_id).await?;
 
-    let query = query_builder.build();
-    query.execute(&app_state.pool).await?;
+    if !deleted {
+        return Err(AppError::NotFound);
+    }
+    complete_parents(&app_state, task.parent_id).await?;
 
-    // Fetch the updated task
-    let row = sqlx::query(
-        r#"
-        SELECT id, title, completed, user_id, created_at, updated_at
-        FROM tasks
-        WHERE id = ?
-        "#,
-    )
-    .bind(task_id.to_string())
-    .fetch_one(&app_state.pool)
-    .await?;
+    tracing::info!("Task deleted successfully");
+    Ok(StatusCode::NO_CONTENT)
+}
 
-    let task = row_to_task(&row)?;
+/// List a task's direct subtasks, with the same pagination, search, filters
+/// and sorting as `GET /tasks`
+#[tracing::instrument(skip_all, fields(task_id = %task_id))]
+pub async fn list_subtasks(
+    State(app_state): State<AppState>,
+    auth_user: Option<Extension<AuthUser>>,
+    Path(task_id): Path<Uuid>,
+    Query(query_params): Query<TaskQueryParams>,
+) -> Result<Json<PaginatedResponse<Task>>, AppError> {
+    // 404 rather than an empty page for a task that does not exist
+    app_state.repository.get_task(task_id).await?;
+    search_tasks(&app_state, auth_user.as_deref(), query_params, |filter| filter.children_of(task_id)).await
+}
+
+/// Preview when a recurring task's next occurrences are due, after the
+/// current one; empty for tasks that do not recur
+#[tracing::instrument(skip_all, fields(task_id = %task_id))]
+pub async fn list_occurrences(
+    State(app_state): State<AppState>,
+    Path(task_id): Path<Uuid>,
+    Query(params): Query<OccurrencesParams>,
+) -> Result<Json<Vec<DateTime<Utc>>>, AppError> {
+    let count = params.count.unwrap_or(5);
+    if !(1..=MAX_PREVIEWED_OCCURRENCES).contains(&count) {
+        return Err(AppError::ValidationError(format!(
+            "count must be between 1 and {}",
+            MAX_PREVIEWED_OCCURRENCES
+        )));
+    }
+
+    let task = app_state.repository.get_task(task_id).await?;
+    let (Some(rule), Some(at)) = (&task.recurrence, task.due_at) else {
+        return Ok(Json(Vec::new()));
+    };
+    let rule = parse_recurrence(rule)?;
+    let calendar = owner_calendar(&app_state, task.user_id).await?;
+    let due = Due {
+        at,
+        all_day: task.due_all_day,
+    };
+    Ok(Json(
+        rule.occurrences(due, &calendar)
+            .take(count as usize)
+            .map(|due| due.at)
+            .collect(),
+    ))
+}
+
+/// Move a task in the owner's manual order, rewriting only its own position
+#[tracing::instrument(skip_all, fields(task_id = %task_id))]
+pub async fn move_task(
+    State(app_state): State<AppState>,
+    Path(task_id): Path<Uuid>,
+    Json(payload): Json<MoveTaskPayload>,
+) -> Result<Json<Task>, AppError> {
+    tracing::info!("Moving task with id: {}", task_id);
+
+    if payload.after == Some(task_id) || payload.before == Some(task_id) {
+        return Err(AppError::ValidationError(
+            "A task cannot be moved next to itself".to_string(),
+        ));
+    }
+    let task = app_state.repository.get_task(task_id).await?;
+
+    // Neighbours reordered concurrently may leave no room between them;
+    // respacing the positions makes room, so the second attempt finds one
+    let mut position = None;
+    for _ in 0..2 {
+        let (lower, upper) = move_bounds(&app_state, &task, payload.after, payload.before).await?;
+        position = rank::between(lower.as_deref(), upper.as_deref());
+        if position.is_some() {
+            break;
+        }
+        app_state.repository.rebalance_positions(task.user_id).await?;
+    }
+    let position = position.ok_or_else(|| {
+        AppError::Conflict("Tasks were reordered concurrently; try again".to_string())
+    })?;
+
+    let changes = TaskChanges {
+        position: Some(&position),
+        ..Default::default()
+    };
+    let mut task = app_state
+        .repository
+        .update_task(task_id, &changes)
+        .await
+        .map_err(position_taken)?;
+    shorten_positions(&app_state, &mut task).await?;
+    attach_details(&app_state, std::slice::from_mut(&mut task)).await?;
 
-    tracing::info!("Task updated successfully");
     Ok(Json(task))
 }
 
-/// Delete a task
-pub async fn delete_task(
+/// List the tasks blocking a task, oldest first
+#[tracing::instrument(skip_all, fields(task_id = %task_id))]
+pub async fn list_blockers(
     State(app_state): State<AppState>,
     Path(task_id): Path<Uuid>,
+) -> Result<Json<Vec<Task>>, AppError> {
+    app_state.repository.get_task(task_id).await?;
+    let mut blockers = app_state.repository.list_blockers(task_id).await?;
+    attach_details(&app_state, &mut blockers).await?;
+    Ok(Json(blockers))
+}
+
+/// Make a task wait for another, refusing dependencies that would form a cycle
+#[tracing::instrument(skip_all, fields(task_id = %task_id))]
+pub async fn add_blocker(
+    State(app_state): State<AppState>,
+    Path(task_id): Path<Uuid>,
+    Json(payload): Json<AddBlockerPayload>,
+) -> Result<Json<Vec<Task>>, AppError> {
+    let blocker_id = payload.blocker_id;
+    tracing::info!("Blocking task {} by {}", task_id, blocker_id);
+
+    app_state.repository.get_task(task_id).await?;
+    match app_state.repository.get_task(blocker_id).await {
+        Ok(_) => {}
+        Err(sqlx::Error::RowNotFound) => {
+            return Err(AppError::ValidationError(format!("Unknown blocker task {}", blocker_id)))
+        }
+        Err(error) => return Err(error.into()),
+    }
+
+    if !app_state.repository.add_blocker(task_id, blocker_id).await? {
+        return Err(AppError::ValidationError("Dependencies cannot form a cycle".to_string()));
+    }
+
+    let mut blockers = app_state.repository.list_blockers(task_id).await?;
+    attach_details(&app_state, &mut blockers).await?;
+    Ok(Json(blockers))
+}
+
+/// Stop a task from waiting for another
+#[tracing::instrument(skip_all, fields(task_id = %task_id, blocker_id = %blocker_id))]
+pub async fn remove_blocker(
+    State(app_state): State<AppState>,
+    Path((task_id, blocker_id)): Path<(Uuid, Uuid)>,
 ) -> Result<StatusCode, AppError> {
-    tracing::info!("Deleting task with id: {}", task_id);
+    tracing::info!("Unblocking task {} from {}", task_id, blocker_id);
+
+    if !app_state.repository.remove_blocker(task_id, blocker_id).await? {
+        return Err(AppError::NotFound);
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+
+/// Check where a reminder goes for its channel, defaulting email reminders
+/// to the user's own address
+async fn reminder_target(
+    app_state: &AppState,
+    channel: ReminderChannel,
+    target: Option<&str>,
+) -> Result<Option<String>, AppError> {
+    let target = target.map(str::trim).filter(|target| !target.is_empty());
+    match channel {
+        ReminderChannel::InApp => match target {
+            None => Ok(None),
+            Some(_) => Err(AppError::ValidationError(
+                "In-app reminders do not take a target".to_string(),
+            )),
+        },
+        ReminderChannel::Email => {
+            let address = match target {
+                Some(address) => address.to_string(),
+                None => app_state.repository.get_user_by_id(ANONYMOUS_USER_ID).await?.email,
+            };
+            if !address.contains('@') || address.len() > MAX_REMINDER_TARGET_LENGTH {
+                return Err(AppError::ValidationError("Invalid email address".to_string()));
+            }
+            Ok(Some(address))
+        }
+        ReminderChannel::Webhook => {
+            let url = target.ok_or_else(|| {
+                AppError::ValidationError("Webhook reminders need a target URL".to_string())
+            })?;
+            let parsed = reqwest::Url::parse(url)
+                .ok()
+                .filter(|parsed| matches!(parsed.scheme(), "http" | "https"))
+                .filter(|_| url.len() <= MAX_REMINDER_TARGET_LENGTH)
+                .ok_or_else(|| {
+                    AppError::ValidationError(
+                        "Invalid webhook URL; expected an http or https URL".to_string(),
+                    )
+                })?;
+            if !app_state.config.reminders.allow_private_webhooks {
+                reminders::check_webhook_url(&parsed).await.map_err(|error| {
+                    AppError::ValidationError(format!("Webhook URL is not allowed: {}", error))
+                })?;
+            }
+            Ok(Some(url.to_string()))
+        }
+    }
+}
 
-    let result = sqlx::query(r#"DELETE FROM tasks WHERE id = ?"#)
-        .bind(task_id.to_string())
-        .execute(&app_state.pool)
+/// Look up a task's reminder, 404 if it belongs to another task
+async fn task_reminder(app_state: &AppState, task_id: Uuid, reminder_id: Uuid) -> Result<Reminder, AppError> {
+    let reminder = app_state.repository.get_reminder(reminder_id).await?;
+    if reminder.task_id != task_id {
+        return Err(AppError::NotFound);
+    }
+    Ok(reminder)
+}
+
+/// List a task's reminders, oldest first
+#[tracing::instrument(skip_all, fields(task_id = %task_id))]
+pub async fn list_reminders(
+    State(app_state): State<AppState>,
+    Path(task_id): Path<Uuid>,
+) -> Result<Json<Vec<Reminder>>, AppError> {
+    app_state.repository.get_task(task_id).await?;
+    Ok(Json(app_state.repository.list_reminders(task_id).await?))
+}
+
+/// Remind about a task at a fixed time, or some minutes before it is due
+#[tracing::instrument(skip_all, fields(task_id = %task_id))]
+pub async fn create_reminder(
+    State(app_state): State<AppState>,
+    auth_user: Option<Extension<AuthUser>>,
+    Path(task_id): Path<Uuid>,
+    Json(payload): Json<CreateReminderPayload>,
+) -> Result<(StatusCode, Json<Reminder>), AppError> {
+    tracing::info!("Creating {} reminder for task {}", payload.channel.name(), task_id);
+
+    let task = app_state.repository.get_task(task_id).await?;
+    let (remind_at, fire_at) = match (&payload.remind_at, payload.minutes_before) {
+        (Some(remind_at), None) => {
+            let calendar = user_calendar(&app_state, auth_user.as_deref()).await?;
+            let at = calendar.instant(parse_date_or_time("remind_at", remind_at)?);
+            if at <= Utc::now() {
+                return Err(AppError::ValidationError(
+                    "remind_at must be in the future".to_string(),
+                ));
+            }
+            (Some(at), Some(at))
+        }
+        (None, Some(minutes)) => {
+            if !(0..=MAX_REMINDER_OFFSET_MINUTES).contains(&minutes) {
+                return Err(AppError::ValidationError(format!(
+                    "minutes_before must be between 0 and {}",
+                    MAX_REMINDER_OFFSET_MINUTES
+                )));
+            }
+            if task.due_at.is_none() {
+                return Err(AppError::ValidationError(
+                    "minutes_before needs the task to have a due date".to_string(),
+                ));
+            }
+            (None, offset_fire_at(task.due_at, minutes))
+        }
+        _ => {
+            return Err(AppError::ValidationError(
+                "Give exactly one of remind_at and minutes_before".to_string(),
+            ))
+        }
+    };
+    let target = reminder_target(&app_state, payload.channel, payload.target.as_deref()).await?;
+
+    let reminder = app_state
+        .repository
+        .create_reminder(&NewReminder {
+            id: Uuid::now_v7(),
+            task_id,
+            user_id: task.user_id,
+            channel: payload.channel,
+            target: target.as_deref(),
+            remind_at,
+            minutes_before: payload.minutes_before,
+            fire_at,
+        })
         .await?;
 
-    if result.rows_affected() == 0 {
+    tracing::info!("Reminder created with id: {}", reminder.id);
+    Ok((StatusCode::CREATED, Json(reminder)))
+}
+
+/// Delete one of a task's reminders
+#[tracing::instrument(skip_all, fields(task_id = %task_id, reminder_id = %reminder_id))]
+pub async fn delete_reminder(
+    State(app_state): State<AppState>,
+    Path((task_id, reminder_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, AppError> {
+    tracing::info!("Deleting reminder {} of task {}", reminder_id, task_id);
+
+    task_reminder(&app_state, task_id, reminder_id).await?;
+    if !app_state.repository.delete_reminder(reminder_id).await? {
         return Err(AppError::NotFound);
     }
+    Ok(StatusCode::NO_CONTENT)
+}
 
-    tracing::info!("Task deleted successfully");
+/// Send a reminder again later, whether or not it has gone out yet
+#[tracing::instrument(skip_all, fields(task_id = %task_id, reminder_id = %reminder_id))]
+pub async fn snooze_reminder(
+    State(app_state): State<AppState>,
+    auth_user: Option<Extension<AuthUser>>,
+    Path((task_id, reminder_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<SnoozeReminderPayload>,
+) -> Result<Json<Reminder>, AppError> {
+    task_reminder(&app_state, task_id, reminder_id).await?;
+
+    let now = Utc::now();
+    let fire_at = match (payload.minutes, &payload.until) {
+        (Some(minutes), None) => {
+            if !(1..=i64::from(MAX_REMINDER_OFFSET_MINUTES)).contains(&minutes) {
+                return Err(AppError::ValidationError(format!(
+                    "minutes must be between 1 and {}",
+                    MAX_REMINDER_OFFSET_MINUTES
+                )));
+            }
+            now + chrono::Duration::minutes(minutes)
+        }
+        (None, Some(until)) => {
+            let calendar = user_calendar(&app_state, auth_user.as_deref()).await?;
+            let at = calendar.instant(parse_date_or_time("until", until)?);
+            if at <= now {
+                return Err(AppError::ValidationError("until must be in the future".to_string()));
+            }
+            at
+        }
+        _ => {
+            return Err(AppError::ValidationError(
+                "Give exactly one of minutes and until".to_string(),
+            ))
+        }
+    };
+    let fire_at = fire_at.trunc_subsecs(0);
+    tracing::info!("Snoozing reminder {} until {}", reminder_id, fire_at);
+
+    Ok(Json(app_state.repository.schedule_reminder(reminder_id, Some(fire_at)).await?))
+}
+
+/// List the user's notifications, newest first; `?unread=true` leaves out read ones
+#[tracing::instrument(skip_all)]
+pub async fn list_notifications(
+    State(app_state): State<AppState>,
+    Query(params): Query<NotificationListParams>,
+) -> Result<Json<Vec<Notification>>, AppError> {
+    Ok(Json(
+        app_state
+            .repository
+            .list_notifications(ANONYMOUS_USER_ID, params.unread)
+            .await?,
+    ))
+}
+
+/// Mark a notification as read
+#[tracing::instrument(skip_all, fields(notification_id = %notification_id))]
+pub async fn mark_notification_read(
+    State(app_state): State<AppState>,
+    Path(notification_id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    if !app_state
+        .repository
+        .mark_notification_read(notification_id, Utc::now())
+        .await?
+    {
+        return Err(AppError::NotFound);
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// List the user's tags by name, with how many tasks carry each
+#[tracing::instrument(skip_all)]
+pub async fn list_tags(State(app_state): State<AppState>) -> Result<Json<Vec<Tag>>, AppError> {
+    tracing::info!("Listing tags");
+
+    let tags = app_state.repository.list_tags(ANONYMOUS_USER_ID).await?;
+    Ok(Json(tags))
+}
+
+/// Create a tag
+#[tracing::instrument(skip_all)]
+pub async fn create_tag(
+    State(app_state): State<AppState>,
+    Json(payload): Json<CreateTagPayload>,
+) -> Result<(StatusCode, Json<Tag>), AppError> {
+    let name = validate_tag_name(&payload.name)?;
+    let color = payload.color.as_deref().map(validate_color).transpose()?;
+    tracing::info!("Creating tag {}", redact::text(&name));
+
+    let existing = app_state.repository.list_tags(ANONYMOUS_USER_ID).await?;
+    if existing.iter().any(|tag| tag.name == name) {
+        return Err(AppError::ValidationError("A tag with this name already exists".to_string()));
+    }
+
+    let tag = app_state
+        .repository
+        .create_tag(&NewTag {
+            id: Uuid::now_v7(),
+            user_id: ANONYMOUS_USER_ID,
+            name: &name,
+            color: color.as_deref(),
+        })
+        .await
+        .map_err(|error| match error {
+            // Lost a race with a concurrent request for the same name
+            error if is_duplicate_name(&error) => {
+                AppError::ValidationError("A tag with this name already exists".to_string())
+            }
+            error => error.into(),
+        })?;
+
+    Ok((StatusCode::CREATED, Json(tag)))
+}
+
+/// Get a tag by ID
+#[tracing::instrument(skip_all, fields(tag_id = %tag_id))]
+pub async fn get_tag(
+    State(app_state): State<AppState>,
+    Path(tag_id): Path<Uuid>,
+) -> Result<Json<Tag>, AppError> {
+    let tag = app_state.repository.get_tag(tag_id).await?;
+    Ok(Json(tag))
+}
+
+/// Rename or recolor a tag
+#[tracing::instrument(skip_all, fields(tag_id = %tag_id))]
+pub async fn update_tag(
+    State(app_state): State<AppState>,
+    Path(tag_id): Path<Uuid>,
+    Json(payload): Json<UpdateTagPayload>,
+) -> Result<Json<Tag>, AppError> {
+    tracing::info!("Updating tag with id: {}", tag_id);
+
+    let name = payload.name.as_deref().map(validate_tag_name).transpose()?;
+    let color = match &payload.color {
+        Some(Some(color)) => Some(Some(validate_color(color)?)),
+        Some(None) => Some(None),
+        None => None,
+    };
+    let changes = TagChanges {
+        name: name.as_deref(),
+        color: color.as_ref().map(Option::as_deref),
+    };
+    if changes.is_empty() {
+        return Err(AppError::NoFieldsToUpdate);
+    }
+
+    if let Some(name) = &name {
+        let existing = app_state.repository.list_tags(ANONYMOUS_USER_ID).await?;
+        if existing.iter().any(|tag| tag.name == *name && tag.id != tag_id) {
+            return Err(AppError::ValidationError("A tag with this name already exists".to_string()));
+        }
+    }
+
+    let tag = app_state
+        .repository
+        .update_tag(tag_id, &changes)
+        .await
+        .map_err(|error| match error {
+            error if is_duplicate_name(&error) => {
+                AppError::ValidationError("A tag with this name already exists".to_string())
+            }
+            error => error.into(),
+        })?;
+
+    Ok(Json(tag))
+}
+
+/// Delete a tag, taking it off every task
+#[tracing::instrument(skip_all, fields(tag_id = %tag_id))]
+pub async fn delete_tag(
+    State(app_state): State<AppState>,
+    Path(tag_id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    tracing::info!("Deleting tag with id: {}", tag_id);
+
+    if !app_state.repository.delete_tag(tag_id).await? {
+        return Err(AppError::NotFound);
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// List the user's projects in order, leaving out archived ones unless asked
+#[tracing::instrument(skip_all)]
+pub async fn list_projects(
+    State(app_state): State<AppState>,
+    Query(params): Query<ProjectListParams>,
+) -> Result<Json<Vec<Project>>, AppError> {
+    tracing::info!("Listing projects (include_archived: {})", params.include_archived);
+
+    let mut projects = app_state.repository.list_projects(ANONYMOUS_USER_ID).await?;
+    if !params.include_archived {
+        projects.retain(|project| !project.archived);
+    }
+    Ok(Json(projects))
+}
+
+/// Create a project, after the user's others unless given a position
+#[tracing::instrument(skip_all)]
+pub async fn create_project(
+    State(app_state): State<AppState>,
+    Json(payload): Json<CreateProjectPayload>,
+) -> Result<(StatusCode, Json<Project>), AppError> {
+    let name = validate_project_name(&payload.name)?;
+    let color = payload.color.as_deref().map(validate_color).transpose()?;
+    tracing::info!("Creating project {}", redact::text(&name));
+
+    let existing = app_state.repository.list_projects(ANONYMOUS_USER_ID).await?;
+    if existing.iter().any(|project| project.name == name) {
+        return Err(AppError::ValidationError("A project with this name already exists".to_string()));
+    }
+    let position = payload.position.unwrap_or_else(|| {
+        existing
+            .iter()
+            .map(|project| project.position.saturating_add(1))
+            .max()
+            .unwrap_or_default()
+    });
+
+    let project = app_state
+        .repository
+        .create_project(&NewProject {
+            id: Uuid::now_v7(),
+            user_id: ANONYMOUS_USER_ID,
+            name: &name,
+            color: color.as_deref(),
+            position,
+        })
+        .await
+        .map_err(|error| match error {
+            // Lost a race with a concurrent request for the same name
+            error if is_duplicate_name(&error) => {
+                AppError::ValidationError("A project with this name already exists".to_string())
+            }
+            error => error.into(),
+        })?;
+
+    Ok((StatusCode::CREATED, Json(project)))
+}
+
+/// Get a project by ID
+#[tracing::instrument(skip_all, fields(project_id = %project_id))]
+pub async fn get_project(
+    State(app_state): State<AppState>,
+    Path(project_id): Path<Uuid>,
+) -> Result<Json<Project>, AppError> {
+    let project = app_state.repository.get_project(project_id).await?;
+    Ok(Json(project))
+}
+
+/// Rename, recolor, archive or reorder a project
+#[tracing::instrument(skip_all, fields(project_id = %project_id))]
+pub async fn update_project(
+    State(app_state): State<AppState>,
+    Path(project_id): Path<Uuid>,
+    Json(payload): Json<UpdateProjectPayload>,
+) -> Result<Json<Project>, AppError> {
+    tracing::info!("Updating project with id: {}", project_id);
+
+    let name = payload.name.as_deref().map(validate_project_name).transpose()?;
+    let color = match &payload.color {
+        Some(Some(color)) => Some(Some(validate_color(color)?)),
+        Some(None) => Some(None),
+        None => None,
+    };
+    let changes = ProjectChanges {
+        name: name.as_deref(),
+        color: color.as_ref().map(Option::as_deref),
+        archived: payload.archived,
+        position: payload.position,
+    };
+    if changes.is_empty() {
+        return Err(AppError::NoFieldsToUpdate);
+    }
+
+    if let Some(name) = &name {
+        let existing = app_state.repository.list_projects(ANONYMOUS_USER_ID).await?;
+        if existing.iter().any(|project| project.name == *name && project.id != project_id) {
+            return Err(AppError::ValidationError("A project with this name already exists".to_string()));
+        }
+    }
+
+    let project = app_state
+        .repository
+        .update_project(project_id, &changes)
+        .await
+        .map_err(|error| match error {
+            error if is_duplicate_name(&error) => {
+                AppError::ValidationError("A project with this name already exists".to_string())
+            }
+            error => error.into(),
+        })?;
+
+    Ok(Json(project))
+}
+
+/// Delete a project; its tasks are kept, without a project
+#[tracing::instrument(skip_all, fields(project_id = %project_id))]
+pub async fn delete_project(
+    State(app_state): State<AppState>,
+    Path(project_id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    tracing::info!("Deleting project with id: {}", project_id);
+
+    if !app_state.repository.delete_project(project_id).await? {
+        return Err(AppError::NotFound);
+    }
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// List a project's tasks, with the same pagination, search, filters and
+/// sorting as `GET /tasks`
+#[tracing::instrument(skip_all, fields(project_id = %project_id))]
+pub async fn list_project_tasks(
+    State(app_state): State<AppState>,
+    auth_user: Option<Extension<AuthUser>>,
+    Path(project_id): Path<Uuid>,
+    Query(query_params): Query<TaskQueryParams>,
+) -> Result<Json<PaginatedResponse<Task>>, AppError> {
+    // 404 rather than an empty page for a project that does not exist
+    app_state.repository.get_project(project_id).await?;
+    search_tasks(&app_state, auth_user.as_deref(), query_params, |filter| filter.in_project(project_id)).await
+}
+
 /// Register a new user
+#[tracing::instrument(skip_all)]
 pub async fn register(
     State(app_state): State<AppState>,
     Json(payload): Json<RegisterPayload>,
 ) -> Result<Json<AuthResponse>, AppError> {
-    tracing::info!("Registering new user with email: {}", payload.email);
+    tracing::info!("Registering new user with email: {}", redact::email(&payload.email));
 
+    let result = register_user(&app_state, payload).await;
+    crate::metrics::record_auth_attempt("register", crate::metrics::auth_outcome(&result));
+    result
+}
+
+async fn register_user(
+    app_state: &AppState,
+    payload: RegisterPayload,
+) -> Result<Json<AuthResponse>, AppError> {
     // Validate email format (basic validation)
     if !payload.email.contains('@') || payload.email.len() > 255 {
         return Err(AppError::ValidationError("Invalid email format".to_string()));
@@ -314,8 +1898,13 @@ pub async fn register(
         ));
     }
 
+    let time_zone = match payload.time_zone.as_deref() {
+        Some(name) => validate_time_zone(name)?,
+        None => schedule::DEFAULT_TIME_ZONE,
+    };
+
     // Check if user already exists
-    let existing_user = crate::db::get_user_by_email(&app_state.pool, &payload.email).await?;
+    let existing_user = app_state.repository.get_user_by_email(&payload.email).await?;
     if existing_user.is_some() {
         return Err(AppError::ValidationError(
             "User with this email already exists".to_string(),
@@ -327,29 +1916,35 @@ pub async fn register(
         .map_err(|_| AppError::InternalServerError("Failed to hash password".to_string()))?;
 
     // Create the user
-    let user_id = Uuid::new_v4();
-    let user = crate::db::create_user(&app_state.pool, user_id, &payload.email, &password_hash).await?;
+    let user_id = Uuid::now_v7();
+    let user = app_state
+        .repository
+        .create_user(user_id, &payload.email, &password_hash, time_zone)
+        .await
+        .map_err(|error| match error {
+            // Lost a race with a concurrent registration for the same email
+            sqlx::Error::Database(db_error) if db_error.is_unique_violation() => {
+                AppError::ValidationError("User with this email already exists".to_string())
+            }
+            error => error.into(),
+        })?;
 
     // Create a session
-    let session_id = Uuid::new_v4();
-    let session_token = generate_session_token();
-    let token_hash = hash_session_token(&session_token);
-    let expires_at = chrono::Utc::now() + app_state.auth_service.get_token_expiry_duration();
-
-    let _session = crate::db::create_session(
-        &app_state.pool,
-        session_id,
-        user_id,
-        &token_hash,
-        expires_at,
-    ).await?;
-
-    // Generate JWT
+    let session_id = Uuid::now_v7();
     let jwt_token = app_state
         .auth_service
         .generate_token(user_id, session_id)
         .map_err(|_| AppError::InternalServerError("Failed to generate token".to_string()))?;
 
+    // The session is looked up by the hash of the token the client presents
+    let token_hash = hash_session_token(&jwt_token);
+    let expires_at = chrono::Utc::now() + app_state.auth_service.get_token_expiry_duration();
+
+    let _session = app_state
+        .repository
+        .create_session(session_id, user_id, &token_hash, expires_at)
+        .await?;
+
     tracing::info!("User registered successfully");
     Ok(Json(AuthResponse {
         token: jwt_token,
@@ -358,14 +1953,26 @@ pub async fn register(
 }
 
 /// Login a user
+#[tracing::instrument(skip_all)]
 pub async fn login(
     State(app_state): State<AppState>,
     Json(payload): Json<LoginPayload>,
 ) -> Result<Json<AuthResponse>, AppError> {
-    tracing::info!("User login attempt for email: {}", payload.email);
+    tracing::info!("User login attempt for email: {}", redact::email(&payload.email));
 
+    let result = login_user(&app_state, payload).await;
+    crate::metrics::record_auth_attempt("login", crate::metrics::auth_outcome(&result));
+    result
+}
+
+async fn login_user(
+    app_state: &AppState,
+    payload: LoginPayload,
+) -> Result<Json<AuthResponse>, AppError> {
     // Get user by email
-    let user = crate::db::get_user_by_email(&app_state.pool, &payload.email)
+    let user = app_state
+        .repository
+        .get_user_by_email(&payload.email)
         .await?
         .ok_or(AppError::Unauthorized("Invalid credentials".to_string()))?;
 
@@ -378,25 +1985,21 @@ pub async fn login(
     }
 
     // Create a new session
-    let session_id = Uuid::new_v4();
-    let session_token = generate_session_token();
-    let token_hash = hash_session_token(&session_token);
-    let expires_at = chrono::Utc::now() + app_state.auth_service.get_token_expiry_duration();
-
-    let _session = crate::db::create_session(
-        &app_state.pool,
-        session_id,
-        user.id,
-        &token_hash,
-        expires_at,
-    ).await?;
-
-    // Generate JWT
+    let session_id = Uuid::now_v7();
     let jwt_token = app_state
         .auth_service
         .generate_token(user.id, session_id)
         .map_err(|_| AppError::InternalServerError("Failed to generate token".to_string()))?;
 
+    // The session is looked up by the hash of the token the client presents
+    let token_hash = hash_session_token(&jwt_token);
+    let expires_at = chrono::Utc::now() + app_state.auth_service.get_token_expiry_duration();
+
+    let _session = app_state
+        .repository
+        .create_session(session_id, user.id, &token_hash, expires_at)
+        .await?;
+
     tracing::info!("User logged in successfully");
     Ok(Json(AuthResponse {
         token: jwt_token,
@@ -404,30 +2007,60 @@ pub async fn login(
     }))
 }
 
-/// Logout a user (requires auth in real implementation)
+/// Logout user by ending the session their token belongs to
+#[tracing::instrument(skip_all)]
 pub async fn logout(
-    State(_app_state): State<AppState>,
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
 ) -> Result<StatusCode, AppError> {
     tracing::info!("User logout endpoint called");
 
-    // TODO: Extract user from auth middleware and delete session
-    // crate::db::delete_session(&app_state.pool, auth_user.session_id).await?;
+    app_state.repository.delete_session(auth_user.session_id).await?;
 
     tracing::info!("User logged out successfully");
     Ok(StatusCode::NO_CONTENT)
 }
 
-/// Get current user profile (requires auth in real implementation)
+/// Get the authenticated user's profile
+#[tracing::instrument(skip_all)]
 pub async fn get_profile(
-    State(_app_state): State<AppState>,
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
 ) -> Result<Json<UserResponse>, AppError> {
     tracing::info!("Get profile endpoint called");
 
-    // TODO: Extract user from auth middleware and return user data
-    // let user = crate::db::get_user_by_id(&app_state.pool, auth_user.user_id).await?;
-    // Ok(Json(UserResponse::from(user)))
-    
-    Err(AppError::Unauthorized("Authentication middleware not fully implemented".to_string()))
+    let user = app_state.repository.get_user_by_id(auth_user.user_id).await?;
+    Ok(Json(UserResponse::from(user)))
+}
+
+/// Update the authenticated user's profile
+#[tracing::instrument(skip_all)]
+pub async fn update_profile(
+    State(app_state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(payload): Json<UpdateProfilePayload>,
+) -> Result<Json<UserResponse>, AppError> {
+    tracing::info!("Update profile endpoint called");
+
+    let time_zone = match payload.time_zone.as_deref() {
+        Some(name) => validate_time_zone(name)?,
+        None => return Err(AppError::NoFieldsToUpdate),
+    };
+
+    let user = app_state
+        .repository
+        .update_user_time_zone(auth_user.user_id, time_zone)
+        .await?;
+    tracing::info!("Updated time zone for user {} to {}", auth_user.user_id, time_zone);
+    Ok(Json(UserResponse::from(user)))
+}
+
+/// Create health routes (`/health`, `/health/live`, `/health/ready`)
+pub fn health_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(health_check))
+        .route("/live", get(liveness))
+        .route("/ready", get(readiness))
 }
 
 /// Create task routes
@@ -435,6 +2068,36 @@ pub fn task_routes() -> Router<AppState> {
     Router::new()
         .route("/", post(create_task).get(list_tasks))
         .route("/{id}", get(get_task).put(update_task).delete(delete_task))
+        .route("/{id}/children", get(list_subtasks))
+        .route("/{id}/move", post(move_task))
+        .route("/{id}/occurrences", get(list_occurrences))
+        .route("/{id}/blockers", get(list_blockers).post(add_blocker))
+        .route("/{id}/blockers/{blocker_id}", delete(remove_blocker))
+        .route("/{id}/reminders", get(list_reminders).post(create_reminder))
+        .route("/{id}/reminders/{reminder_id}", delete(delete_reminder))
+        .route("/{id}/reminders/{reminder_id}/snooze", post(snooze_reminder))
+}
+
+/// Create tag routes
+pub fn tag_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", post(create_tag).get(list_tags))
+        .route("/{id}", get(get_tag).put(update_tag).delete(delete_tag))
+}
+
+/// Create project routes
+pub fn project_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", post(create_project).get(list_projects))
+        .route("/{id}", get(get_project).put(update_project).delete(delete_project))
+        .route("/{id}/tasks", get(list_project_tasks))
+}
+
+/// Create notification routes (`/notifications`)
+pub fn notification_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_notifications))
+        .route("/{id}/read", post(mark_notification_read))
 }
 
 /// Create public auth routes (no authentication required)
@@ -445,8 +2108,9 @@ pub fn public_auth_routes() -> Router<AppState> {
 }
 
 /// Create protected auth routes (authentication required)
-pub fn protected_auth_routes() -> Router<AppState> {
+pub fn protected_auth_routes(app_state: AppState) -> Router<AppState> {
     Router::new()
         .route("/logout", post(logout))
-        .route("/profile", get(get_profile))
+        .route("/profile", get(get_profile).patch(update_profile))
+        .route_layer(from_fn_with_state(app_state, auth_middleware))
 }
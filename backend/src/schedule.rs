@@ -0,0 +1,141 @@
+//! Calendar dates in a user's time zone.
+//!
+//! Due and start dates may be given as a calendar date (`2026-03-01`) or an
+//! RFC 3339 timestamp. Everything is stored as UTC instants; a date stands for
+//! the moment that day starts in the user's time zone, so "today" and
+//! "overdue" follow the user's clock rather than the server's.
+
+use chrono::{DateTime, Days, NaiveDate, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+
+/// Time zone of users who have not chosen one
+pub const DEFAULT_TIME_ZONE: &str = "UTC";
+
+/// A calendar date or an exact instant, as accepted in payloads and filters
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateOrTime {
+    Date(NaiveDate),
+    Time(DateTime<Utc>),
+}
+
+impl DateOrTime {
+    /// Parse `YYYY-MM-DD` or an RFC 3339 timestamp
+    pub fn parse(value: &str) -> Option<Self> {
+        if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+            return Some(DateOrTime::Time(time.with_timezone(&Utc)));
+        }
+        NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .ok()
+            .map(DateOrTime::Date)
+    }
+}
+
+/// When a task is due. All-day tasks are due on a date rather than at a time;
+/// they are stored as the start of that date and are overdue once it has passed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Due {
+    pub at: DateTime<Utc>,
+    pub all_day: bool,
+}
+
+/// Parse an IANA time zone name such as `Europe/Paris`
+pub fn parse_time_zone(name: &str) -> Option<Tz> {
+    name.parse().ok()
+}
+
+/// Dates as seen by one user at one moment
+#[derive(Debug, Clone, Copy)]
+pub struct Calendar {
+    pub time_zone: Tz,
+    pub now: DateTime<Utc>,
+}
+
+impl Calendar {
+    /// The calendar of a user in `time_zone` (a stored preference) right now.
+    /// Unknown names fall back to UTC.
+    pub fn for_time_zone(time_zone: &str) -> Self {
+        Self {
+            time_zone: parse_time_zone(time_zone).unwrap_or(Tz::UTC),
+            now: Utc::now(),
+        }
+    }
+
+    pub fn today(&self) -> NaiveDate {
+        self.now.with_timezone(&self.time_zone).date_naive()
+    }
+
+    /// The instant `date` begins. Where a daylight saving change skips
+    /// midnight, the day begins at the first hour that exists.
+    pub fn start_of(&self, date: NaiveDate) -> DateTime<Utc> {
+        (0..24)
+            .filter_map(|hour| {
+                let time = NaiveTime::from_hms_opt(hour, 0, 0)?;
+                self.time_zone
+                    .from_local_datetime(&date.and_time(time))
+                    .earliest()
+            })
+            .next()
+            .map(|start| start.with_timezone(&Utc))
+            .unwrap_or_else(|| date.and_time(NaiveTime::MIN).and_utc())
+    }
+
+    /// The instant the day after `date` begins
+    pub fn end_of(&self, date: NaiveDate) -> DateTime<Utc> {
+        self.start_of(date.checked_add_days(Days::new(1)).unwrap_or(date))
+    }
+
+    /// The instant a date or time stands for: the start of a date
+    pub fn instant(&self, value: DateOrTime) -> DateTime<Utc> {
+        match value {
+            DateOrTime::Date(date) => self.start_of(date),
+            DateOrTime::Time(time) => time,
+        }
+    }
+
+    /// When a task given `value` as its due date is due; dates are due all day
+    pub fn due(&self, value: DateOrTime) -> Due {
+        Due {
+            at: self.instant(value),
+            all_day: matches!(value, DateOrTime::Date(_)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dates_start_in_the_users_time_zone() {
+        let calendar = Calendar {
+            time_zone: parse_time_zone("America/New_York").unwrap(),
+            now: Utc.with_ymd_and_hms(2026, 3, 2, 3, 0, 0).unwrap(),
+        };
+        // Still March 1st in New York
+        assert_eq!(calendar.today(), NaiveDate::from_ymd_opt(2026, 3, 1).unwrap());
+        assert_eq!(
+            calendar.start_of(calendar.today()),
+            Utc.with_ymd_and_hms(2026, 3, 1, 5, 0, 0).unwrap()
+        );
+
+        let due = calendar.due(DateOrTime::parse("2026-03-01").unwrap());
+        assert!(due.all_day);
+        assert_eq!(due.at, Utc.with_ymd_and_hms(2026, 3, 1, 5, 0, 0).unwrap());
+        let due = calendar.due(DateOrTime::parse("2026-03-01T09:30:00-05:00").unwrap());
+        assert!(!due.all_day);
+        assert_eq!(due.at, Utc.with_ymd_and_hms(2026, 3, 1, 14, 30, 0).unwrap());
+        assert_eq!(DateOrTime::parse("tomorrow"), None);
+    }
+
+    #[test]
+    fn test_days_skipping_midnight_start_at_the_first_hour() {
+        // Santiago moved clocks from midnight to 1am on 2026-09-06
+        let calendar = Calendar::for_time_zone("America/Santiago");
+        let date = NaiveDate::from_ymd_opt(2026, 9, 6).unwrap();
+        assert_eq!(
+            calendar.start_of(date),
+            Utc.with_ymd_and_hms(2026, 9, 6, 4, 0, 0).unwrap()
+        );
+        assert_eq!(Calendar::for_time_zone("Mars/Olympus").time_zone, Tz::UTC);
+    }
+}
@@ -0,0 +1,413 @@
+//! Full-text search queries.
+//!
+//! Queries use the boolean syntax of MySQL's `MATCH ... AGAINST`: plain words
+//! are optional, `+word` is required, `-word` is excluded, `word*` matches a
+//! prefix and `"two words"` matches a phrase. When a query has required words,
+//! optional words only affect ranking. A parsed [`SearchQuery`] is translated
+//! into each backend's own syntax, so user input is never spliced into SQL.
+
+/// Whether a term must, may or must not appear
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Occurrence {
+    Optional,
+    Required,
+    Excluded,
+}
+
+/// A word, prefix or phrase in a search query
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Term {
+    /// Lowercase words; more than one makes a phrase
+    pub words: Vec<String>,
+    pub occurrence: Occurrence,
+    /// Match words starting with the last word
+    pub prefix: bool,
+}
+
+/// A parsed full-text search query
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SearchQuery {
+    pub terms: Vec<Term>,
+}
+
+/// Words of a description shown in a snippet, and how many of them come
+/// before the first match
+const SNIPPET_WORDS: usize = 24;
+const SNIPPET_WORDS_BEFORE: usize = 6;
+
+/// Split text into lowercase words the way the backends' tokenizers do
+fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+impl SearchQuery {
+    pub fn parse(query: &str) -> Self {
+        let mut terms = Vec::new();
+        let mut rest = query.trim_start();
+
+        while !rest.is_empty() {
+            let occurrence = match rest.chars().next() {
+                Some('+') => Occurrence::Required,
+                Some('-') => Occurrence::Excluded,
+                _ => Occurrence::Optional,
+            };
+            if occurrence != Occurrence::Optional {
+                rest = &rest[1..];
+            }
+
+            // Phrases run to the closing quote, words to the next space
+            let (text, after, quoted) = match rest.strip_prefix('"') {
+                Some(quoted) => match quoted.find('"') {
+                    Some(end) => (&quoted[..end], &quoted[end + 1..], true),
+                    None => (quoted, "", true),
+                },
+                None => match rest.find(char::is_whitespace) {
+                    Some(end) => (&rest[..end], &rest[end..], false),
+                    None => (rest, "", false),
+                },
+            };
+            rest = after.trim_start();
+
+            let prefix = !quoted && text.ends_with('*');
+            let words = words(text);
+            if !words.is_empty() {
+                terms.push(Term {
+                    words,
+                    occurrence,
+                    prefix,
+                });
+            }
+        }
+
+        Self { terms }
+    }
+
+    fn with(&self, occurrence: Occurrence) -> impl Iterator<Item = &Term> {
+        self.terms
+            .iter()
+            .filter(move |term| term.occurrence == occurrence)
+    }
+
+    /// Whether the query can match anything; a query of only excluded words cannot
+    pub fn has_positive_terms(&self) -> bool {
+        self.terms
+            .iter()
+            .any(|term| term.occurrence != Occurrence::Excluded)
+    }
+
+    /// The terms that decide whether a row matches: the required ones, or the
+    /// optional ones when nothing is required
+    fn matching_terms(&self) -> Vec<&Term> {
+        let required: Vec<&Term> = self.with(Occurrence::Required).collect();
+        if required.is_empty() {
+            self.with(Occurrence::Optional).collect()
+        } else {
+            required
+        }
+    }
+
+    /// The query to score matching rows with: every required and optional term
+    /// becomes optional, so each one found raises the score
+    pub fn ranking(&self) -> Self {
+        let terms = self
+            .terms
+            .iter()
+            .filter(|term| term.occurrence != Occurrence::Excluded)
+            .map(|term| Term {
+                occurrence: Occurrence::Optional,
+                ..term.clone()
+            })
+            .collect();
+        Self { terms }
+    }
+
+    /// Query for MySQL's `MATCH ... AGAINST (... IN BOOLEAN MODE)`
+    pub fn to_mysql(&self) -> String {
+        self.terms
+            .iter()
+            .map(|term| {
+                let operator = match term.occurrence {
+                    Occurrence::Optional => "",
+                    Occurrence::Required => "+",
+                    Occurrence::Excluded => "-",
+                };
+                let text = if term.words.len() > 1 {
+                    format!("\"{}\"", term.words.join(" "))
+                } else {
+                    term.words[0].clone()
+                };
+                // MySQL has no prefix phrases
+                let prefix = if term.prefix && term.words.len() == 1 { "*" } else { "" };
+                format!("{operator}{text}{prefix}")
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Query for PostgreSQL's `to_tsquery('simple', ...)`
+    pub fn to_tsquery(&self) -> String {
+        let lexeme = |term: &Term| {
+            let last = term.words.len() - 1;
+            let words: Vec<String> = term
+                .words
+                .iter()
+                .enumerate()
+                .map(|(index, word)| {
+                    let prefix = if term.prefix && index == last { ":*" } else { "" };
+                    format!("'{}'{prefix}", word)
+                })
+                .collect();
+            format!("({})", words.join(" <-> "))
+        };
+
+        let matching: Vec<String> = self.matching_terms().into_iter().map(lexeme).collect();
+        let operator = if self.with(Occurrence::Required).next().is_some() {
+            " & "
+        } else {
+            " | "
+        };
+        let mut query = format!("({})", matching.join(operator));
+        for term in self.with(Occurrence::Excluded) {
+            query.push_str(&format!(" & !{}", lexeme(term)));
+        }
+        query
+    }
+
+    /// Query for an SQLite FTS5 `MATCH`
+    pub fn to_fts5(&self) -> String {
+        let phrase = |term: &Term| {
+            let prefix = if term.prefix { "*" } else { "" };
+            format!("\"{}\"{prefix}", term.words.join(" "))
+        };
+
+        let matching: Vec<String> = self.matching_terms().into_iter().map(phrase).collect();
+        let operator = if self.with(Occurrence::Required).next().is_some() {
+            " AND "
+        } else {
+            " OR "
+        };
+        let mut query = format!("({})", matching.join(operator));
+        for term in self.with(Occurrence::Excluded) {
+            query.push_str(&format!(" NOT {}", phrase(term)));
+        }
+        query
+    }
+
+    /// Relevance of `text` to this query, or `None` when it does not match.
+    /// The score is the number of required and optional terms found.
+    pub fn score(&self, text: &str) -> Option<f64> {
+        let words = words(text);
+        let found = |term: &Term| term_positions(term, &words).next().is_some();
+
+        if self.with(Occurrence::Excluded).any(found) {
+            return None;
+        }
+        let matching = self.matching_terms();
+        let matches = if self.with(Occurrence::Required).next().is_some() {
+            matching.iter().all(|term| found(term))
+        } else {
+            matching.iter().any(|term| found(term))
+        };
+        if !matches {
+            return None;
+        }
+
+        let score = self
+            .terms
+            .iter()
+            .filter(|term| term.occurrence != Occurrence::Excluded && found(term))
+            .count();
+        Some(score as f64)
+    }
+
+    /// Byte ranges of the words in `text`, and which of them match this query
+    fn marked_words(&self, text: &str) -> (Vec<(usize, usize)>, Vec<bool>) {
+        let mut spans = Vec::new();
+        let mut start = None;
+        for (index, c) in text.char_indices().chain([(text.len(), ' ')]) {
+            match (c.is_alphanumeric(), start) {
+                (true, None) => start = Some(index),
+                (false, Some(begin)) => {
+                    spans.push((begin, index));
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+        let words: Vec<String> = spans
+            .iter()
+            .map(|&(begin, end)| text[begin..end].to_lowercase())
+            .collect();
+
+        let mut marked = vec![false; words.len()];
+        for term in self.with(Occurrence::Required).chain(self.with(Occurrence::Optional)) {
+            for position in term_positions(term, &words) {
+                marked[position..position + term.words.len()].fill(true);
+            }
+        }
+        (spans, marked)
+    }
+
+    /// `text` as HTML with the words matching this query wrapped in `<mark>`
+    pub fn highlight(&self, text: &str) -> String {
+        let (spans, marked) = self.marked_words(text);
+
+        let mut html = String::with_capacity(text.len());
+        let mut cursor = 0;
+        for (&(begin, end), marked) in spans.iter().zip(marked) {
+            if marked {
+                html.push_str(&escape_html(&text[cursor..begin]));
+                html.push_str("<mark>");
+                html.push_str(&escape_html(&text[begin..end]));
+                html.push_str("</mark>");
+                cursor = end;
+            }
+        }
+        html.push_str(&escape_html(&text[cursor..]));
+        html
+    }
+
+    /// A highlighted snippet for a matching task: its title when the title
+    /// matches, otherwise the words of its description around the first match
+    pub fn snippet(&self, title: &str, description: Option<&str>) -> String {
+        let title_matches = self.marked_words(title).1.contains(&true);
+        let Some(description) = description.filter(|_| !title_matches) else {
+            return self.highlight(title);
+        };
+        let (spans, marked) = self.marked_words(description);
+        let Some(first) = marked.iter().position(|marked| *marked) else {
+            return self.highlight(title);
+        };
+
+        let start = first.saturating_sub(SNIPPET_WORDS_BEFORE);
+        let end = spans.len().min(start + SNIPPET_WORDS);
+        let excerpt = description[spans[start].0..spans[end - 1].1]
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        let mut html = self.highlight(&excerpt);
+        if start > 0 {
+            html.insert(0, '…');
+        }
+        if end < spans.len() {
+            html.push('…');
+        }
+        html
+    }
+}
+
+/// Indexes into `words` where `term` starts
+fn term_positions<'a>(term: &'a Term, words: &'a [String]) -> impl Iterator<Item = usize> + 'a {
+    let length = term.words.len();
+    (0..words.len().saturating_sub(length - 1)).filter(move |&start| {
+        term.words.iter().enumerate().all(|(offset, word)| {
+            let candidate = &words[start + offset];
+            if term.prefix && offset == length - 1 {
+                candidate.starts_with(word.as_str())
+            } else {
+                candidate == word
+            }
+        })
+    })
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_operators() {
+        let query = SearchQuery::parse(r#"+milk -oat choc* "corner shop" ;--"#);
+        assert_eq!(
+            query.terms,
+            vec![
+                Term {
+                    words: vec!["milk".to_string()],
+                    occurrence: Occurrence::Required,
+                    prefix: false,
+                },
+                Term {
+                    words: vec!["oat".to_string()],
+                    occurrence: Occurrence::Excluded,
+                    prefix: false,
+                },
+                Term {
+                    words: vec!["choc".to_string()],
+                    occurrence: Occurrence::Optional,
+                    prefix: true,
+                },
+                Term {
+                    words: vec!["corner".to_string(), "shop".to_string()],
+                    occurrence: Occurrence::Optional,
+                    prefix: false,
+                },
+            ]
+        );
+        assert!(!SearchQuery::parse("-milk").has_positive_terms());
+    }
+
+    #[test]
+    fn test_backend_syntax() {
+        let query = SearchQuery::parse(r#"+milk -oat choc* "corner shop""#);
+        assert_eq!(query.to_mysql(), r#"+milk -oat choc* "corner shop""#);
+        assert_eq!(query.to_tsquery(), "(('milk')) & !('oat')");
+        assert_eq!(query.to_fts5(), r#"("milk") NOT "oat""#);
+
+        let query = SearchQuery::parse("buy choc*");
+        assert_eq!(query.to_tsquery(), "(('buy') | ('choc':*))");
+        assert_eq!(query.to_fts5(), r#"("buy" OR "choc"*)"#);
+    }
+
+    #[test]
+    fn test_score_follows_boolean_semantics() {
+        let query = SearchQuery::parse("+milk chocolate -oat");
+        assert_eq!(query.score("Buy milk"), Some(1.0));
+        assert_eq!(query.score("Buy chocolate milk"), Some(2.0));
+        assert_eq!(query.score("Buy chocolate"), None);
+        assert_eq!(query.score("Buy oat milk"), None);
+
+        let query = SearchQuery::parse(r#""corner shop" choc*"#);
+        assert_eq!(query.score("Chocolates"), Some(1.0));
+        assert_eq!(query.score("Go to the corner shop"), Some(1.0));
+        assert_eq!(query.score("Shop at the corner"), None);
+    }
+
+    #[test]
+    fn test_highlight_marks_matches_and_escapes() {
+        let query = SearchQuery::parse("milk choc*");
+        assert_eq!(
+            query.highlight("Milk & <b>Chocolate</b>"),
+            "<mark>Milk</mark> &amp; &lt;b&gt;<mark>Chocolate</mark>&lt;/b&gt;"
+        );
+    }
+
+    #[test]
+    fn test_snippet_falls_back_to_the_description() {
+        let query = SearchQuery::parse("oat*");
+        assert_eq!(query.snippet("Oats", Some("Porridge oats")), "<mark>Oats</mark>");
+        assert_eq!(
+            query.snippet("Groceries", Some("Milk\n\n- oat *milk*\n- bread")),
+            "Milk - <mark>oat</mark> *milk* - bread"
+        );
+        assert_eq!(query.snippet("Groceries", None), "Groceries");
+
+        let description = (1..=40).map(|n| format!("w{}", n)).collect::<Vec<_>>().join(" ");
+        let query = SearchQuery::parse("w20");
+        let snippet = query.snippet("Words", Some(&description));
+        assert!(snippet.starts_with("…w14 "), "{}", snippet);
+        assert!(snippet.contains("<mark>w20</mark>"), "{}", snippet);
+        assert!(snippet.ends_with(" w37…"), "{}", snippet);
+    }
+}
@@ -0,0 +1,200 @@
+use axum::{http::Request, middleware::Next, response::Response};
+use opentelemetry::{global, trace::TracerProvider as _, Context};
+use opentelemetry_http::{HeaderExtractor, HeaderInjector};
+use opentelemetry_sdk::{
+    propagation::TraceContextPropagator,
+    trace::{SdkTracer, SdkTracerProvider},
+    Resource,
+};
+use std::env;
+use tracing::Span;
+use tracing_opentelemetry::{OpenTelemetryLayer, OpenTelemetrySpanExt};
+
+/// Service name reported when `OTEL_SERVICE_NAME` is not set
+const DEFAULT_SERVICE_NAME: &str = "backend";
+
+/// Owns the tracer provider so buffered spans can be flushed on shutdown
+pub struct Telemetry {
+    provider: Option<SdkTracerProvider>,
+}
+
+impl Telemetry {
+    /// Set up OTLP trace export from the standard `OTEL_*` environment variables.
+    ///
+    /// Export is enabled when `OTEL_EXPORTER_OTLP_ENDPOINT`,
+    /// `OTEL_EXPORTER_OTLP_TRACES_ENDPOINT` or `OTEL_TRACES_EXPORTER=otlp` is set,
+    /// and disabled by `OTEL_SDK_DISABLED=true` or `OTEL_TRACES_EXPORTER=none`.
+    /// The W3C trace context propagator is installed either way.
+    pub fn from_env() -> Self {
+        global::set_text_map_propagator(TraceContextPropagator::new());
+
+        if !otlp_enabled() {
+            return Self { provider: None };
+        }
+
+        // The exporter reads the endpoint, headers and timeout from `OTEL_EXPORTER_OTLP_*`
+        let exporter = match opentelemetry_otlp::SpanExporter::builder()
+            .with_http()
+            .build()
+        {
+            Ok(exporter) => exporter,
+            Err(error) => {
+                eprintln!("Failed to create OTLP span exporter, tracing export disabled: {error}");
+                return Self { provider: None };
+            }
+        };
+
+        let service_name =
+            env::var("OTEL_SERVICE_NAME").unwrap_or_else(|_| DEFAULT_SERVICE_NAME.to_string());
+        let provider = SdkTracerProvider::builder()
+            .with_resource(Resource::builder().with_service_name(service_name).build())
+            .with_batch_exporter(exporter)
+            .build();
+
+        global::set_tracer_provider(provider.clone());
+        Self {
+            provider: Some(provider),
+        }
+    }
+
+    /// Whether spans are being exported
+    pub fn is_enabled(&self) -> bool {
+        self.provider.is_some()
+    }
+
+    /// A `tracing` layer that turns spans into OpenTelemetry spans, or `None` when export is disabled
+    pub fn layer<S>(&self) -> Option<OpenTelemetryLayer<S, SdkTracer>>
+    where
+        S: tracing::Subscriber + for<'span> tracing_subscriber::registry::LookupSpan<'span>,
+    {
+        self.provider
+            .as_ref()
+            .map(|provider| tracing_opentelemetry::layer().with_tracer(provider.tracer("backend")))
+    }
+
+    /// Export any buffered spans. Blocks, so call it from a blocking context.
+    pub fn force_flush(&self) {
+        if let Some(provider) = &self.provider {
+            if let Err(error) = provider.force_flush() {
+                tracing::warn!("Failed to flush spans: {}", error);
+            }
+        }
+    }
+
+    /// Flush remaining spans and stop the exporter
+    pub fn shutdown(self) {
+        if let Some(provider) = self.provider {
+            if let Err(error) = provider.shutdown() {
+                eprintln!("Failed to shut down tracer provider: {error}");
+            }
+        }
+    }
+}
+
+/// Decide from the standard environment variables whether OTLP export is on
+fn otlp_enabled() -> bool {
+    otlp_enabled_with(|name| env::var(name).ok())
+}
+
+fn otlp_enabled_with(var: impl Fn(&str) -> Option<String>) -> bool {
+    let is_set = |name: &str| var(name).is_some_and(|value| !value.is_empty());
+
+    if var("OTEL_SDK_DISABLED").is_some_and(|value| value.eq_ignore_ascii_case("true")) {
+        return false;
+    }
+
+    match var("OTEL_TRACES_EXPORTER") {
+        Some(exporter) if exporter.eq_ignore_ascii_case("none") => false,
+        Some(exporter) if exporter.eq_ignore_ascii_case("otlp") => true,
+        _ => {
+            is_set("OTEL_EXPORTER_OTLP_ENDPOINT") || is_set("OTEL_EXPORTER_OTLP_TRACES_ENDPOINT")
+        }
+    }
+}
+
+/// Create the per-request span for `TraceLayer`, tagged with the request ID and
+/// continuing the caller's W3C trace (`traceparent`/`tracestate`) when one is present
+pub fn make_request_span<B>(req: &Request<B>) -> Span {
+    let request_id = req
+        .headers()
+        .get(crate::middleware::REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    let span = tracing::info_span!(
+        "request",
+        method = %req.method(),
+        uri = %req.uri(),
+        version = ?req.version(),
+        request_id = %request_id,
+    );
+
+    let parent = global::get_text_map_propagator(|propagator| {
+        propagator.extract(&HeaderExtractor(req.headers()))
+    });
+    // The parent has to be set before the span is first entered. This only fails
+    // when export is disabled, in which case there is nothing to link.
+    let _ = span.set_parent(parent);
+
+    span
+}
+
+/// Middleware returning the request's trace context in the response headers
+pub async fn propagate_trace_context(req: axum::extract::Request, next: Next) -> Response {
+    let mut response = next.run(req).await;
+    inject_trace_context(&Span::current().context(), response.headers_mut());
+    response
+}
+
+/// Write `context` into `headers` so a downstream service can continue the trace
+pub fn inject_trace_context(context: &Context, headers: &mut axum::http::HeaderMap) {
+    global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(context, &mut HeaderInjector(headers))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn enabled_with(vars: &[(&str, &str)]) -> bool {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        otlp_enabled_with(|name| vars.get(name).cloned())
+    }
+
+    #[test]
+    fn test_export_is_disabled_by_default() {
+        assert!(!enabled_with(&[]));
+    }
+
+    #[test]
+    fn test_endpoint_enables_export() {
+        assert!(enabled_with(&[("OTEL_EXPORTER_OTLP_ENDPOINT", "http://collector:4318")]));
+        assert!(enabled_with(&[(
+            "OTEL_EXPORTER_OTLP_TRACES_ENDPOINT",
+            "http://collector:4318/v1/traces"
+        )]));
+        assert!(!enabled_with(&[("OTEL_EXPORTER_OTLP_ENDPOINT", "")]));
+    }
+
+    #[test]
+    fn test_traces_exporter_selects_export() {
+        assert!(enabled_with(&[("OTEL_TRACES_EXPORTER", "otlp")]));
+        assert!(!enabled_with(&[
+            ("OTEL_TRACES_EXPORTER", "none"),
+            ("OTEL_EXPORTER_OTLP_ENDPOINT", "http://collector:4318"),
+        ]));
+    }
+
+    #[test]
+    fn test_sdk_disabled_wins() {
+        assert!(!enabled_with(&[
+            ("OTEL_SDK_DISABLED", "true"),
+            ("OTEL_TRACES_EXPORTER", "otlp"),
+            ("OTEL_EXPORTER_OTLP_ENDPOINT", "http://collector:4318"),
+        ]));
+    }
+}
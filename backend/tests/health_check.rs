@@ -47,3 +47,84 @@ async fn health_check_returns_200() {
 
     test_app.cleanup().await;
 }
+
+#[tokio::test]
+async fn liveness_returns_ok_without_touching_dependencies() {
+    let test_app = spawn_app().await;
+    let client = reqwest::Client::new();
+
+    let response = client
+        .get(format!("{}/health/live", &test_app.address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    let json_response: serde_json::Value = response.json().await.expect("Failed to parse JSON");
+    assert_eq!(json_response["status"], "ok");
+
+    test_app.cleanup().await;
+}
+
+#[tokio::test]
+async fn readiness_reports_database_and_migration_checks() {
+    let test_app = spawn_app().await;
+    let client = reqwest::Client::new();
+
+    let response = client
+        .get(format!("{}/health/ready", &test_app.address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    let json_response: serde_json::Value = response.json().await.expect("Failed to parse JSON");
+    assert_eq!(json_response["status"], "ok");
+
+    let database = &json_response["checks"]["database"];
+    assert_eq!(database["status"], "ok");
+    assert!(database["latency_ms"].is_u64());
+    assert!(database["pool"]["size"].is_u64());
+    assert!(database["pool"]["idle"].is_u64());
+    assert_eq!(database["pool"]["max_connections"], 3);
+
+    let migrations = &json_response["checks"]["migrations"];
+    assert_eq!(migrations["status"], "ok");
+    assert_eq!(migrations["pending"], json!([]));
+
+    test_app.cleanup().await;
+}
+
+#[tokio::test]
+async fn readiness_returns_503_when_database_is_unavailable() {
+    let test_app = spawn_app().await;
+    let client = reqwest::Client::new();
+
+    // The app shares this pool, so closing it breaks the app's database access
+    test_app.db_pool.close().await;
+
+    let response = client
+        .get(format!("{}/health/ready", &test_app.address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(response.status().as_u16(), 503);
+
+    let json_response: serde_json::Value = response.json().await.expect("Failed to parse JSON");
+    assert_eq!(json_response["status"], "unavailable");
+    assert_eq!(json_response["checks"]["database"]["status"], "error");
+    assert!(json_response["checks"]["database"]["error"].is_string());
+
+    // Liveness is unaffected by dependency failures
+    let response = client
+        .get(format!("{}/health/live", &test_app.address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(response.status().as_u16(), 200);
+
+    test_app.cleanup().await;
+}
@@ -0,0 +1,1410 @@
+//! Handler tests against the in-memory repository, driven through the router
+//! with `oneshot` so they need neither Docker nor a listening socket.
+
+mod common;
+
+use async_trait::async_trait;
+use axum::{
+    body::Body,
+    extract::Path,
+    http::{Request, StatusCode},
+    Json, Router,
+};
+use backend::config::Config;
+use backend::reminders::{DeliveryError, DispatchReport, Email, Mailer, Scheduler};
+use backend::repository::{NewProject, NewTag, ProjectChanges, TagChanges, ANONYMOUS_USER_ID};
+use chrono::{DateTime, SubsecRound, Utc};
+use common::{build_app, in_memory_app, in_memory_app_with, in_memory_state};
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+async fn send(app: &Router, method: &str, uri: &str, body: Option<Value>) -> (StatusCode, Value) {
+    dispatch(app, Request::builder().method(method).uri(uri), body).await
+}
+
+/// Send a request with the bearer token of a registered user
+async fn send_as(
+    app: &Router,
+    token: &str,
+    method: &str,
+    uri: &str,
+    body: Option<Value>,
+) -> (StatusCode, Value) {
+    let request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("authorization", format!("Bearer {}", token));
+    dispatch(app, request, body).await
+}
+
+async fn dispatch(
+    app: &Router,
+    request: axum::http::request::Builder,
+    body: Option<Value>,
+) -> (StatusCode, Value) {
+    let request = match body {
+        Some(body) => request
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string())),
+        None => request.body(Body::empty()),
+    }
+    .unwrap();
+
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let body = if bytes.is_empty() {
+        Value::Null
+    } else {
+        serde_json::from_slice(&bytes).unwrap()
+    };
+
+    (status, body)
+}
+
+async fn create_task(app: &Router, title: &str) -> Value {
+    let (status, task) = send(app, "POST", "/tasks", Some(json!({ "title": title }))).await;
+    assert_eq!(status, StatusCode::CREATED);
+    task
+}
+
+#[tokio::test]
+async fn task_crud_round_trip() {
+    let app = in_memory_app();
+
+    let task = create_task(&app, "  Buy milk  ").await;
+    assert_eq!(task["title"], "Buy milk");
+    assert_eq!(task["completed"], false);
+    let uri = format!("/tasks/{}", task["id"].as_str().unwrap());
+
+    let (status, fetched) = send(&app, "GET", &uri, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(fetched["id"], task["id"]);
+
+    let (status, updated) = send(&app, "PUT", &uri, Some(json!({ "completed": true }))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(updated["completed"], true);
+    assert_eq!(updated["title"], "Buy milk");
+
+    let (status, _) = send(&app, "DELETE", &uri, None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let (status, _) = send(&app, "GET", &uri, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn task_descriptions_render_as_sanitized_html() {
+    let app = in_memory_app();
+    let description = "Pick up **oat** milk <script>alert(1)</script>";
+    let (status, task) = send(
+        &app,
+        "POST",
+        "/tasks",
+        Some(json!({ "title": "Shopping", "description": description })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(task["description"], description);
+    assert!(task.get("description_html").is_none());
+    let uri = format!("/tasks/{}", task["id"].as_str().unwrap());
+
+    let (_, fetched) = send(&app, "GET", &format!("{}?render=html", uri), None).await;
+    assert_eq!(fetched["description_html"], "<p>Pick up <strong>oat</strong> milk </p>\n");
+    let (_, page) = send(&app, "GET", "/tasks?q=oat&render=html", None).await;
+    assert_eq!(page["data"][0]["description_html"], fetched["description_html"]);
+    let (status, _) = send(&app, "GET", &format!("{}?render=pdf", uri), None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // A blank description removes it
+    let (_, updated) = send(&app, "PUT", &uri, Some(json!({ "description": "  " }))).await;
+    assert_eq!(updated["description"], Value::Null);
+
+    let (status, _) = send(
+        &app,
+        "PUT",
+        &uri,
+        Some(json!({ "description": "x".repeat(10_001) })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn invalid_task_input_is_rejected() {
+    let app = in_memory_app();
+
+    let (status, body) = send(&app, "POST", "/tasks", Some(json!({ "title": "   " }))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"], "Task title cannot be empty");
+
+    let long_title = "x".repeat(256);
+    let (status, _) = send(&app, "POST", "/tasks", Some(json!({ "title": long_title }))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let task = create_task(&app, "Valid").await;
+    let uri = format!("/tasks/{}", task["id"].as_str().unwrap());
+    let (status, _) = send(&app, "PUT", &uri, Some(json!({}))).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+    let missing = format!("/tasks/{}", Uuid::new_v4());
+    let (status, _) = send(&app, "PUT", &missing, Some(json!({ "completed": true }))).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = send(&app, "DELETE", &missing, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn list_tasks_paginates() {
+    let app = in_memory_app();
+    for i in 0..25 {
+        create_task(&app, &format!("Task {:02}", i)).await;
+    }
+
+    let (status, page) = send(&app, "GET", "/tasks?page=2&page_size=10", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(page["data"].as_array().unwrap().len(), 10);
+    assert_eq!(page["pagination"]["total_items"], 25);
+    assert_eq!(page["pagination"]["total_pages"], 3);
+    assert_eq!(page["pagination"]["has_next"], true);
+    assert_eq!(page["pagination"]["has_previous"], true);
+
+    let (_, last) = send(&app, "GET", "/tasks?page=3&page_size=10", None).await;
+    assert_eq!(last["data"].as_array().unwrap().len(), 5);
+    assert_eq!(last["pagination"]["has_next"], false);
+}
+
+#[tokio::test]
+async fn list_tasks_totals_can_be_skipped_or_capped() {
+    let mut config = Config::default();
+    config.tasks.total_count_cap = Some(10);
+    let app = in_memory_app_with(config);
+    for i in 0..12 {
+        create_task(&app, &format!("Task {:02}", i)).await;
+    }
+
+    let (_, page) = send(&app, "GET", "/tasks?page_size=5&include_total=false", None).await;
+    assert!(page["pagination"].get("total_items").is_none());
+    assert!(page["pagination"].get("total_pages").is_none());
+    assert_eq!(page["pagination"]["total_exact"], false);
+    assert_eq!(page["pagination"]["has_next"], true);
+
+    // 12 tasks counted against a cap of 10 report "10+"
+    let (_, page) = send(&app, "GET", "/tasks?page=3&page_size=5", None).await;
+    assert_eq!(page["pagination"]["total_items"], 10);
+    assert_eq!(page["pagination"]["total_exact"], false);
+    assert!(page["pagination"].get("total_pages").is_none());
+    assert_eq!(page["data"].as_array().unwrap().len(), 2);
+    assert_eq!(page["pagination"]["has_next"], false);
+
+    let (_, page) = send(&app, "GET", "/tasks?q=Task%200", None).await;
+    assert_eq!(page["pagination"]["total_items"], 10);
+    assert_eq!(page["pagination"]["total_exact"], true);
+    assert_eq!(page["pagination"]["total_pages"], 1);
+}
+
+#[tokio::test]
+async fn list_tasks_pages_by_cursor() {
+    let app = in_memory_app();
+    // Duplicate titles exercise the id tie-breaker
+    for i in 0..7 {
+        create_task(&app, &format!("Task {}", i % 3)).await;
+    }
+
+    let ids = |page: &Value| -> Vec<String> {
+        page["data"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|task| task["id"].as_str().unwrap().to_string())
+            .collect()
+    };
+
+    let (status, first) = send(&app, "GET", "/tasks?limit=3&sort_by=title&sort_order=asc", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(first["pagination"]["total_items"], 7);
+    assert_eq!(first["pagination"]["has_previous"], false);
+    assert!(first["pagination"].get("page").is_none());
+    assert!(first["pagination"].get("prev_cursor").is_none());
+
+    // Walk forward to the end without duplicates or gaps
+    let mut seen = ids(&first);
+    let mut page = first.clone();
+    while let Some(cursor) = page["pagination"]["next_cursor"].as_str() {
+        let (_, next) = send(&app, "GET", &format!("/tasks?limit=3&cursor={}", cursor), None).await;
+        assert_eq!(next["pagination"]["has_previous"], true);
+        seen.extend(ids(&next));
+        page = next;
+    }
+    assert_eq!(page["pagination"]["has_next"], false);
+    assert_eq!(seen.len(), 7);
+    let mut unique = seen.clone();
+    unique.sort();
+    unique.dedup();
+    assert_eq!(unique.len(), 7);
+
+    // And back again from the second page
+    let cursor = first["pagination"]["next_cursor"].as_str().unwrap();
+    let (_, second) = send(&app, "GET", &format!("/tasks?limit=3&cursor={}", cursor), None).await;
+    let cursor = second["pagination"]["prev_cursor"].as_str().unwrap();
+    let (_, back) = send(&app, "GET", &format!("/tasks?limit=3&cursor={}", cursor), None).await;
+    assert_eq!(ids(&back), ids(&first));
+    assert_eq!(back["pagination"]["has_previous"], false);
+
+    let (status, _) = send(&app, "GET", "/tasks?cursor=garbage", None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn cursor_pages_step_back_over_missing_due_dates() {
+    let app = in_memory_app();
+    for (title, due_at) in [
+        ("d1", Some("2030-01-01")),
+        ("d2", Some("2030-01-02")),
+        ("d3", Some("2030-01-03")),
+        ("n1", None),
+        ("n2", None),
+    ] {
+        let (status, _) = send(&app, "POST", "/tasks", Some(json!({ "title": title, "due_at": due_at }))).await;
+        assert_eq!(status, StatusCode::CREATED);
+    }
+    let titles = |page: &Value| -> Vec<String> {
+        page["data"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|task| task["title"].as_str().unwrap().to_string())
+            .collect()
+    };
+
+    for (sort, expected) in [
+        ("due_at", [["d1", "d2"], ["d3", "n1"], ["n2", ""]]),
+        ("-due_at", [["d3", "d2"], ["d1", "n2"], ["n1", ""]]),
+    ] {
+        let (_, first) = send(&app, "GET", &format!("/tasks?sort={}&limit=2", sort), None).await;
+        let next = first["pagination"]["next_cursor"].as_str().unwrap();
+        let (_, second) = send(&app, "GET", &format!("/tasks?cursor={}&limit=2", next), None).await;
+        let next = second["pagination"]["next_cursor"].as_str().unwrap();
+        let (_, third) = send(&app, "GET", &format!("/tasks?cursor={}&limit=2", next), None).await;
+        assert_eq!(titles(&third), [expected[2][0]], "{}", sort);
+
+        // Back from the last page, across the tasks without a due date
+        let prev = third["pagination"]["prev_cursor"].as_str().unwrap();
+        let (_, back) = send(&app, "GET", &format!("/tasks?cursor={}&limit=2", prev), None).await;
+        assert_eq!(titles(&back), expected[1], "{}", sort);
+        assert_eq!(back["pagination"]["has_previous"], true, "{}", sort);
+
+        let prev = back["pagination"]["prev_cursor"].as_str().unwrap();
+        let (_, back) = send(&app, "GET", &format!("/tasks?cursor={}&limit=2", prev), None).await;
+        assert_eq!(titles(&back), expected[0], "{}", sort);
+        assert_eq!(back["pagination"]["has_previous"], false, "{}", sort);
+    }
+}
+
+#[tokio::test]
+async fn list_tasks_searches_filters_and_sorts() {
+    let app = in_memory_app();
+    create_task(&app, "charlie").await;
+    create_task(&app, "Alpha milk").await;
+    let bravo = create_task(&app, "Bravo milk").await;
+    let uri = format!("/tasks/{}", bravo["id"].as_str().unwrap());
+    send(&app, "PUT", &uri, Some(json!({ "completed": true }))).await;
+
+    let titles = |page: &Value| -> Vec<String> {
+        page["data"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|task| task["title"].as_str().unwrap().to_string())
+            .collect()
+    };
+
+    let (_, page) = send(&app, "GET", "/tasks?q=MILK&sort_by=title&sort_order=asc", None).await;
+    assert_eq!(titles(&page), ["Alpha milk", "Bravo milk"]);
+
+    let (_, page) = send(&app, "GET", "/tasks?status=pending&sort_by=title", None).await;
+    assert_eq!(titles(&page), ["charlie", "Alpha milk"]);
+
+    let (_, page) = send(&app, "GET", "/tasks?q=milk&status=completed", None).await;
+    assert_eq!(titles(&page), ["Bravo milk"]);
+    assert_eq!(page["pagination"]["total_items"], 1);
+
+    let (_, page) = send(&app, "GET", "/tasks?sort=-completed,title", None).await;
+    assert_eq!(titles(&page), ["Bravo milk", "Alpha milk", "charlie"]);
+
+    let (_, page) = send(&app, "GET", "/tasks?sort=-completed,title&limit=1", None).await;
+    let cursor = page["pagination"]["next_cursor"].as_str().unwrap().to_string();
+    let (_, page) = send(&app, "GET", &format!("/tasks?limit=5&cursor={}", cursor), None).await;
+    assert_eq!(titles(&page), ["Alpha milk", "charlie"]);
+
+    let (status, body) = send(&app, "GET", "/tasks?sort=title,owner", None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body["error"].as_str().unwrap().contains("created_at, updated_at, title"));
+    let (status, _) = send(&app, "GET", "/tasks?sort_by=owner", None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn list_tasks_filter_language() {
+    let app = in_memory_app();
+    create_task(&app, "Release notes").await;
+    create_task(&app, "Walk dog").await;
+    let party = create_task(&app, "Release party").await;
+    let uri = format!("/tasks/{}", party["id"].as_str().unwrap());
+    send(&app, "PUT", &uri, Some(json!({ "completed": true }))).await;
+
+    let (status, page) = send(
+        &app,
+        "GET",
+        "/tasks?filter=completed:false%20title:%22release%22%20created:%3E2000-01-01",
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(page["data"].as_array().unwrap().len(), 1);
+    assert_eq!(page["data"][0]["title"], "Release notes");
+
+    let (status, body) = send(&app, "GET", "/tasks?filter=completed:false%20created:%3E2026-13-01", None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let error = body["error"].as_str().unwrap();
+    assert!(error.contains("position 17"), "{}", error);
+    assert!(!error.contains("2026-13-01"), "{}", error);
+
+    let (status, _) = send(&app, "GET", "/tasks?status=done", None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn list_tasks_fulltext_search() {
+    let app = in_memory_app();
+    create_task(&app, "Buy milk").await;
+    create_task(&app, "Buy <b>oat</b> milk & bread").await;
+    create_task(&app, "Milking time").await;
+
+    let (status, page) = send(
+        &app,
+        "GET",
+        "/tasks?q=%2Bmilk%20bread%20-cookies&search_mode=fulltext&sort_by=relevance",
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let data = page["data"].as_array().unwrap();
+    assert_eq!(data.len(), 2);
+    assert_eq!(data[0]["title"], "Buy <b>oat</b> milk & bread");
+    assert_eq!(
+        data[0]["snippet"],
+        "Buy &lt;b&gt;oat&lt;/b&gt; <mark>milk</mark> &amp; <mark>bread</mark>"
+    );
+    assert!(data[0]["relevance"].as_f64().unwrap() > data[1]["relevance"].as_f64().unwrap());
+
+    // Matches only in the description are shown from the description
+    let (status, _) = send(
+        &app,
+        "POST",
+        "/tasks",
+        Some(json!({ "title": "Groceries", "description": "Eggs, flour and whole oats" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let (_, page) = send(&app, "GET", "/tasks?q=whole&search_mode=fulltext", None).await;
+    assert_eq!(page["data"][0]["snippet"], "Eggs, flour and <mark>whole</mark> oats");
+
+    // Substring search is unchanged and carries no search metadata
+    let (_, page) = send(&app, "GET", "/tasks?q=milk", None).await;
+    assert_eq!(page["data"].as_array().unwrap().len(), 3);
+    assert!(page["data"][0].get("snippet").is_none());
+
+    let (status, _) = send(&app, "GET", "/tasks?q=-milk&search_mode=fulltext", None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = send(
+        &app,
+        "GET",
+        "/tasks?q=milk&search_mode=fulltext&sort_by=relevance&limit=5",
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn list_tasks_by_due_date() {
+    let app = in_memory_app();
+    let today = chrono::Utc::now().date_naive();
+    let yesterday = today.pred_opt().unwrap();
+    let tomorrow = today.succ_opt().unwrap();
+
+    for (title, due_at) in [
+        ("Pay rent", Some(yesterday.to_string())),
+        ("Call mom", Some(today.to_string())),
+        ("Book flights", None),
+        ("Renew passport", Some(format!("{}T12:00:00Z", tomorrow))),
+    ] {
+        let (status, task) = send(
+            &app,
+            "POST",
+            "/tasks",
+            Some(json!({ "title": title, "due_at": due_at, "start_at": yesterday.to_string() })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(task["start_at"], format!("{}T00:00:00Z", yesterday));
+        if title == "Pay rent" {
+            // Dates are due all day, from the start of the day in the user's time zone
+            assert_eq!(task["due_at"], format!("{}T00:00:00Z", yesterday));
+            assert_eq!(task["due_all_day"], true);
+        }
+    }
+
+    let titles = |page: &Value| -> Vec<String> {
+        page["data"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|task| task["title"].as_str().unwrap().to_string())
+            .collect()
+    };
+
+    let (_, page) = send(&app, "GET", "/tasks?overdue=true", None).await;
+    assert_eq!(titles(&page), ["Pay rent"]);
+    let (_, page) = send(&app, "GET", "/tasks?filter=due:today", None).await;
+    assert_eq!(titles(&page), ["Call mom"]);
+    let (_, page) = send(&app, "GET", &format!("/tasks?due_before={}", today), None).await;
+    assert_eq!(titles(&page), ["Pay rent"]);
+    let (_, page) = send(&app, "GET", &format!("/tasks?due_after={}", today), None).await;
+    assert_eq!(titles(&page), ["Renew passport"]);
+
+    // Tasks without a due date come last in both directions
+    let (_, page) = send(&app, "GET", "/tasks?sort=due_at", None).await;
+    assert_eq!(titles(&page), ["Pay rent", "Call mom", "Renew passport", "Book flights"]);
+    let (_, page) = send(&app, "GET", "/tasks?sort=-due_at&limit=2", None).await;
+    assert_eq!(titles(&page), ["Renew passport", "Call mom"]);
+    let next = page["pagination"]["next_cursor"].as_str().unwrap();
+    let (_, page) = send(&app, "GET", &format!("/tasks?cursor={}&limit=2", next), None).await;
+    assert_eq!(titles(&page), ["Pay rent", "Book flights"]);
+
+    // null clears a due date
+    let id = page["data"][0]["id"].as_str().unwrap();
+    let (status, task) = send(
+        &app,
+        "PUT",
+        &format!("/tasks/{}", id),
+        Some(json!({ "due_at": null })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(task["due_at"], Value::Null);
+    assert_eq!(task["due_all_day"], false);
+    let (_, page) = send(&app, "GET", "/tasks?filter=due:none", None).await;
+    assert_eq!(page["data"].as_array().unwrap().len(), 2);
+
+    let (status, _) = send(&app, "POST", "/tasks", Some(json!({ "title": "x", "due_at": "soon" }))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    for uri in ["/tasks?due_before=monday", "/tasks?filter=due:someday", "/tasks?sort=due"] {
+        let (status, _) = send(&app, "GET", uri, None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}", uri);
+    }
+}
+
+#[tokio::test]
+async fn list_tasks_by_priority() {
+    let app = in_memory_app();
+    for (title, priority) in [("Fix outage", "urgent"), ("Tidy desk", "low"), ("Ship release", "high")] {
+        let (status, task) = send(
+            &app,
+            "POST",
+            "/tasks",
+            Some(json!({ "title": title, "priority": priority })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(task["priority"], priority);
+    }
+    let task = create_task(&app, "Water plants").await;
+    assert_eq!(task["priority"], "none");
+    let uri = format!("/tasks/{}", task["id"].as_str().unwrap());
+    let (status, task) = send(&app, "PUT", &uri, Some(json!({ "priority": "medium" }))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(task["priority"], "medium");
+
+    let titles = |page: &Value| -> Vec<String> {
+        page["data"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|task| task["title"].as_str().unwrap().to_string())
+            .collect()
+    };
+    let (_, page) = send(&app, "GET", "/tasks?sort=-priority", None).await;
+    assert_eq!(titles(&page), ["Fix outage", "Ship release", "Water plants", "Tidy desk"]);
+    let (_, page) = send(&app, "GET", "/tasks?priority=high,urgent&sort=title", None).await;
+    assert_eq!(titles(&page), ["Fix outage", "Ship release"]);
+    let (_, page) = send(&app, "GET", "/tasks?filter=priority:%3C%3Dmedium&sort=priority", None).await;
+    assert_eq!(titles(&page), ["Tidy desk", "Water plants"]);
+
+    let (status, _) = send(&app, "GET", "/tasks?priority=critical", None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn tasks_are_ordered_by_hand() {
+    let app = in_memory_app();
+    let mut ids = std::collections::HashMap::new();
+    for title in ["a", "b", "c", "d"] {
+        ids.insert(title, create_task(&app, title).await["id"].clone());
+    }
+    let order = || {
+        let app = app.clone();
+        async move {
+            let (_, page) = send(&app, "GET", "/tasks?sort=position", None).await;
+            page["data"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|task| task["title"].as_str().unwrap())
+                .collect::<String>()
+        }
+    };
+    let move_task = |title: &str, body: Value| {
+        let app = app.clone();
+        let uri = format!("/tasks/{}/move", ids[title].as_str().unwrap());
+        async move { send(&app, "POST", &uri, Some(body)).await }
+    };
+    assert_eq!(order().await, "abcd");
+
+    let (status, moved) = move_task("d", json!({ "after": ids["a"] })).await;
+    assert_eq!(status, StatusCode::OK, "{}", moved);
+    assert_eq!(moved["title"], "d");
+    assert_eq!(order().await, "adbc");
+    move_task("a", json!({ "before": ids["c"] })).await;
+    assert_eq!(order().await, "dbac");
+    move_task("c", json!({ "after": ids["d"], "before": ids["b"] })).await;
+    assert_eq!(order().await, "dcba");
+    move_task("d", json!({ "after": ids["a"] })).await;
+    assert_eq!(order().await, "cbad");
+
+    for (title, body, expected) in [
+        ("a", json!({}), StatusCode::BAD_REQUEST),
+        ("a", json!({ "after": ids["a"] }), StatusCode::BAD_REQUEST),
+        ("a", json!({ "after": Uuid::new_v4() }), StatusCode::BAD_REQUEST),
+        ("a", json!({ "after": ids["d"], "before": ids["b"] }), StatusCode::CONFLICT),
+    ] {
+        let (status, error) = move_task(title, body).await;
+        assert_eq!(status, expected, "{}", error);
+    }
+    let uri = format!("/tasks/{}/move", Uuid::new_v4());
+    let (status, _) = send(&app, "POST", &uri, Some(json!({ "after": ids["a"] }))).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // Squeezing tasks in at the same spot over and over lengthens positions
+    // until they are respaced
+    for round in 0..200 {
+        let title = if round % 2 == 0 { "a" } else { "b" };
+        let (_, moved) = move_task(title, json!({ "after": ids["c"] })).await;
+        assert!(moved["position"].as_str().unwrap().len() <= 32, "{}", moved);
+    }
+    assert_eq!(order().await, "cbad");
+    let (_, page) = send(&app, "GET", "/tasks?sort=position", None).await;
+    assert!(page["data"][3]["position"].as_str().unwrap().len() <= 3);
+}
+
+#[tokio::test]
+async fn tags_label_and_filter_tasks() {
+    let app = in_memory_app();
+    let (status, work) = send(
+        &app,
+        "POST",
+        "/tags",
+        Some(json!({ "name": " Work ", "color": "#FF8800" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(work["name"], "work");
+    assert_eq!(work["color"], "#ff8800");
+    let (status, _) = send(&app, "POST", "/tags", Some(json!({ "name": "home" }))).await;
+    assert_eq!(status, StatusCode::CREATED);
+    for invalid in [json!({ "name": "WORK" }), json!({ "name": "two words" }), json!({ "name": "x", "color": "red" })] {
+        let (status, _) = send(&app, "POST", "/tags", Some(invalid)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    let create = |title: &'static str, tags: Value| {
+        let app = app.clone();
+        async move {
+            let (status, task) =
+                send(&app, "POST", "/tasks", Some(json!({ "title": title, "tags": tags }))).await;
+            assert_eq!(status, StatusCode::CREATED);
+            task
+        }
+    };
+    let report = create("Report", json!(["work"])).await;
+    assert_eq!(report["tags"], json!(["work"]));
+    create("Laundry", json!(["home"])).await;
+    create("Plan week", json!(["work", "HOME"])).await;
+    create("Nap", json!([])).await;
+    let (status, _) = send(&app, "POST", "/tasks", Some(json!({ "title": "x", "tags": ["gym"] }))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let titles = |page: &Value| -> Vec<String> {
+        page["data"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|task| task["title"].as_str().unwrap().to_string())
+            .collect()
+    };
+    let (_, page) = send(&app, "GET", "/tasks?tags_any=work,home&sort=title", None).await;
+    assert_eq!(titles(&page), ["Laundry", "Plan week", "Report"]);
+    assert_eq!(page["data"][1]["tags"], json!(["home", "work"]));
+    let (_, page) = send(&app, "GET", "/tasks?tags_all=work,home", None).await;
+    assert_eq!(titles(&page), ["Plan week"]);
+    let (_, page) = send(&app, "GET", "/tasks?tags_none=work&sort=title", None).await;
+    assert_eq!(titles(&page), ["Laundry", "Nap"]);
+    let (_, page) = send(&app, "GET", "/tasks?filter=tag:home%20-tag:work", None).await;
+    assert_eq!(titles(&page), ["Laundry"]);
+
+    // Tags alone are a valid update
+    let uri = format!("/tasks/{}", report["id"].as_str().unwrap());
+    let (status, task) = send(
+        &app,
+        "PUT",
+        &uri,
+        Some(json!({ "add_tags": ["home"], "remove_tags": ["work"] })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(task["tags"], json!(["home"]));
+
+    let (_, tags) = send(&app, "GET", "/tags", None).await;
+    assert_eq!(tags[0]["name"], "home");
+    assert_eq!(tags[0]["task_count"], 3);
+    assert_eq!(tags[1]["task_count"], 1);
+
+    let tag_uri = format!("/tags/{}", work["id"].as_str().unwrap());
+    let (status, renamed) = send(&app, "PUT", &tag_uri, Some(json!({ "name": "job", "color": null }))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(renamed["name"], "job");
+    assert_eq!(renamed["color"], Value::Null);
+    let (status, _) = send(&app, "PUT", &tag_uri, Some(json!({ "name": "home" }))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _) = send(&app, "DELETE", &tag_uri, None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (_, page) = send(&app, "GET", "/tasks?filter=tag:job", None).await;
+    assert_eq!(titles(&page), Vec::<String>::new());
+    let (status, _) = send(&app, "GET", &tag_uri, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn duplicate_names_are_reported_as_unique_violations() {
+    let state = in_memory_state(Config::default());
+    let repository = &state.repository;
+    let is_unique_violation = |error: sqlx::Error| {
+        error
+            .as_database_error()
+            .is_some_and(|error| error.is_unique_violation())
+    };
+
+    let tag = |name| NewTag { id: Uuid::now_v7(), user_id: ANONYMOUS_USER_ID, name, color: None };
+    repository.create_tag(&tag("work")).await.unwrap();
+    let home = repository.create_tag(&tag("home")).await.unwrap();
+    assert!(is_unique_violation(repository.create_tag(&tag("work")).await.unwrap_err()));
+    let rename = TagChanges { name: Some("work"), color: None };
+    assert!(is_unique_violation(repository.update_tag(home.id, &rename).await.unwrap_err()));
+    let rename = TagChanges { name: Some("home"), color: Some(None) };
+    assert_eq!(repository.update_tag(home.id, &rename).await.unwrap().name, "home");
+
+    let project = |name| NewProject {
+        id: Uuid::now_v7(),
+        user_id: ANONYMOUS_USER_ID,
+        name,
+        color: None,
+        position: 0,
+    };
+    repository.create_project(&project("Work")).await.unwrap();
+    let home = repository.create_project(&project("Home")).await.unwrap();
+    assert!(is_unique_violation(repository.create_project(&project("Work")).await.unwrap_err()));
+    let rename = ProjectChanges { name: Some("Work"), color: None, archived: None, position: None };
+    assert!(is_unique_violation(repository.update_project(home.id, &rename).await.unwrap_err()));
+    let rename = ProjectChanges { name: Some("Home"), color: None, archived: Some(true), position: None };
+    assert!(repository.update_project(home.id, &rename).await.unwrap().archived);
+}
+
+#[tokio::test]
+async fn projects_group_tasks() {
+    let app = in_memory_app();
+    let create_project = |body: Value| {
+        let app = app.clone();
+        async move {
+            let (status, project) = send(&app, "POST", "/projects", Some(body)).await;
+            assert_eq!(status, StatusCode::CREATED);
+            project
+        }
+    };
+    let work = create_project(json!({ "name": " Work ", "color": "#00AA00" })).await;
+    assert_eq!(work["name"], "Work");
+    assert_eq!(work["color"], "#00aa00");
+    assert_eq!(work["archived"], false);
+    assert_eq!(work["position"], 0);
+    let home = create_project(json!({ "name": "Home" })).await;
+    assert_eq!(home["position"], 1);
+    let (status, _) = send(&app, "POST", "/projects", Some(json!({ "name": "Work" }))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let project_uri = |project: &Value| format!("/projects/{}", project["id"].as_str().unwrap());
+    let (_, task) = send(
+        &app,
+        "POST",
+        "/tasks",
+        Some(json!({ "title": "Report", "project_id": work["id"] })),
+    )
+    .await;
+    assert_eq!(task["project_id"], work["id"]);
+    for title in ["Plan week", "Laundry"] {
+        send(&app, "POST", "/tasks", Some(json!({ "title": title, "project_id": work["id"] }))).await;
+    }
+    let (status, _) = send(
+        &app,
+        "POST",
+        "/tasks",
+        Some(json!({ "title": "Lost", "project_id": Uuid::now_v7() })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // Nested listings search, sort and paginate like /tasks
+    let tasks_uri = format!("{}/tasks", project_uri(&work));
+    let (status, page) = send(&app, "GET", &format!("{}?sort=title&page_size=2", tasks_uri), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(page["data"][0]["title"], "Laundry");
+    assert_eq!(page["pagination"]["total_items"], 3);
+    assert_eq!(page["pagination"]["has_next"], true);
+    let (_, page) = send(&app, "GET", &format!("{}?q=plan", tasks_uri), None).await;
+    assert_eq!(page["data"].as_array().unwrap().len(), 1);
+
+    // Move the laundry home, then out of any project
+    let (_, page) = send(&app, "GET", &format!("{}?q=laundry", tasks_uri), None).await;
+    let task_uri = format!("/tasks/{}", page["data"][0]["id"].as_str().unwrap());
+    let (status, task) = send(&app, "PUT", &task_uri, Some(json!({ "project_id": home["id"] }))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(task["project_id"], home["id"]);
+    let (_, page) = send(&app, "GET", &format!("{}/tasks", project_uri(&home)), None).await;
+    assert_eq!(page["data"][0]["title"], "Laundry");
+    let (_, task) = send(&app, "PUT", &task_uri, Some(json!({ "project_id": null }))).await;
+    assert_eq!(task["project_id"], Value::Null);
+
+    // Archived projects are hidden from the list and take no new tasks
+    let (status, archived) = send(&app, "PUT", &project_uri(&home), Some(json!({ "archived": true }))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(archived["archived"], true);
+    let (_, projects) = send(&app, "GET", "/projects", None).await;
+    assert_eq!(projects.as_array().unwrap().len(), 1);
+    let (_, projects) = send(&app, "GET", "/projects?include_archived=true", None).await;
+    assert_eq!(projects.as_array().unwrap().len(), 2);
+    let (status, _) = send(&app, "PUT", &task_uri, Some(json!({ "project_id": home["id"] }))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // Deleting a project keeps its tasks
+    let (status, _) = send(&app, "DELETE", &project_uri(&work), None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = send(&app, "GET", &tasks_uri, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (_, page) = send(&app, "GET", "/tasks", None).await;
+    assert_eq!(page["pagination"]["total_items"], 3);
+    assert!(page["data"].as_array().unwrap().iter().all(|task| task["project_id"].is_null()));
+}
+
+#[tokio::test]
+async fn subtasks_roll_up_and_cascade() {
+    let mut config = Config::default();
+    config.tasks.max_subtask_depth = 2;
+    let app = in_memory_app_with(config);
+    let subtask = |title: &'static str, parent: &Value| {
+        let app = app.clone();
+        let body = json!({ "title": title, "parent_id": parent["id"] });
+        async move {
+            let (status, task) = send(&app, "POST", "/tasks", Some(body)).await;
+            assert_eq!(status, StatusCode::CREATED, "{}", task);
+            task
+        }
+    };
+    let uri = |task: &Value| format!("/tasks/{}", task["id"].as_str().unwrap());
+
+    let (_, release) = send(
+        &app,
+        "POST",
+        "/tasks",
+        Some(json!({ "title": "Release", "auto_complete": true })),
+    )
+    .await;
+    let build = subtask("Build", &release).await;
+    let test = subtask("Test", &release).await;
+    let unit = subtask("Unit tests", &test).await;
+    assert_eq!(unit["parent_id"], test["id"]);
+
+    // Too deep, unknown parents and cycles are rejected
+    let body = json!({ "title": "Too deep", "parent_id": unit["id"] });
+    let (status, error) = send(&app, "POST", "/tasks", Some(body)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(error["error"].as_str().unwrap().contains("2 levels"), "{}", error);
+    let body = json!({ "title": "Orphan", "parent_id": Uuid::now_v7() });
+    let (status, _) = send(&app, "POST", "/tasks", Some(body)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = send(&app, "PUT", &uri(&release), Some(json!({ "parent_id": unit["id"] }))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = send(&app, "PUT", &uri(&test), Some(json!({ "parent_id": build["id"] }))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "Unit tests would be 3 levels deep");
+
+    let (status, page) = send(&app, "GET", &format!("{}/children?sort=title", uri(&release)), None).await;
+    assert_eq!(status, StatusCode::OK);
+    let titles: Vec<&str> = page["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|task| task["title"].as_str().unwrap())
+        .collect();
+    assert_eq!(titles, ["Build", "Test"]);
+    assert_eq!(page["data"][1]["subtasks"], json!({ "total": 1, "completed": 0, "percent": 0 }));
+    let (status, _) = send(&app, "GET", &format!("/tasks/{}/children", Uuid::now_v7()), None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let complete = json!({ "completed": true });
+    send(&app, "PUT", &uri(&build), Some(complete.clone())).await;
+    let (_, task) = send(&app, "GET", &uri(&release), None).await;
+    assert_eq!(task["subtasks"]["percent"], 50);
+    assert_eq!(task["completed"], false);
+
+    // Test does not auto-complete, so Release waits for it to be completed by hand
+    send(&app, "PUT", &uri(&unit), Some(complete.clone())).await;
+    let (_, task) = send(&app, "GET", &uri(&test), None).await;
+    assert_eq!(task["completed"], false);
+    assert_eq!(task["subtasks"]["percent"], 100);
+    let (_, task) = send(&app, "PUT", &uri(&test), Some(json!({ "auto_complete": true }))).await;
+    assert_eq!(task["completed"], true);
+    let (_, task) = send(&app, "GET", &uri(&release), None).await;
+    assert_eq!(task["completed"], true);
+    assert_eq!(task["subtasks"], json!({ "total": 2, "completed": 2, "percent": 100 }));
+
+    // Promoting keeps the subtasks, one level up; deleting takes them along
+    let (status, _) = send(&app, "DELETE", &format!("{}?subtasks=promote", uri(&test)), None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (_, task) = send(&app, "GET", &uri(&unit), None).await;
+    assert_eq!(task["parent_id"], release["id"]);
+    let (status, _) = send(&app, "DELETE", &format!("{}?subtasks=keep", uri(&release)), None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = send(&app, "DELETE", &uri(&release), None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = send(&app, "GET", &uri(&unit), None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (_, page) = send(&app, "GET", "/tasks", None).await;
+    assert_eq!(page["pagination"]["total_items"], 0);
+}
+
+#[tokio::test]
+async fn dependencies_block_completion() {
+    let app = in_memory_app();
+    let create = |title: &'static str| {
+        let app = app.clone();
+        async move {
+            let (status, task) = send(&app, "POST", "/tasks", Some(json!({ "title": title }))).await;
+            assert_eq!(status, StatusCode::CREATED, "{}", task);
+            task
+        }
+    };
+    let uri = |task: &Value| format!("/tasks/{}", task["id"].as_str().unwrap());
+    let block = |task: &Value, blocker: &Value| {
+        let app = app.clone();
+        let uri = format!("{}/blockers", uri(task));
+        let body = json!({ "blocker_id": blocker["id"] });
+        async move { send(&app, "POST", &uri, Some(body)).await }
+    };
+
+    let design = create("Design").await;
+    let build = create("Build").await;
+    let ship = create("Ship").await;
+    let (status, blockers) = block(&build, &design).await;
+    assert_eq!(status, StatusCode::OK, "{}", blockers);
+    assert_eq!(blockers[0]["id"], design["id"]);
+    let (status, _) = block(&ship, &build).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Self-dependencies, cycles and unknown tasks are rejected
+    let (status, _) = block(&ship, &ship).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, error) = block(&design, &ship).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(error["error"].as_str().unwrap().contains("cycle"), "{}", error);
+    let (status, _) = block(&ship, &json!({ "id": Uuid::now_v7() })).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = block(&json!({ "id": Uuid::now_v7() }), &ship).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (_, task) = send(&app, "GET", &uri(&build), None).await;
+    assert_eq!(task["blocked"], true);
+    let (_, page) = send(&app, "GET", "/tasks?status=actionable", None).await;
+    assert_eq!(page["data"].as_array().unwrap().len(), 1);
+    assert_eq!(page["data"][0]["id"], design["id"]);
+
+    // Blocked tasks cannot be completed until their blockers are
+    let complete = json!({ "completed": true });
+    let (status, error) = send(&app, "PUT", &uri(&build), Some(complete.clone())).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert!(error["error"].as_str().unwrap().contains("1 open task"), "{}", error);
+    send(&app, "PUT", &uri(&design), Some(complete.clone())).await;
+    let (status, task) = send(&app, "PUT", &uri(&build), Some(complete.clone())).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(task["blocked"], false);
+
+    let blockers_uri = format!("{}/blockers", uri(&ship));
+    let (_, blockers) = send(&app, "GET", &blockers_uri, None).await;
+    assert_eq!(blockers[0]["completed"], true);
+    let (status, _) = send(&app, "DELETE", &format!("{}/{}", blockers_uri, build["id"].as_str().unwrap()), None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = send(&app, "DELETE", &format!("{}/{}", blockers_uri, build["id"].as_str().unwrap()), None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (_, blockers) = send(&app, "GET", &blockers_uri, None).await;
+    assert_eq!(blockers, json!([]));
+}
+
+#[tokio::test]
+async fn recurring_tasks_repeat_when_completed() {
+    let app = in_memory_app();
+    send(&app, "POST", "/tags", Some(json!({ "name": "chores" }))).await;
+    let uri = |task: &Value| format!("/tasks/{}", task["id"].as_str().unwrap());
+    let pending = || {
+        let app = app.clone();
+        async move {
+            let (_, page) = send(&app, "GET", "/tasks?status=pending", None).await;
+            page["data"].as_array().unwrap().clone()
+        }
+    };
+
+    // 2026-10-19 is a Monday
+    let body = json!({
+        "title": "Water plants",
+        "due_at": "2026-10-19",
+        "start_at": "2026-10-18",
+        "recurrence": "RRULE:freq=weekly;byday=th,mo;count=3",
+        "tags": ["chores"]
+    });
+    let (status, task) = send(&app, "POST", "/tasks", Some(body)).await;
+    assert_eq!(status, StatusCode::CREATED, "{}", task);
+    assert_eq!(task["recurrence"], "FREQ=WEEKLY;BYDAY=MO,TH;COUNT=3");
+
+    let (status, occurrences) = send(&app, "GET", &format!("{}/occurrences", uri(&task)), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(occurrences, json!(["2026-10-22T00:00:00Z", "2026-10-26T00:00:00Z"]));
+    let (status, _) = send(&app, "GET", &format!("{}/occurrences?count=0", uri(&task)), None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // Rules must parse and have a due date to count from
+    for body in [
+        json!({ "title": "Undated", "recurrence": "FREQ=DAILY" }),
+        json!({ "title": "Hourly", "due_at": "2026-10-19", "recurrence": "FREQ=HOURLY" }),
+    ] {
+        let (status, error) = send(&app, "POST", "/tasks", Some(body)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}", error);
+    }
+    let (status, _) = send(&app, "PUT", &uri(&task), Some(json!({ "due_at": null }))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // A task after the recurring one in manual order, out of the pending list
+    let complete = json!({ "completed": true });
+    let later = create_task(&app, "Later").await;
+    send(&app, "PUT", &uri(&later), Some(complete.clone())).await;
+    let position = |task: &Value| task["position"].as_str().unwrap().to_string();
+
+    // Each completion hands the rule on to the next occurrence, until the count runs out
+    let mut current = task;
+    for (due_at, start_at, recurrence) in [
+        ("2026-10-22T00:00:00Z", "2026-10-21T00:00:00Z", "FREQ=WEEKLY;BYDAY=MO,TH;COUNT=2"),
+        ("2026-10-26T00:00:00Z", "2026-10-25T00:00:00Z", "FREQ=WEEKLY;BYDAY=MO,TH;COUNT=1"),
+    ] {
+        let (status, completed) = send(&app, "PUT", &uri(&current), Some(complete.clone())).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(completed["recurrence"], Value::Null);
+        let tasks = pending().await;
+        assert_eq!(tasks.len(), 1);
+        current = tasks[0].clone();
+        assert_eq!(current["title"], "Water plants");
+        assert_eq!((current["due_at"].as_str(), current["due_all_day"].as_bool()), (Some(due_at), Some(true)));
+        assert_eq!(current["start_at"], start_at);
+        assert_eq!(current["recurrence"], recurrence);
+        assert_eq!(current["tags"], json!(["chores"]));
+        // It takes the completed occurrence's place, before the next task
+        assert!(position(&completed) < position(&current) && position(&current) < position(&later));
+
+        // Completing an occurrence again does not repeat it twice
+        send(&app, "PUT", &uri(&completed), Some(complete.clone())).await;
+        assert_eq!(pending().await.len(), 1);
+    }
+    send(&app, "PUT", &uri(&current), Some(complete.clone())).await;
+    assert!(pending().await.is_empty());
+    let (_, occurrences) = send(&app, "GET", &format!("{}/occurrences", uri(&current)), None).await;
+    assert_eq!(occurrences, json!([]));
+}
+
+/// Records emails instead of sending them
+#[derive(Default)]
+struct CapturingMailer {
+    sent: Mutex<Vec<Email>>,
+}
+
+#[async_trait]
+impl Mailer for CapturingMailer {
+    async fn send(&self, email: &Email) -> Result<(), DeliveryError> {
+        self.sent.lock().unwrap().push(email.clone());
+        Ok(())
+    }
+}
+
+/// Serve a webhook receiver on a local port, answering `/ok` with 200 and
+/// anything else with 500, and return its address and the bodies it got
+async fn webhook_receiver() -> (String, Arc<Mutex<Vec<Value>>>) {
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let record = received.clone();
+    let app = Router::new().route(
+        "/{outcome}",
+        axum::routing::post(move |Path(outcome): Path<String>, Json(body): Json<Value>| async move {
+            record.lock().unwrap().push(body);
+            if outcome == "ok" {
+                StatusCode::OK
+            } else {
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (address, received)
+}
+
+#[tokio::test]
+async fn reminders_are_delivered_once_and_can_be_snoozed() {
+    // The webhook receiver listens on the loopback interface
+    let mut config = Config::default();
+    config.reminders.allow_private_webhooks = true;
+    let state = in_memory_state(config);
+    let app = build_app(state.clone());
+    let mailer = Arc::new(CapturingMailer::default());
+    let scheduler = Scheduler::new(state.repository.clone(), mailer.clone(), state.config.reminders.clone());
+    let (webhook, received) = webhook_receiver().await;
+
+    let now = Utc::now().trunc_subsecs(0);
+    let minutes = |minutes: i64| now + chrono::Duration::minutes(minutes);
+    let time = |value: &Value| value.as_str().unwrap().parse::<DateTime<Utc>>().unwrap();
+    let body = json!({ "title": "Pay rent", "due_at": minutes(120).to_rfc3339() });
+    let (_, task) = send(&app, "POST", "/tasks", Some(body)).await;
+    let reminders = format!("/tasks/{}/reminders", task["id"].as_str().unwrap());
+
+    // Email reminders go to the user's address unless told otherwise
+    let body = json!({ "minutes_before": 60, "channel": "email" });
+    let (status, email) = send(&app, "POST", &reminders, Some(body)).await;
+    assert_eq!(status, StatusCode::CREATED, "{}", email);
+    assert_eq!(email["target"], "anonymous@localhost");
+    assert_eq!(time(&email["fire_at"]), minutes(60));
+    let body = json!({ "remind_at": minutes(30).to_rfc3339() });
+    let (status, in_app) = send(&app, "POST", &reminders, Some(body)).await;
+    assert_eq!(status, StatusCode::CREATED, "{}", in_app);
+    assert_eq!(in_app["channel"], "in_app");
+    for target in ["ok", "fail"] {
+        let body = json!({ "minutes_before": 90, "channel": "webhook", "target": format!("{}/{}", webhook, target) });
+        let (status, reminder) = send(&app, "POST", &reminders, Some(body)).await;
+        assert_eq!(status, StatusCode::CREATED, "{}", reminder);
+    }
+
+    for body in [
+        json!({}),
+        json!({ "minutes_before": 5, "remind_at": minutes(30).to_rfc3339() }),
+        json!({ "remind_at": minutes(-5).to_rfc3339() }),
+        json!({ "minutes_before": -5 }),
+        json!({ "minutes_before": 5, "target": "someone@example.com" }),
+        json!({ "minutes_before": 5, "channel": "email", "target": "nobody" }),
+        json!({ "minutes_before": 5, "channel": "webhook" }),
+        json!({ "minutes_before": 5, "channel": "webhook", "target": "ftp://example.com/hook" }),
+    ] {
+        let (status, error) = send(&app, "POST", &reminders, Some(body.clone())).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{} {}", body, error);
+    }
+    let undated = create_task(&app, "Someday").await;
+    let uri = format!("/tasks/{}/reminders", undated["id"].as_str().unwrap());
+    let (status, _) = send(&app, "POST", &uri, Some(json!({ "minutes_before": 5 }))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // Nothing is due yet; later everything is, and goes out exactly once
+    assert_eq!(scheduler.dispatch_due(now).await.unwrap(), DispatchReport::default());
+    let report = scheduler.dispatch_due(minutes(100)).await.unwrap();
+    assert_eq!((report.delivered, report.failed), (3, 1));
+    let report = scheduler.dispatch_due(minutes(100)).await.unwrap();
+    assert_eq!((report.delivered, report.failed), (0, 1));
+
+    let sent = mailer.sent.lock().unwrap().clone();
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].to, "anonymous@localhost");
+    assert_eq!(sent[0].subject, "Reminder: Pay rent");
+    let received = received.lock().unwrap().clone();
+    assert_eq!(received.len(), 3);
+    assert_eq!(received[0]["event"], "reminder");
+    assert_eq!(received[0]["task"]["id"], task["id"]);
+    let (_, listed) = send(&app, "GET", &reminders, None).await;
+    let failing = &listed.as_array().unwrap()[3];
+    assert_eq!(failing["delivered_at"], Value::Null);
+    assert_eq!(failing["attempts"], 2);
+    assert_eq!(failing["last_error"], "Webhook answered 500 Internal Server Error");
+
+    let (status, notifications) = send(&app, "GET", "/notifications?unread=true", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(notifications.as_array().unwrap().len(), 1);
+    assert_eq!(notifications[0]["task_id"], task["id"]);
+    let read = format!("/notifications/{}/read", notifications[0]["id"].as_str().unwrap());
+    let (status, _) = send(&app, "POST", &read, None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (_, notifications) = send(&app, "GET", "/notifications?unread=true", None).await;
+    assert_eq!(notifications, json!([]));
+    let (_, notifications) = send(&app, "GET", "/notifications", None).await;
+    assert_eq!(notifications.as_array().unwrap().len(), 1);
+    let (status, _) = send(&app, "POST", &format!("/notifications/{}/read", Uuid::new_v4()), None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // Snoozing sends a delivered reminder again
+    let snooze = format!("{}/{}/snooze", reminders, in_app["id"].as_str().unwrap());
+    let (status, _) = send(&app, "POST", &snooze, Some(json!({ "minutes": 0 }))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, snoozed) = send(&app, "POST", &snooze, Some(json!({ "until": minutes(200).to_rfc3339() }))).await;
+    assert_eq!(status, StatusCode::OK, "{}", snoozed);
+    assert_eq!(time(&snoozed["fire_at"]), minutes(200));
+    assert_eq!(snoozed["delivered_at"], Value::Null);
+    assert_eq!(scheduler.dispatch_due(minutes(150)).await.unwrap().delivered, 0);
+    assert_eq!(scheduler.dispatch_due(minutes(200)).await.unwrap().delivered, 1);
+
+    // Moving the due date moves the reminders set relative to it
+    let body = json!({ "due_at": minutes(300).to_rfc3339() });
+    send(&app, "PUT", &format!("/tasks/{}", task["id"].as_str().unwrap()), Some(body)).await;
+    let (_, listed) = send(&app, "GET", &reminders, None).await;
+    assert_eq!(time(&listed[0]["fire_at"]), minutes(240));
+    assert_eq!(listed[0]["delivered_at"], Value::Null);
+    assert_eq!(time(&listed[1]["fire_at"]), minutes(200));
+
+    let reminder = format!("{}/{}", reminders, email["id"].as_str().unwrap());
+    let (status, _) = send(&app, "DELETE", &format!("{}/{}", uri, email["id"].as_str().unwrap()), None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = send(&app, "DELETE", &reminder, None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = send(&app, "DELETE", &reminder, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn webhooks_to_private_addresses_are_refused() {
+    let app = in_memory_app();
+    let body = json!({ "title": "Pay rent", "due_at": (Utc::now() + chrono::Duration::hours(2)).to_rfc3339() });
+    let (_, task) = send(&app, "POST", "/tasks", Some(body)).await;
+    let reminders = format!("/tasks/{}/reminders", task["id"].as_str().unwrap());
+
+    for target in [
+        "http://127.0.0.1:8080/hook",
+        "http://localhost/hook",
+        "http://10.1.2.3/hook",
+        "http://192.168.0.10/hook",
+        "http://169.254.169.254/latest/meta-data",
+        "http://[::1]/hook",
+        "http://[fd00::1]/hook",
+        "http://[::ffff:127.0.0.1]/hook",
+        "http://0.0.0.0/hook",
+    ] {
+        let body = json!({ "minutes_before": 5, "channel": "webhook", "target": target });
+        let (status, error) = send(&app, "POST", &reminders, Some(body)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{} {}", target, error);
+    }
+
+    // Reminders stored while private addresses were allowed are not sent either
+    let mut config = Config::default();
+    config.reminders.allow_private_webhooks = true;
+    let state = in_memory_state(config);
+    let app = build_app(state.clone());
+    let (webhook, received) = webhook_receiver().await;
+    let (_, task) = send(&app, "POST", "/tasks", Some(json!({ "title": "Pay rent" }))).await;
+    let reminders = format!("/tasks/{}/reminders", task["id"].as_str().unwrap());
+    let remind_at = Utc::now() + chrono::Duration::minutes(5);
+    let body = json!({ "remind_at": remind_at.to_rfc3339(), "channel": "webhook", "target": format!("{}/ok", webhook) });
+    let (status, _) = send(&app, "POST", &reminders, Some(body)).await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let mut config = state.config.reminders.clone();
+    config.allow_private_webhooks = false;
+    let scheduler = Scheduler::new(state.repository.clone(), Arc::new(CapturingMailer::default()), config);
+    let report = scheduler.dispatch_due(remind_at).await.unwrap();
+    assert_eq!((report.delivered, report.failed), (0, 1));
+    assert!(received.lock().unwrap().is_empty());
+    let (_, listed) = send(&app, "GET", &reminders, None).await;
+    let error = listed[0]["last_error"].as_str().unwrap();
+    assert!(error.starts_with("Webhook refused: 127.0.0.1 is not a public address"), "{}", error);
+}
+
+/// Log output of the current thread, collected while the guard is alive
+#[derive(Clone, Default)]
+struct CapturedLogs(Arc<Mutex<Vec<u8>>>);
+
+impl CapturedLogs {
+    fn install(&self) -> tracing::subscriber::DefaultGuard {
+        let logs = self.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_ansi(false)
+            .with_writer(move || logs.clone())
+            .finish();
+        tracing::subscriber::set_default(subscriber)
+    }
+
+    fn contents(&self) -> String {
+        String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+    }
+}
+
+impl std::io::Write for CapturedLogs {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn rejected_reminder_targets_are_not_logged() {
+    let app = in_memory_app();
+    let task = create_task(&app, "Pay rent").await;
+    let reminders = format!("/tasks/{}/reminders", task["id"].as_str().unwrap());
+
+    let logs = CapturedLogs::default();
+    let _guard = logs.install();
+    for (channel, target) in [
+        ("email", "jane.doe.example.com"),
+        ("webhook", "ftp://hooks.example.com/jane?token=s3cret"),
+        ("webhook", "http://10.1.2.3/jane?token=s3cret"),
+    ] {
+        let remind_at = Utc::now() + chrono::Duration::hours(1);
+        let body = json!({ "remind_at": remind_at.to_rfc3339(), "channel": channel, "target": target });
+        let (status, error) = send(&app, "POST", &reminders, Some(body)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}", target);
+        assert!(!error.to_string().contains("jane"), "{}", error);
+    }
+
+    let logs = logs.contents();
+    assert_eq!(logs.matches("Validation error").count(), 3, "{}", logs);
+    assert!(!logs.contains("jane"), "{}", logs);
+    assert!(!logs.contains("s3cret"), "{}", logs);
+}
+
+#[tokio::test]
+async fn register_and_login() {
+    let app = in_memory_app();
+    let credentials = json!({ "email": "jane@example.com", "password": "correct horse" });
+
+    let (status, body) = send(&app, "POST", "/auth/register", Some(credentials.clone())).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["user"]["email"], "jane@example.com");
+    assert_eq!(body["user"]["time_zone"], "UTC");
+    assert!(body["token"].as_str().is_some_and(|token| !token.is_empty()));
+
+    let (status, _) = send(&app, "POST", "/auth/register", Some(credentials.clone())).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, body) = send(&app, "POST", "/auth/login", Some(credentials)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["user"]["email"], "jane@example.com");
+
+    let wrong = json!({ "email": "jane@example.com", "password": "wrong password" });
+    let (status, _) = send(&app, "POST", "/auth/login", Some(wrong)).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let short = json!({ "email": "joe@example.com", "password": "short" });
+    let (status, _) = send(&app, "POST", "/auth/register", Some(short)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let zoned = json!({ "email": "joe@example.com", "password": "correct horse", "time_zone": "Asia/Tokyo" });
+    let (status, body) = send(&app, "POST", "/auth/register", Some(zoned)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["user"]["time_zone"], "Asia/Tokyo");
+
+    let unknown = json!({ "email": "ann@example.com", "password": "correct horse", "time_zone": "Mars/Olympus" });
+    let (status, _) = send(&app, "POST", "/auth/register", Some(unknown)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn profile_time_zone_decides_the_requesters_dates() {
+    let app = in_memory_app();
+    let credentials = json!({ "email": "kiri@example.com", "password": "correct horse" });
+    let (_, body) = send(&app, "POST", "/auth/register", Some(credentials)).await;
+    let token = body["token"].as_str().unwrap().to_string();
+
+    let (status, _) = send(&app, "GET", "/auth/profile", None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, body) = send_as(&app, &token, "GET", "/auth/profile", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["time_zone"], "UTC");
+
+    let unknown = json!({ "time_zone": "Mars/Olympus" });
+    let (status, _) = send_as(&app, &token, "PATCH", "/auth/profile", Some(unknown)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = send_as(&app, &token, "PATCH", "/auth/profile", Some(json!({}))).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+    let auckland = json!({ "time_zone": "Pacific/Auckland" });
+    let (status, body) = send_as(&app, &token, "PATCH", "/auth/profile", Some(auckland)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["time_zone"], "Pacific/Auckland");
+
+    // Noon UTC on the 14th is already the 15th in Auckland (UTC+13 in January)
+    let lunch = json!({ "title": "Late lunch", "due_at": "2030-01-14T12:00:00Z" });
+    let (status, _) = send(&app, "POST", "/tasks", Some(lunch)).await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    let uri = "/tasks?due_before=2030-01-15";
+    let (_, page) = send(&app, "GET", uri, None).await;
+    assert_eq!(page["data"].as_array().unwrap().len(), 1);
+    let (_, page) = send_as(&app, &token, "GET", uri, None).await;
+    assert_eq!(page["data"].as_array().unwrap().len(), 0);
+
+    // A date is due from the start of that day in the requester's time zone
+    let dated = json!({ "title": "Picnic", "due_at": "2030-01-15" });
+    let (_, task) = send_as(&app, &token, "POST", "/tasks", Some(dated.clone())).await;
+    assert_eq!(task["due_at"], "2030-01-14T11:00:00Z");
+    let (_, task) = send(&app, "POST", "/tasks", Some(dated)).await;
+    assert_eq!(task["due_at"], "2030-01-15T00:00:00Z");
+
+    // A recurring task repeats on its owner's days, whoever asks
+    let daily = json!({ "title": "Stretch", "due_at": "2030-01-15", "recurrence": "FREQ=DAILY" });
+    let (_, task) = send(&app, "POST", "/tasks", Some(daily)).await;
+    let uri = format!("/tasks/{}", task["id"].as_str().unwrap());
+    let (_, occurrences) = send_as(&app, &token, "GET", &format!("{}/occurrences?count=1", uri), None).await;
+    assert_eq!(occurrences, json!(["2030-01-16T00:00:00Z"]));
+    let completed = json!({ "completed": true });
+    let (status, _) = send_as(&app, &token, "PUT", &uri, Some(completed)).await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, page) = send(&app, "GET", "/tasks?status=pending&q=Stretch", None).await;
+    assert_eq!(page["data"][0]["due_at"], "2030-01-16T00:00:00Z");
+
+    // The token stops working once its session is logged out
+    let (status, _) = send_as(&app, &token, "POST", "/auth/logout", None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let (status, _) = send_as(&app, &token, "GET", "/auth/profile", None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn readiness_reports_ok() {
+    let app = in_memory_app();
+
+    let (status, body) = send(&app, "GET", "/health/ready", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "ok");
+}
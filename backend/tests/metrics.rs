@@ -0,0 +1,54 @@
+mod common;
+
+use backend::metrics;
+use backend::repository::MySqlRepository;
+use common::spawn_app;
+use serde_json::json;
+
+#[tokio::test]
+async fn metrics_endpoint_exposes_http_pool_auth_and_domain_metrics() {
+    let test_app = spawn_app().await;
+    let client = reqwest::Client::new();
+
+    // Generate some traffic
+    client
+        .post(format!("{}/tasks", &test_app.address))
+        .json(&json!({ "title": "Measured task" }))
+        .send()
+        .await
+        .expect("Failed to create task");
+
+    client
+        .post(format!("{}/auth/login", &test_app.address))
+        .json(&json!({ "email": "nobody@example.com", "password": "wrong-password" }))
+        .send()
+        .await
+        .expect("Failed to attempt login");
+
+    // The database gauges are refreshed on a timer in the server
+    metrics::refresh_gauges(&MySqlRepository::new(test_app.db_pool.clone())).await;
+
+    let response = client
+        .get(format!("{}/metrics", &test_app.address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(response.headers()["content-type"]
+        .to_str()
+        .unwrap()
+        .starts_with("text/plain"));
+
+    let body = response.text().await.expect("Failed to read body");
+
+    assert!(body.contains(r#"http_requests_total{method="POST",route="/tasks",status="201"}"#));
+    assert!(body.contains("http_request_duration_seconds_bucket"));
+    assert!(body.contains(r#"auth_attempts_total{operation="login",outcome="invalid_credentials"}"#));
+    assert!(body.contains("db_pool_connections"));
+    assert!(body.contains("db_pool_idle_connections"));
+    assert!(body.contains("db_pool_acquire_wait_seconds"));
+    assert!(body.contains("tasks_total 1"));
+
+    test_app.cleanup().await;
+}
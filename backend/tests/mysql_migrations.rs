@@ -0,0 +1,195 @@
+use backend::models::Task;
+use backend::repository::{
+    MySqlRepository, NewTask, Repository, SessionRepository, TaskRepository, UserRepository,
+    ANONYMOUS_USER_ID,
+};
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+
+mod common;
+
+use common::{create_test_database, drop_test_database};
+
+/// Insert a task the way the CHAR(36) schema stored it
+async fn insert_legacy_task(repository: &MySqlRepository, id: &str, title: &str, user_id: &str) {
+    sqlx::query("INSERT INTO tasks (id, title, completed, user_id) VALUES (?, ?, FALSE, ?)")
+        .bind(id)
+        .bind(title)
+        .bind(user_id)
+        .execute(repository.pool())
+        .await
+        .expect("Failed to insert legacy task");
+}
+
+#[tokio::test]
+async fn text_keys_are_converted_to_binary_keys() {
+    let (db_pool, db_name) = create_test_database().await;
+    let repository = MySqlRepository::new(db_pool.clone());
+    repository.migrate_to(Some(2)).await.unwrap();
+
+    let user_id = Uuid::new_v4();
+    let session_id = Uuid::new_v4();
+    let task_id = Uuid::new_v4();
+    let anonymous_task_id = Uuid::new_v4();
+
+    // Keys written in upper case convert just the same
+    sqlx::query("INSERT INTO users (id, email, password_hash) VALUES (?, 'old@example.com', 'hash')")
+        .bind(user_id.to_string().to_uppercase())
+        .execute(&db_pool)
+        .await
+        .unwrap();
+    sqlx::query("INSERT INTO sessions (id, user_id, token_hash, expires_at) VALUES (?, ?, 'old-token', ?)")
+        .bind(session_id.to_string())
+        .bind(user_id.to_string().to_uppercase())
+        .bind(Utc::now() + Duration::hours(1))
+        .execute(&db_pool)
+        .await
+        .unwrap();
+    insert_legacy_task(&repository, &task_id.to_string(), "Old task", &user_id.to_string().to_uppercase()).await;
+
+    // Version 3 fills in the binary columns alongside the text ones
+    repository.migrate_to(Some(3)).await.unwrap();
+    let unconverted: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM tasks WHERE id_bin IS NULL")
+        .fetch_one(&db_pool)
+        .await
+        .unwrap();
+    assert_eq!(unconverted, 0);
+
+    // The previous release keeps writing text keys until version 4 is applied
+    insert_legacy_task(
+        &repository,
+        &anonymous_task_id.to_string(),
+        "Written during the upgrade",
+        &ANONYMOUS_USER_ID.to_string(),
+    )
+    .await;
+
+    repository.migrate().await.unwrap();
+    assert!(repository.pending_migrations().await.unwrap().is_empty());
+
+    let task = repository.get_task(task_id).await.unwrap();
+    assert_eq!(task.title, "Old task");
+    assert_eq!(task.user_id, user_id);
+
+    let task = repository.get_task(anonymous_task_id).await.unwrap();
+    assert_eq!(task.title, "Written during the upgrade");
+    assert_eq!(task.user_id, ANONYMOUS_USER_ID);
+
+    let user = repository.get_user_by_id(user_id).await.unwrap();
+    assert_eq!(user.email, "old@example.com");
+    let session = repository
+        .get_session_by_token("old-token")
+        .await
+        .unwrap()
+        .expect("Session should survive the migration");
+    assert_eq!(session.id, session_id);
+    assert_eq!(session.user_id, user_id);
+
+    // The recreated foreign keys still cascade
+    sqlx::query("DELETE FROM users WHERE id = ?")
+        .bind(user_id)
+        .execute(&db_pool)
+        .await
+        .unwrap();
+    assert!(matches!(
+        repository.get_task(task_id).await,
+        Err(sqlx::Error::RowNotFound)
+    ));
+    assert!(repository.get_session_by_token("old-token").await.unwrap().is_none());
+
+    db_pool.close().await;
+    drop_test_database(&db_name).await;
+}
+
+#[tokio::test]
+async fn malformed_text_keys_stop_the_migration_before_any_change() {
+    let (db_pool, db_name) = create_test_database().await;
+    let repository = MySqlRepository::new(db_pool.clone());
+    repository.migrate_to(Some(2)).await.unwrap();
+
+    let good_id = Uuid::new_v4();
+    insert_legacy_task(&repository, &good_id.to_string(), "Fine", &ANONYMOUS_USER_ID.to_string()).await;
+    insert_legacy_task(&repository, "not-a-uuid", "Broken", &ANONYMOUS_USER_ID.to_string()).await;
+    // Valid hex, but too short to be a key
+    insert_legacy_task(&repository, "0123abcd", "Short", &ANONYMOUS_USER_ID.to_string()).await;
+
+    // Version 3 leaves the bad keys alone rather than retrying them forever
+    repository.migrate_to(Some(3)).await.unwrap();
+    let unconverted: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM tasks WHERE id_bin IS NULL")
+        .fetch_one(&db_pool)
+        .await
+        .unwrap();
+    assert_eq!(unconverted, 2);
+
+    // Version 4 refuses to drop the text keys
+    assert!(repository.migrate().await.is_err());
+    let pending: Vec<i64> = repository
+        .pending_migrations()
+        .await
+        .unwrap()
+        .iter()
+        .map(|migration| migration.version)
+        .collect();
+    assert_eq!(pending.first(), Some(&4));
+    let title: String = sqlx::query_scalar("SELECT title FROM tasks WHERE id = 'not-a-uuid'")
+        .fetch_one(&db_pool)
+        .await
+        .unwrap();
+    assert_eq!(title, "Broken");
+
+    // Once the bad rows are dealt with, the migration can be run again
+    sqlx::query("DELETE FROM tasks WHERE id_bin IS NULL")
+        .execute(&db_pool)
+        .await
+        .unwrap();
+    repository.migrate().await.unwrap();
+    assert_eq!(repository.get_task(good_id).await.unwrap().title, "Fine");
+
+    db_pool.close().await;
+    drop_test_database(&db_name).await;
+}
+
+#[tokio::test]
+async fn corrupted_rows_are_decode_errors() {
+    let (db_pool, db_name) = create_test_database().await;
+    let repository = MySqlRepository::new(db_pool.clone());
+    repository.migrate().await.unwrap();
+
+    let id = Uuid::now_v7();
+    repository
+        .create_task(&NewTask::new(id, "Odd", ANONYMOUS_USER_ID))
+        .await
+        .unwrap();
+
+    // A key of the wrong length in place of the task's own
+    let result = sqlx::query_as::<_, Task>(
+        r#"
+        SELECT X'0102' AS id, title, description, completed, user_id, created_at, updated_at,
+            due_at, due_all_day, start_at, priority, project_id, parent_id, auto_complete,
+            recurrence, position
+        FROM tasks WHERE id = ?
+        "#,
+    )
+    .bind(id)
+    .fetch_one(&db_pool)
+    .await;
+    assert!(matches!(result, Err(sqlx::Error::ColumnDecode { .. })));
+
+    // A stored value outside its type's range
+    sqlx::query("UPDATE tasks SET priority = 9 WHERE id = ?")
+        .bind(id)
+        .execute(&db_pool)
+        .await
+        .unwrap();
+    assert!(matches!(
+        repository.get_task(id).await,
+        Err(sqlx::Error::ColumnDecode { .. })
+    ));
+    assert!(matches!(
+        repository.list_all_tasks().await,
+        Err(sqlx::Error::ColumnDecode { .. })
+    ));
+
+    db_pool.close().await;
+    drop_test_database(&db_name).await;
+}
@@ -0,0 +1,43 @@
+mod common;
+
+use common::spawn_app;
+use uuid::Uuid;
+
+#[tokio::test]
+async fn responses_carry_a_generated_request_id() {
+    let test_app = spawn_app().await;
+    let client = reqwest::Client::new();
+
+    let response = client
+        .get(format!("{}/health", &test_app.address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    let request_id = response.headers()["x-request-id"].to_str().unwrap();
+    assert!(Uuid::parse_str(request_id).is_ok());
+
+    test_app.cleanup().await;
+}
+
+#[tokio::test]
+async fn error_bodies_include_the_callers_request_id() {
+    let test_app = spawn_app().await;
+    let client = reqwest::Client::new();
+
+    let response = client
+        .get(format!("{}/tasks/{}", &test_app.address, Uuid::new_v4()))
+        .header("X-Request-Id", "client-req-42")
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(response.status().as_u16(), 404);
+    assert_eq!(response.headers()["x-request-id"], "client-req-42");
+
+    let body: serde_json::Value = response.json().await.expect("Failed to parse JSON");
+    assert_eq!(body["error"], "Resource not found");
+    assert_eq!(body["request_id"], "client-req-42");
+
+    test_app.cleanup().await;
+}
@@ -1,7 +1,5 @@
-use backend::models::{CreateTaskPayload, Task, TaskQueryParams};
+use backend::models::{CreateTaskPayload, Task};
 use serde_json::json;
-use std::collections::HashMap;
-use uuid::Uuid;
 
 mod common;
 
@@ -12,12 +10,39 @@ async fn test_search_by_title() {
     // Create test tasks
     let task1_payload = CreateTaskPayload {
         title: "Learn Rust programming".to_string(),
+        description: None,
+        due_at: None,
+        start_at: None,
+        priority: Default::default(),
+        project_id: None,
+        parent_id: None,
+        auto_complete: false,
+        recurrence: None,
+        tags: Vec::new(),
     };
     let task2_payload = CreateTaskPayload {
         title: "Build web application".to_string(),
+        description: None,
+        due_at: None,
+        start_at: None,
+        priority: Default::default(),
+        project_id: None,
+        parent_id: None,
+        auto_complete: false,
+        recurrence: None,
+        tags: Vec::new(),
     };
     let task3_payload = CreateTaskPayload {
         title: "Deploy to production".to_string(),
+        description: None,
+        due_at: None,
+        start_at: None,
+        priority: Default::default(),
+        project_id: None,
+        parent_id: None,
+        auto_complete: false,
+        recurrence: None,
+        tags: Vec::new(),
     };
 
     // Create tasks
@@ -64,6 +89,8 @@ async fn test_search_by_title() {
     let no_results: serde_json::Value = no_results_response.json().await;
     let no_tasks = no_results["data"].as_array().unwrap();
     assert_eq!(no_tasks.len(), 0);
+
+    app.app.cleanup().await;
 }
 
 #[tokio::test]
@@ -73,9 +100,27 @@ async fn test_filter_by_status() {
     // Create test tasks
     let task1_payload = CreateTaskPayload {
         title: "Completed task".to_string(),
+        description: None,
+        due_at: None,
+        start_at: None,
+        priority: Default::default(),
+        project_id: None,
+        parent_id: None,
+        auto_complete: false,
+        recurrence: None,
+        tags: Vec::new(),
     };
     let task2_payload = CreateTaskPayload {
         title: "Pending task".to_string(),
+        description: None,
+        due_at: None,
+        start_at: None,
+        priority: Default::default(),
+        project_id: None,
+        parent_id: None,
+        auto_complete: false,
+        recurrence: None,
+        tags: Vec::new(),
     };
 
     // Create tasks
@@ -115,7 +160,7 @@ async fn test_filter_by_status() {
     let completed_result: serde_json::Value = completed_response.json().await;
     let completed_tasks = completed_result["data"].as_array().unwrap();
     assert_eq!(completed_tasks.len(), 1);
-    assert_eq!(completed_tasks[0]["completed"].as_bool().unwrap(), true);
+    assert!(completed_tasks[0]["completed"].as_bool().unwrap());
 
     // Test filter by pending status
     let pending_response = app
@@ -127,7 +172,7 @@ async fn test_filter_by_status() {
     let pending_result: serde_json::Value = pending_response.json().await;
     let pending_tasks = pending_result["data"].as_array().unwrap();
     assert_eq!(pending_tasks.len(), 1);
-    assert_eq!(pending_tasks[0]["completed"].as_bool().unwrap(), false);
+    assert!(!pending_tasks[0]["completed"].as_bool().unwrap());
 
     // Test filter by all status
     let all_response = app
@@ -139,6 +184,8 @@ async fn test_filter_by_status() {
     let all_result: serde_json::Value = all_response.json().await;
     let all_tasks = all_result["data"].as_array().unwrap();
     assert_eq!(all_tasks.len(), 2);
+
+    app.app.cleanup().await;
 }
 
 #[tokio::test]
@@ -148,12 +195,39 @@ async fn test_combined_search_and_filter() {
     // Create test tasks with similar titles
     let task1_payload = CreateTaskPayload {
         title: "Learn Rust basics".to_string(),
+        description: None,
+        due_at: None,
+        start_at: None,
+        priority: Default::default(),
+        project_id: None,
+        parent_id: None,
+        auto_complete: false,
+        recurrence: None,
+        tags: Vec::new(),
     };
     let task2_payload = CreateTaskPayload {
         title: "Learn Rust advanced".to_string(),
+        description: None,
+        due_at: None,
+        start_at: None,
+        priority: Default::default(),
+        project_id: None,
+        parent_id: None,
+        auto_complete: false,
+        recurrence: None,
+        tags: Vec::new(),
     };
     let task3_payload = CreateTaskPayload {
         title: "Learn Python".to_string(),
+        description: None,
+        due_at: None,
+        start_at: None,
+        priority: Default::default(),
+        project_id: None,
+        parent_id: None,
+        auto_complete: false,
+        recurrence: None,
+        tags: Vec::new(),
     };
 
     // Create tasks
@@ -202,7 +276,7 @@ async fn test_combined_search_and_filter() {
     assert_eq!(filtered_tasks.len(), 1);
     assert!(filtered_tasks[0]["title"].as_str().unwrap().contains("Rust"));
     assert!(filtered_tasks[0]["title"].as_str().unwrap().contains("advanced"));
-    assert_eq!(filtered_tasks[0]["completed"].as_bool().unwrap(), false);
+    assert!(!filtered_tasks[0]["completed"].as_bool().unwrap());
 
     // Test combined search for "Rust" with status "completed"
     let completed_rust_response = app
@@ -215,7 +289,9 @@ async fn test_combined_search_and_filter() {
     let completed_rust_tasks = completed_rust_result["data"].as_array().unwrap();
     assert_eq!(completed_rust_tasks.len(), 1);
     assert!(completed_rust_tasks[0]["title"].as_str().unwrap().contains("basics"));
-    assert_eq!(completed_rust_tasks[0]["completed"].as_bool().unwrap(), true);
+    assert!(completed_rust_tasks[0]["completed"].as_bool().unwrap());
+
+    app.app.cleanup().await;
 }
 
 #[tokio::test]
@@ -226,6 +302,15 @@ async fn test_search_with_pagination() {
     for i in 1..=25 {
         let task_payload = CreateTaskPayload {
             title: format!("Search task {}", i),
+            description: None,
+            due_at: None,
+            start_at: None,
+            priority: Default::default(),
+            project_id: None,
+            parent_id: None,
+            auto_complete: false,
+            recurrence: None,
+            tags: Vec::new(),
         };
         let response = app
             .post("/tasks")
@@ -251,8 +336,8 @@ async fn test_search_with_pagination() {
     assert_eq!(pagination["page_size"].as_u64().unwrap(), 10);
     assert_eq!(pagination["total_items"].as_u64().unwrap(), 25);
     assert_eq!(pagination["total_pages"].as_u64().unwrap(), 3);
-    assert_eq!(pagination["has_next"].as_bool().unwrap(), true);
-    assert_eq!(pagination["has_previous"].as_bool().unwrap(), false);
+    assert!(pagination["has_next"].as_bool().unwrap());
+    assert!(!pagination["has_previous"].as_bool().unwrap());
 
     // Test second page
     let page2_response = app
@@ -267,6 +352,8 @@ async fn test_search_with_pagination() {
     
     let page2_pagination = &page2_result["pagination"];
     assert_eq!(page2_pagination["page"].as_u64().unwrap(), 2);
-    assert_eq!(page2_pagination["has_next"].as_bool().unwrap(), true);
-    assert_eq!(page2_pagination["has_previous"].as_bool().unwrap(), true);
-}
\ No newline at end of file
+    assert!(page2_pagination["has_next"].as_bool().unwrap());
+    assert!(page2_pagination["has_previous"].as_bool().unwrap());
+
+    app.app.cleanup().await;
+}
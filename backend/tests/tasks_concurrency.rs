@@ -1,10 +1,13 @@
 mod common;
 
+use backend::repository::ANONYMOUS_USER_ID;
 use common::spawn_app;
 use reqwest::StatusCode;
 use serde_json::json;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::Barrier;
+use uuid::Uuid;
 
 /// Test concurrent task creation to ensure no race conditions
 #[tokio::test]
@@ -68,6 +71,47 @@ async fn concurrent_task_creation_succeeds() {
     test_app.cleanup().await;
 }
 
+/// Appends lock nothing of the owner's: they are not held up by another task
+/// being inserted for the same user, and still take distinct positions
+#[tokio::test]
+async fn concurrent_appends_take_distinct_positions_without_waiting() {
+    let test_app = spawn_app().await;
+    let client = reqwest::Client::new();
+
+    // A task still being inserted, with a position no append takes
+    let mut transaction = test_app.db_pool.begin().await.unwrap();
+    sqlx::query("INSERT INTO tasks (id, title, user_id, position) VALUES (?, 'In flight', ?, '1')")
+        .bind(Uuid::now_v7())
+        .bind(ANONYMOUS_USER_ID)
+        .execute(&mut *transaction)
+        .await
+        .unwrap();
+
+    let appends = (0..10).map(|i| {
+        client
+            .post(format!("{}/tasks", test_app.address))
+            .json(&json!({ "title": format!("Append {}", i) }))
+            .send()
+    });
+    let responses = tokio::time::timeout(Duration::from_secs(10), futures::future::join_all(appends))
+        .await
+        .expect("Appends waited for the task being inserted");
+
+    let mut positions = Vec::new();
+    for response in responses {
+        let response = response.expect("Failed to execute request");
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let task: serde_json::Value = response.json().await.unwrap();
+        positions.push(task["position"].as_str().unwrap().to_string());
+    }
+    positions.sort();
+    positions.dedup();
+    assert_eq!(positions.len(), 10, "Appends took the same position");
+
+    transaction.rollback().await.unwrap();
+    test_app.cleanup().await;
+}
+
 /// Test concurrent updates to the same task
 #[tokio::test]
 async fn concurrent_task_updates_maintain_consistency() {
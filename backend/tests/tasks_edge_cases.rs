@@ -356,7 +356,7 @@ async fn boundary_value_analysis() {
                 "Returned title should not be longer than input"
             );
             assert!(
-                returned_title.len() > 0,
+                !returned_title.is_empty(),
                 "Returned title should not be empty"
             );
         }
@@ -55,11 +55,9 @@ async fn bulk_task_creation_performance() {
         // Execute batch concurrently
         let results = futures::future::join_all(batch_futures).await;
 
-        for result in results {
-            if let Ok(response) = result {
-                if response.status() == StatusCode::CREATED {
-                    successful_creates += 1;
-                }
+        for response in results.into_iter().flatten() {
+            if response.status() == StatusCode::CREATED {
+                successful_creates += 1;
             }
         }
     }
@@ -0,0 +1,107 @@
+use axum::{
+    body::{Body, Bytes},
+    extract::State,
+    http::{Request, StatusCode},
+    routing::{get, post},
+    Router,
+};
+use backend::telemetry::{self, Telemetry};
+use std::sync::{Arc, Mutex};
+use tower::ServiceExt;
+use tower_http::trace::TraceLayer;
+use tracing_subscriber::layer::SubscriberExt;
+
+const TRACE_ID: &str = "4bf92f3577b34da6a3ce929d0e0e4736";
+const PARENT_SPAN_ID: &str = "00f067aa0ba902b7";
+
+type Received = Arc<Mutex<Vec<Bytes>>>;
+
+// Stand-in for an OTLP/HTTP collector that records every export request body
+async fn spawn_collector() -> (String, Received) {
+    let received: Received = Arc::default();
+    let app = Router::new()
+        .route(
+            "/v1/traces",
+            post(|State(received): State<Received>, body: Bytes| async move {
+                received.lock().unwrap().push(body);
+                StatusCode::OK
+            }),
+        )
+        .with_state(received.clone());
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = format!("http://{}/v1/traces", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    (address, received)
+}
+
+#[tracing::instrument]
+async fn traced_work() -> &'static str {
+    "pong"
+}
+
+fn hex_to_bytes(hex: &str) -> Vec<u8> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn spans_continue_the_callers_trace_and_are_exported_over_otlp() {
+    let (endpoint, received) = spawn_collector().await;
+    std::env::set_var("OTEL_EXPORTER_OTLP_TRACES_ENDPOINT", &endpoint);
+    std::env::set_var("OTEL_SERVICE_NAME", "backend-test");
+
+    let telemetry = Telemetry::from_env();
+    assert!(telemetry.is_enabled());
+
+    let subscriber = tracing_subscriber::registry().with(telemetry.layer());
+    tracing::subscriber::set_global_default(subscriber).unwrap();
+
+    let app = Router::new()
+        .route("/ping", get(traced_work))
+        .layer(axum::middleware::from_fn(telemetry::propagate_trace_context))
+        .layer(TraceLayer::new_for_http().make_span_with(telemetry::make_request_span));
+
+    let response = app
+        .oneshot(
+            Request::get("/ping")
+                .header(
+                    "traceparent",
+                    format!("00-{}-{}-01", TRACE_ID, PARENT_SPAN_ID),
+                )
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // The response carries the same trace with the server's own span id
+    let traceparent = response.headers()["traceparent"].to_str().unwrap().to_string();
+    let parts: Vec<&str> = traceparent.split('-').collect();
+    assert_eq!(parts.len(), 4);
+    assert_eq!(parts[1], TRACE_ID);
+    assert_ne!(parts[2], PARENT_SPAN_ID);
+
+    // Flushing blocks on the exporter, so keep it off the async workers
+    let telemetry = tokio::task::spawn_blocking(move || {
+        telemetry.force_flush();
+        telemetry
+    })
+    .await
+    .unwrap();
+
+    let trace_id = hex_to_bytes(TRACE_ID);
+    let exports = received.lock().unwrap().clone();
+    assert!(!exports.is_empty(), "collector received no spans");
+    assert!(exports
+        .iter()
+        .any(|body| body.windows(trace_id.len()).any(|window| window == trace_id)));
+
+    tokio::task::spawn_blocking(move || telemetry.shutdown())
+        .await
+        .unwrap();
+}